@@ -0,0 +1,48 @@
+#!/bin/sh
+# Fetch the responses listed by `cargo scorecard export-requests` on a machine with
+# internet access, for `cargo scorecard import-responses` inside the air gap.
+#
+# Usage: fetch-responses.sh requests.json responses/
+# Needs curl, jq and sha256sum. Set GITHUB_TOKEN to lift GitHub's anonymous rate limit.
+#
+# responses/SHA256SUMS lets the import catch corrupted or truncated files. It travels
+# with the responses, so on its own it does not catch tampering: to detect that, carry
+# a copy over a separate trusted channel and pass it to
+# `cargo scorecard import-responses --checksums`.
+set -eu
+
+requests=$1
+out=$2
+mkdir -p "$out"
+cp "$requests" "$out/requests.json"
+
+fetch() {
+    case $2 in
+        https://api.github.com/*)
+            if [ -n "${GITHUB_TOKEN:-}" ]; then
+                curl -sSL -A cargo-scorecard/0.1.0 -H "Authorization: Bearer $GITHUB_TOKEN" \
+                    -o "$1" -w '%{http_code}' "$2"
+                return
+            fi
+            ;;
+    esac
+    curl -sSL -A cargo-scorecard/0.1.0 -o "$1" -w '%{http_code}' "$2"
+}
+
+jq -r '.requests[] | "\(.id) \(.url)"' "$requests" | while read -r id url; do
+    status=$(fetch "$out/$id.json" "$url" || echo 000)
+    case $status in
+        200) ;;
+        # A missing scorecard or repository is an answer too
+        404) echo null > "$out/$id.json" ;;
+        *)
+            rm -f "$out/$id.json"
+            echo "$url: HTTP $status" >&2
+            ;;
+    esac
+    # crates.io allows one request per second
+    case $url in https://crates.io/*) sleep 1 ;; esac
+done
+
+cd "$out"
+find . -maxdepth 1 -name '[0-9]*.json' | sed 's|^\./||' | sort | xargs sha256sum > SHA256SUMS
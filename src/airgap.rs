@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::cache::{Cache, CacheKind};
+use crate::deps::CrateInfo;
+use crate::fetch;
+use crate::field::{self, Field};
+use crate::github;
+use crate::repo::Repository;
+
+/// Version of the `export-requests` file layout.
+const REQUESTS_VERSION: u32 = 1;
+
+/// Checksums of the response files, in `sha256sum` format.
+pub const CHECKSUMS: &str = "SHA256SUMS";
+
+/// The request list written by `export-requests`.
+#[derive(Serialize, Deserialize)]
+pub struct Requests {
+    pub version: u32,
+    pub requests: Vec<Request>,
+}
+
+/// One URL an `--offline` run needs, and where its response goes in the cache.
+#[derive(Serialize, Deserialize)]
+pub struct Request {
+    /// Names the response file, `<id>.json`.
+    pub id: String,
+    /// The cache directory the response belongs in, e.g. `crates-io`.
+    pub cache: String,
+    pub key: String,
+    pub url: String,
+}
+
+impl Requests {
+    /// Everything missing from the cache for scoring `crates` and the project's own
+    /// `repository`, deduplicated.
+    ///
+    /// Scorecard URLs depend on the repository in each crate's crates.io response, so a
+    /// crate whose crates.io response isn't cached yet only asks for that, unless
+    /// `--repo-map` gives its repository; exporting again after importing it adds the rest.
+    pub fn missing(cache: &Cache, crates: &[CrateInfo], repository: Option<String>) -> Self {
+        let mut needed: BTreeMap<(CacheKind, String), String> = BTreeMap::new();
+        for crate_info in crates {
+            let repository = match (&crate_info.listed_repository, &crate_info.vendored) {
+                (Some(repository), _) => Some(repository.clone()),
+                (None, Some(vendored)) => vendored.repository.clone(),
+                (None, None) => match cache.get_any_age(CacheKind::Crate, &crate_info.name) {
+                    Some(json) => field::optional_str(&json, "crates.io", &["crate", "repository"])
+                        .ok()
+                        .flatten(),
+                    None => {
+                        needed.insert(
+                            (CacheKind::Crate, crate_info.name.clone()),
+                            fetch::crate_url(&crate_info.name),
+                        );
+                        None
+                    }
+                },
+            };
+            // A `--repo-map` repository is known without the crates.io response
+            let repository = crate_info.mapped_repository.clone().or(repository);
+            repository_requests(cache, repository.as_deref(), &mut needed);
+        }
+        repository_requests(cache, repository.as_deref(), &mut needed);
+
+        let requests = needed
+            .into_iter()
+            .enumerate()
+            .map(|(index, ((kind, key), url))| Request {
+                id: format!("{:05}", index + 1),
+                cache: kind.dir().to_string(),
+                key,
+                url,
+            })
+            .collect();
+        Requests {
+            version: REQUESTS_VERSION,
+            requests,
+        }
+    }
+
+    /// How many requests are for crates.io metadata, which can lead to more requests.
+    pub fn crates_io(&self) -> usize {
+        let dir = CacheKind::Crate.dir();
+        self.requests.iter().filter(|r| r.cache == dir).count()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let requests: Requests = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        if requests.version != REQUESTS_VERSION {
+            return Err(anyhow!(
+                "{} has version {}; this build reads version {}",
+                path.display(),
+                requests.version,
+                REQUESTS_VERSION
+            ));
+        }
+        Ok(requests)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).expect("requests always serialize");
+        std::fs::write(path, content + "\n")
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Add the scorecard request for a repository, or the rename lookup for one that was
+/// never scanned, unless the cache already has it.
+fn repository_requests(
+    cache: &Cache,
+    repository: Option<&str>,
+    needed: &mut BTreeMap<(CacheKind, String), String>,
+) {
+    let Some(repo) = repository
+        .and_then(Repository::parse)
+        .filter(Repository::is_scorecard_supported)
+    else {
+        return;
+    };
+    let key = repo.to_string();
+    match cache.get_any_age(CacheKind::Scorecard, &key) {
+        None => {
+            needed.insert((CacheKind::Scorecard, key), fetch::scorecard_url(&repo));
+        }
+        Some(Value::Null) if repo.host == "github.com" => {
+            match cache.get_any_age(CacheKind::GitHubCanonical, &key) {
+                None => {
+                    needed.insert(
+                        (CacheKind::GitHubCanonical, key),
+                        github::repo_api_url(&repo),
+                    );
+                }
+                Some(Value::String(canonical)) => {
+                    let canonical = format!("https://{}", canonical);
+                    repository_requests(cache, Some(&canonical), needed);
+                }
+                Some(_) => {}
+            }
+        }
+        Some(_) => {}
+    }
+}
+
+/// What `import-responses` did with each request.
+#[derive(Default)]
+pub struct Imported {
+    pub imported: usize,
+    /// URLs without a response file.
+    pub missing: Vec<String>,
+    /// URLs whose response was refused, with the reason.
+    pub rejected: Vec<(String, String)>,
+}
+
+/// Copy saved responses into the cache.
+///
+/// `dir` holds `<id>.json` for each request, and `checksums_path` a [`CHECKSUMS`] file
+/// covering them. A response is refused when its checksum doesn't match, it isn't JSON,
+/// or it describes another crate or repository than the one requested.
+///
+/// The checksums only prove the responses weren't tampered with when they reached this
+/// machine separately from the responses; otherwise they only catch corruption.
+pub fn import(
+    cache: &Cache,
+    dir: &Path,
+    checksums_path: &Path,
+    requests: &Requests,
+) -> Result<Imported> {
+    let checksums = std::fs::read_to_string(checksums_path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", checksums_path.display(), e))?;
+    let checksums: HashMap<&str, &str> = checksums
+        .lines()
+        .filter_map(|line| {
+            let (hash, file) = line.split_once(char::is_whitespace)?;
+            Some((file.trim_start().trim_start_matches('*'), hash))
+        })
+        .collect();
+
+    let mut imported = Imported::default();
+    for request in &requests.requests {
+        let file = format!("{}.json", request.id);
+        let Ok(content) = std::fs::read(dir.join(&file)) else {
+            imported.missing.push(request.url.clone());
+            continue;
+        };
+        match checked_response(request, &content, checksums.get(file.as_str()).copied()) {
+            Ok((kind, value)) => {
+                cache.put(kind, &request.key, &value);
+                imported.imported += 1;
+            }
+            Err(reason) => imported.rejected.push((request.url.clone(), reason)),
+        }
+    }
+    Ok(imported)
+}
+
+/// Validate one response, returning the value to cache or why it was refused.
+fn checked_response(
+    request: &Request,
+    content: &[u8],
+    checksum: Option<&str>,
+) -> Result<(CacheKind, Value), String> {
+    let digest: String = Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    match checksum {
+        None => return Err(format!("not listed in {}", CHECKSUMS)),
+        Some(expected) if !expected.eq_ignore_ascii_case(&digest) => {
+            return Err("checksum mismatch".to_string());
+        }
+        Some(_) => {}
+    }
+    let body: Value =
+        serde_json::from_slice(content).map_err(|e| format!("not a JSON response: {}", e))?;
+
+    let kind = CacheKind::ALL
+        .into_iter()
+        .find(|kind| kind.dir() == request.cache)
+        .ok_or_else(|| format!("unknown cache {}", request.cache))?;
+    let value = match kind {
+        CacheKind::Crate => match field::lookup(&body, &["crate", "name"]) {
+            Field::Value(name) if *name == *request.key => body,
+            Field::Value(name) => return Err(format!("response is for crate {}", name)),
+            _ => return Err("not a crates.io crate response".to_string()),
+        },
+        // `null` stands for a 404: the repository was never scanned
+        CacheKind::Scorecard => match field::lookup(&body, &["repo", "name"]) {
+            _ if body.is_null() => body,
+            Field::Value(name) if name.as_str() == Some(request.key.as_str()) => body,
+            Field::Value(name) => return Err(format!("response is for repository {}", name)),
+            _ => return Err("not a scorecard response".to_string()),
+        },
+        // Cached as the new location when the repository moved, `null` otherwise
+        CacheKind::GitHubCanonical => match field::lookup(&body, &["full_name"]) {
+            _ if body.is_null() => body,
+            Field::Value(Value::String(full_name)) => {
+                let canonical = Repository::parse(&format!("https://github.com/{}", full_name));
+                let requested = Repository::parse(&format!("https://{}", request.key));
+                match canonical {
+                    Some(canonical) if Some(&canonical) != requested.as_ref() => {
+                        Value::from(canonical.to_string())
+                    }
+                    _ => Value::Null,
+                }
+            }
+            _ => return Err("not a GitHub repository response".to_string()),
+        },
+        _ => return Err(format!("{} responses can't be imported", request.cache)),
+    };
+    Ok((kind, value))
+}
@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow};
+use semver::{Version, VersionReq};
+use tracing::debug;
+
+use crate::cache::CacheKind;
+use crate::deps::{BuildTime, CrateInfo};
+use crate::fetch::{self, Context};
+use crate::score::CrateScore;
+
+/// A crate to audit, written `name` or `name@<req>`.
+#[derive(Clone, Debug)]
+pub struct AuditSpec {
+    pub name: String,
+    pub req: VersionReq,
+}
+
+impl FromStr for AuditSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, req) = match s.split_once('@') {
+            Some((name, req)) => (
+                name,
+                VersionReq::parse(req)
+                    .map_err(|e| anyhow!("invalid version requirement in {}: {}", s, e))?,
+            ),
+            None => (s, VersionReq::STAR),
+        };
+        if name.is_empty() {
+            return Err(anyhow!("missing crate name in {}", s));
+        }
+        Ok(AuditSpec {
+            name: name.to_string(),
+            req,
+        })
+    }
+}
+
+/// Resolve a published crate's dependency closure from the crates.io API.
+///
+/// Each requirement resolves to the newest non-yanked version that satisfies it.
+/// Normal and build dependencies are followed for every target; optional
+/// dependencies are skipped, which matches default features only when those
+/// features enable no optional dependencies.
+pub async fn resolve(ctx: &Context, spec: &AuditSpec) -> Result<Vec<CrateInfo>> {
+    let root = newest_matching(ctx, &spec.name, &spec.req).await?;
+    let mut seen = HashSet::from([(spec.name.clone(), root.clone())]);
+    let mut crates = vec![CrateInfo {
+        name: spec.name.clone(),
+        version: format!("v{}", root),
+        direct: true,
+        kind: None,
+        vendored: None,
+        depth: Some(0),
+        introduced_by: None,
+        projects: Vec::new(),
+        build_time: BuildTime::default(),
+        unique_dependencies: None,
+        reach: None,
+        pinned_from: None,
+        listed_repository: None,
+        mapped_repository: None,
+        changed: None,
+        source: None,
+    }];
+
+    // Breadth first, so every crate is reported at its shallowest depth
+    let mut level = vec![(spec.name.clone(), root)];
+    let mut depth = 0;
+    while !level.is_empty() {
+        depth += 1;
+        let dependencies = futures::future::try_join_all(
+            level
+                .iter()
+                .map(|(name, version)| dependencies(ctx, name, version)),
+        )
+        .await?;
+
+        let mut next = Vec::new();
+        for ((parent, parent_version), dependencies) in level.iter().zip(dependencies) {
+            for (name, req) in dependencies {
+                let version = newest_matching(ctx, &name, &req).await?;
+                if !seen.insert((name.clone(), version.clone())) {
+                    continue;
+                }
+                crates.push(CrateInfo {
+                    name: name.clone(),
+                    version: format!("v{}", version),
+                    direct: depth == 1,
+                    kind: None,
+                    vendored: None,
+                    depth: Some(depth),
+                    introduced_by: Some(format!("{} v{}", parent, parent_version)),
+                    projects: Vec::new(),
+                    build_time: BuildTime::default(),
+                    unique_dependencies: None,
+                    reach: None,
+                    pinned_from: None,
+                    listed_repository: None,
+                    mapped_repository: None,
+                    changed: None,
+                    source: None,
+                });
+                next.push((name, version));
+            }
+        }
+        level = next;
+    }
+
+    crates.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    Ok(crates)
+}
+
+/// The newest published, non-yanked version of `name` matching `req`.
+pub async fn newest_matching(ctx: &Context, name: &str, req: &VersionReq) -> Result<String> {
+    let json = ctx
+        .cache
+        .get_or_fetch(CacheKind::Crate, name, || {
+            fetch::fetch_crate_metadata(&ctx.http, name)
+        })
+        .await?;
+
+    json["versions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|v| v["yanked"].as_bool() != Some(true))
+        .filter_map(|v| Version::parse(v["num"].as_str()?).ok())
+        .filter(|version| req.matches(version))
+        .max()
+        .map(|version| version.to_string())
+        .ok_or_else(|| anyhow!("No published version of {} matches {}", name, req))
+}
+
+/// Normal and build dependencies of one crate version, as `(name, requirement)`.
+async fn dependencies(
+    ctx: &Context,
+    name: &str,
+    version: &str,
+) -> Result<Vec<(String, VersionReq)>> {
+    let key = format!("{}@{}", name, version);
+    let json = ctx
+        .cache
+        .get_or_fetch(CacheKind::CrateDependencies, &key, || {
+            fetch::fetch_crate_dependencies(&ctx.http, name, version)
+        })
+        .await?;
+
+    // Several entries can name the same crate, e.g. per target; keep the first
+    let mut dependencies = BTreeMap::new();
+    for dependency in json["dependencies"].as_array().into_iter().flatten() {
+        if dependency["optional"].as_bool() == Some(true)
+            || dependency["kind"].as_str() == Some("dev")
+        {
+            continue;
+        }
+        let (Some(dep_name), Some(req)) =
+            (dependency["crate_id"].as_str(), dependency["req"].as_str())
+        else {
+            continue;
+        };
+        match VersionReq::parse(req) {
+            Ok(req) => {
+                dependencies.entry(dep_name.to_string()).or_insert(req);
+            }
+            Err(e) => debug!(crate.name = dep_name, req, error = %e, "skipping bad requirement"),
+        }
+    }
+    Ok(dependencies.into_iter().collect())
+}
+
+/// The chain of crates that pulled `name version` into an audited tree, root first.
+pub fn introduction_path(crates: &[CrateScore], crate_score: &CrateScore) -> Vec<String> {
+    let mut path = vec![format!("{} {}", crate_score.name, crate_score.version)];
+    let mut current = crate_score;
+    while let Some(parent) = &current.introduced_by {
+        let Some(next) = crates
+            .iter()
+            .find(|c| format!("{} {}", c.name, c.version) == *parent)
+        else {
+            break;
+        };
+        path.push(parent.clone());
+        current = next;
+    }
+    path.reverse();
+    path
+}
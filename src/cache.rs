@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde_json::{Value, json};
+use tracing::debug;
+
+/// The kinds of API responses kept in the cache, each in its own subdirectory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CacheKind {
+    /// crates.io crate metadata, keyed by crate name.
+    Crate,
+    /// crates.io dependency lists, keyed by `name@version`.
+    CrateDependencies,
+    /// Scorecard API results, keyed by `host/owner/repo`; `null` for unscanned repos.
+    Scorecard,
+    /// Canonical names of renamed GitHub repositories; `null` when not renamed.
+    GitHubCanonical,
+    /// HTTP status of the repository page of unscanned repos, keyed by `host/owner/repo`.
+    RepoStatus,
+    /// Forge metadata (archived, stars, last push), keyed by `host/owner/repo`.
+    RepoMetadata,
+    /// OSV.dev advisory query results, keyed by `name@version`.
+    Advisories,
+    /// Cargo.toml files read from repositories, keyed by `host/owner/repo/path`; `null`
+    /// when the file doesn't exist.
+    RepoManifest,
+    /// Crates cargo resolved, keyed by a hash of the manifests and their Cargo.lock.
+    Resolved,
+}
+
+impl CacheKind {
+    /// Every kind, in the order `--stats` lists them.
+    pub const ALL: [CacheKind; 9] = [
+        CacheKind::Crate,
+        CacheKind::CrateDependencies,
+        CacheKind::Scorecard,
+        CacheKind::GitHubCanonical,
+        CacheKind::RepoStatus,
+        CacheKind::RepoMetadata,
+        CacheKind::Advisories,
+        CacheKind::RepoManifest,
+        CacheKind::Resolved,
+    ];
+
+    pub fn dir(self) -> &'static str {
+        match self {
+            CacheKind::Crate => "crates-io",
+            CacheKind::CrateDependencies => "crates-io-deps",
+            CacheKind::Scorecard => "scorecard",
+            CacheKind::GitHubCanonical => "github-canonical",
+            CacheKind::RepoStatus => "repo-status",
+            CacheKind::RepoMetadata => "repo-metadata",
+            CacheKind::Advisories => "osv",
+            CacheKind::RepoManifest => "repo-manifest",
+            CacheKind::Resolved => "resolved",
+        }
+    }
+}
+
+/// Returned when `--offline` is set and a lookup is not in the cache.
+#[derive(Debug)]
+pub struct Uncached;
+
+impl fmt::Display for Uncached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not in the cache and running offline")
+    }
+}
+
+impl std::error::Error for Uncached {}
+
+/// Cache of raw API responses, kept in memory for the run and on disk across runs.
+pub struct Cache {
+    /// Disk location, or `None` when disk caching is disabled.
+    dir: Option<PathBuf>,
+    ttl: Duration,
+    /// TTL of negative entries: `null` scorecard results for repositories not scanned yet.
+    negative_ttl: Duration,
+    offline: bool,
+    memory: Mutex<HashMap<(CacheKind, String), Value>>,
+    /// Hits and misses per kind, for `--stats`.
+    counters: Mutex<HashMap<CacheKind, (u64, u64)>>,
+}
+
+impl Cache {
+    /// `negative_ttl` never makes negative entries outlive `ttl`.
+    pub fn new(dir: Option<PathBuf>, ttl: Duration, negative_ttl: Duration, offline: bool) -> Self {
+        Cache {
+            dir,
+            ttl,
+            negative_ttl: negative_ttl.min(ttl),
+            offline,
+            memory: Mutex::new(HashMap::new()),
+            counters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Default disk location: `$XDG_CACHE_HOME/cargo-scorecard` or `~/.cache/cargo-scorecard`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        Some(base.join("cargo-scorecard"))
+    }
+
+    /// Return the cached value for `key`, or run `fetch` and cache its result.
+    ///
+    /// Offline, expired entries are still served and misses fail with [`Uncached`].
+    pub async fn get_or_fetch<F, Fut>(&self, kind: CacheKind, key: &str, fetch: F) -> Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        if let Some(value) = self.get(kind, key) {
+            return Ok(value);
+        }
+        if self.offline {
+            return Err(Uncached.into());
+        }
+
+        let value = fetch().await?;
+        self.put(kind, key, &value);
+        Ok(value)
+    }
+
+    /// Whether entries are kept on disk, rather than only for this run.
+    pub fn is_persistent(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Look up a cached value without fetching it.
+    pub fn get(&self, kind: CacheKind, key: &str) -> Option<Value> {
+        self.lookup(kind, key, !self.offline)
+    }
+
+    /// Look up a cached value regardless of its age, for callers that judge freshness
+    /// themselves.
+    pub fn get_any_age(&self, kind: CacheKind, key: &str) -> Option<Value> {
+        self.lookup(kind, key, false)
+    }
+
+    /// Look up a cached value regardless of its age without counting a hit or miss, for
+    /// callers that revalidate it and then [`count`](Self::count) the outcome once.
+    pub fn peek(&self, kind: CacheKind, key: &str) -> Option<Value> {
+        self.read(kind, key, false)
+    }
+
+    /// Count a hit or a miss for `kind` in `--stats`.
+    pub fn count(&self, kind: CacheKind, hit: bool) {
+        let mut counters = self.counters.lock().unwrap();
+        let (hits, misses) = counters.entry(kind).or_default();
+        if hit {
+            *hits += 1;
+        } else {
+            *misses += 1;
+        }
+    }
+
+    /// Hits and misses so far for `kind`.
+    pub fn counters(&self, kind: CacheKind) -> (u64, u64) {
+        self.counters
+            .lock()
+            .unwrap()
+            .get(&kind)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn lookup(&self, kind: CacheKind, key: &str, check_ttl: bool) -> Option<Value> {
+        let value = self.read(kind, key, check_ttl);
+        self.count(kind, value.is_some());
+        value
+    }
+
+    fn read(&self, kind: CacheKind, key: &str, check_ttl: bool) -> Option<Value> {
+        let memory_key = (kind, key.to_string());
+        if let Some(value) = self.memory.lock().unwrap().get(&memory_key) {
+            return Some(value.clone());
+        }
+
+        let path = self.path(kind, key)?;
+        let content = std::fs::read_to_string(&path).ok()?;
+        // A truncated or corrupt entry is a miss; the next put replaces it
+        let Some((fetched_at, value)) =
+            serde_json::from_str::<Value>(&content)
+                .ok()
+                .and_then(|mut entry| {
+                    let fetched_at = entry["fetched_at"].as_u64()?;
+                    Some((fetched_at, entry.get_mut("body")?.take()))
+                })
+        else {
+            debug!(path = %path.display(), "ignoring corrupt cache entry");
+            return None;
+        };
+        let fetched_at = Duration::from_secs(fetched_at);
+        let expired = now().saturating_sub(fetched_at) > self.ttl(kind, &value);
+        if check_ttl && expired {
+            debug!(path = %path.display(), "cache entry expired");
+            return None;
+        }
+
+        // Keep stale entries out of memory so a later TTL-checked lookup still misses
+        if !expired || self.offline {
+            self.memory
+                .lock()
+                .unwrap()
+                .insert(memory_key, value.clone());
+        }
+        Some(value)
+    }
+
+    /// How long an entry stays current. A repository that isn't scanned today may be next
+    /// week, so its negative entry expires sooner than a score does.
+    fn ttl(&self, kind: CacheKind, value: &Value) -> Duration {
+        match (kind, value) {
+            (CacheKind::Scorecard, Value::Null) => self.negative_ttl,
+            _ => self.ttl,
+        }
+    }
+
+    pub fn put(&self, kind: CacheKind, key: &str, value: &Value) {
+        self.memory
+            .lock()
+            .unwrap()
+            .insert((kind, key.to_string()), value.clone());
+
+        let Some(path) = self.path(kind, key) else {
+            return;
+        };
+        let entry = json!({ "fetched_at": now().as_secs(), "body": value });
+        if let Err(e) = write_atomically(&path, entry.to_string().as_bytes()) {
+            debug!(path = %path.display(), error = %e, "failed to write cache entry");
+        }
+    }
+
+    /// Drop a cached value, e.g. one that turned out to be a transient response.
+    pub fn remove(&self, kind: CacheKind, key: &str) {
+        self.memory.lock().unwrap().remove(&(kind, key.to_string()));
+
+        let Some(path) = self.path(kind, key) else {
+            return;
+        };
+        if let Err(e) = std::fs::remove_file(&path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            debug!(path = %path.display(), error = %e, "failed to remove cache entry");
+        }
+    }
+
+    fn path(&self, kind: CacheKind, key: &str) -> Option<PathBuf> {
+        let file_name: String = key
+            .bytes()
+            .map(|b| match b {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                    (b as char).to_string()
+                }
+                _ => format!("%{:02X}", b),
+            })
+            .collect();
+        Some(
+            self.dir
+                .as_ref()?
+                .join(kind.dir())
+                .join(format!("{}.json", file_name)),
+        )
+    }
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it into place, so
+/// concurrent readers see either the old entry or the new one, never half of one.
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp = PathBuf::from(temp);
+    std::fs::write(&temp, contents)
+        .and_then(|_| std::fs::rename(&temp, path))
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp);
+        })
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// A fresh cache directory under the system temp dir, removed by the caller.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-scorecard-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn recovers_from_a_truncated_entry() {
+        let dir = temp_dir("truncated");
+        let key = "github.com/owner/repo";
+        let cache = Cache::new(Some(dir.clone()), DAY, DAY, false);
+        cache.put(CacheKind::Scorecard, key, &json!({"score": 7.5}));
+
+        // As left behind by a writer killed halfway through the file
+        let path = cache.path(CacheKind::Scorecard, key).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &content[..content.len() / 2]).unwrap();
+
+        let cache = Cache::new(Some(dir.clone()), DAY, DAY, false);
+        assert_eq!(cache.get(CacheKind::Scorecard, key), None);
+        let value =
+            futures::executor::block_on(cache.get_or_fetch(CacheKind::Scorecard, key, || async {
+                Ok(json!({"score": 8.0}))
+            }))
+            .unwrap();
+        assert_eq!(value, json!({"score": 8.0}));
+
+        // The refetched value replaced the broken file
+        let cache = Cache::new(Some(dir.clone()), DAY, DAY, false);
+        assert_eq!(
+            cache.get(CacheKind::Scorecard, key),
+            Some(json!({"score": 8.0}))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn an_entry_without_a_body_is_a_miss() {
+        let dir = temp_dir("bodiless");
+        let cache = Cache::new(Some(dir.clone()), DAY, DAY, false);
+        let path = cache.path(CacheKind::Crate, "serde").unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, r#"{"fetched_at": 1}"#).unwrap();
+
+        assert_eq!(cache.get_any_age(CacheKind::Crate, "serde"), None);
+        assert_eq!(cache.counters(CacheKind::Crate), (0, 1));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn peeking_counts_nothing() {
+        let cache = Cache::new(None, DAY, DAY, false);
+        assert_eq!(cache.peek(CacheKind::Scorecard, "github.com/o/r"), None);
+        cache.put(
+            CacheKind::Scorecard,
+            "github.com/o/r",
+            &json!({"score": 5.0}),
+        );
+        assert!(cache.peek(CacheKind::Scorecard, "github.com/o/r").is_some());
+        assert_eq!(cache.counters(CacheKind::Scorecard), (0, 0));
+
+        cache.count(CacheKind::Scorecard, true);
+        assert!(cache.get(CacheKind::Scorecard, "github.com/o/r").is_some());
+        assert_eq!(cache.counters(CacheKind::Scorecard), (2, 0));
+    }
+}
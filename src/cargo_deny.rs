@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::policy::CrateSpec;
+
+/// File name cargo-deny reads by default.
+pub const DENY_TOML: &str = "deny.toml";
+
+/// The parts of a cargo-deny configuration we understand; everything else is ignored.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct DenyToml {
+    bans: Bans,
+    advisories: Advisories,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Bans {
+    skip: Vec<Entry>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Advisories {
+    ignore: Vec<Entry>,
+}
+
+/// A list entry, in any of the shapes cargo-deny has accepted over time.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    /// `"name"`, `"name@version"` or an advisory ID.
+    Plain(String),
+    /// `{ crate = "name@version", reason = "..." }` or `{ id = "RUSTSEC-...", ... }`.
+    Crate {
+        #[serde(rename = "crate")]
+        spec: String,
+    },
+    Advisory {
+        id: String,
+    },
+    /// `{ name = "name", version = "<req>" }`, the older form.
+    Named {
+        name: String,
+        version: Option<String>,
+    },
+}
+
+/// What the `[bans] skip` and `[advisories] ignore` lists of a deny.toml exempt.
+#[derive(Default)]
+pub struct Waivers {
+    pub crates: Vec<CrateSpec>,
+    /// Advisory IDs, e.g. `RUSTSEC-2023-0001`, kept out of `--fail-on-advisory`.
+    pub advisories: Vec<String>,
+}
+
+/// Read the waivers of a deny.toml. Advisory IDs waive just that advisory; crate
+/// entries in either list waive the crate.
+pub fn waivers(path: &Path) -> Result<Waivers> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let deny: DenyToml = toml::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+    let mut waivers = Waivers::default();
+    for entry in deny.bans.skip.into_iter().chain(deny.advisories.ignore) {
+        let spec = match entry {
+            Entry::Plain(spec) | Entry::Crate { spec } | Entry::Advisory { id: spec } => spec,
+            Entry::Named {
+                name,
+                version: Some(version),
+            } => format!("{}@{}", name, version),
+            Entry::Named {
+                name,
+                version: None,
+            } => name,
+        };
+        if is_advisory_id(&spec) {
+            waivers.advisories.push(spec);
+            continue;
+        }
+        match parse_spec(&spec) {
+            Ok(spec) => waivers.crates.push(spec),
+            Err(e) => debug!(path = %path.display(), error = %e, "skipping deny.toml entry"),
+        }
+    }
+    Ok(waivers)
+}
+
+/// cargo-deny versions after `@` are exact unless they carry an operator.
+fn parse_spec(spec: &str) -> Result<CrateSpec> {
+    match spec.split_once('@') {
+        Some((name, version)) if semver::Version::parse(version).is_ok() => {
+            format!("{}@={}", name, version).parse()
+        }
+        _ => spec.parse(),
+    }
+}
+
+/// RUSTSEC, GHSA and CVE style identifiers, as opposed to crate names.
+fn is_advisory_id(id: &str) -> bool {
+    ["RUSTSEC-", "GHSA-", "CVE-"]
+        .iter()
+        .any(|prefix| id.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_crate_and_advisory_waivers() {
+        let path = std::env::temp_dir().join(format!("deny-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+[advisories]
+ignore = [
+    "RUSTSEC-2020-0071",
+    { id = "GHSA-xxxx-yyyy-zzzz", reason = "not reachable" },
+    "atty",
+]
+
+[bans]
+skip = [
+    { crate = "windows-sys@0.48.0", reason = "old tokio" },
+    { name = "syn", version = "<2" },
+]
+"#,
+        )
+        .unwrap();
+        let waivers = waivers(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            waivers.advisories,
+            ["RUSTSEC-2020-0071", "GHSA-xxxx-yyyy-zzzz"]
+        );
+        let crates: Vec<String> = waivers.crates.iter().map(ToString::to_string).collect();
+        assert_eq!(crates, ["windows-sys@=0.48.0", "syn@<2", "atty"]);
+    }
+}
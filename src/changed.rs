@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Result, anyhow};
+use schemars::JsonSchema;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::deps::CrateInfo;
+
+/// How a crate's version came to differ from the `--changed-since` lockfile.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase", tag = "kind")]
+pub enum LockChange {
+    /// No version of the crate was locked before.
+    Added,
+    /// Replaces an older locked version.
+    Upgraded { from: String },
+    /// Replaces a newer locked version.
+    Downgraded { from: String },
+}
+
+impl fmt::Display for LockChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockChange::Added => f.write_str("added"),
+            LockChange::Upgraded { from } => write!(f, "upgraded from {}", from),
+            LockChange::Downgraded { from } => write!(f, "downgraded from {}", from),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Lockfile {
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
+/// Keep only the crates whose version is not in `lockfile` as of `git_ref`, marking each
+/// as added, upgraded or downgraded.
+pub fn retain_changed(crates: &mut Vec<CrateInfo>, lockfile: &Path, git_ref: &str) -> Result<()> {
+    let base = match base_lockfile(lockfile, git_ref)? {
+        Some(content) => locked_versions(&content)
+            .map_err(|e| anyhow!("Failed to parse Cargo.lock at {}: {}", git_ref, e))?,
+        None => {
+            warn!(
+                "There is no {} at {}, so every crate counts as added",
+                lockfile.display(),
+                git_ref
+            );
+            BTreeMap::new()
+        }
+    };
+    crates.retain_mut(|c| {
+        let version = c.version.trim_start_matches('v');
+        let Some(before) = base.get(&c.name) else {
+            c.changed = Some(LockChange::Added);
+            return true;
+        };
+        if before.iter().any(|locked| locked == version) {
+            return false;
+        }
+        c.changed = Some(change(version, before));
+        true
+    });
+    Ok(())
+}
+
+/// Compare `version` with the versions locked before: against the newest older one if
+/// there is one, otherwise against the oldest newer one.
+fn change(version: &str, before: &[String]) -> LockChange {
+    let parsed = |v: &str| Version::parse(v).ok();
+    let Some(current) = parsed(version) else {
+        return LockChange::Upgraded {
+            from: before.join(", "),
+        };
+    };
+    let mut versions: Vec<(Version, &String)> = before
+        .iter()
+        .filter_map(|v| Some((parsed(v)?, v)))
+        .collect();
+    versions.sort();
+    let older = versions.iter().rev().find(|(v, _)| *v < current);
+    match older {
+        Some((_, from)) => LockChange::Upgraded {
+            from: from.to_string(),
+        },
+        None => LockChange::Downgraded {
+            from: versions
+                .first()
+                .map_or_else(|| before.join(", "), |(_, from)| from.to_string()),
+        },
+    }
+}
+
+/// Locked versions by crate name.
+fn locked_versions(content: &str) -> Result<BTreeMap<String, Vec<String>>, toml::de::Error> {
+    let lockfile: Lockfile = toml::from_str(content)?;
+    let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for package in lockfile.package {
+        versions
+            .entry(package.name)
+            .or_default()
+            .push(package.version);
+    }
+    Ok(versions)
+}
+
+/// `lockfile` as committed at `git_ref`, read with `git show`; `None` when the ref has
+/// no such file.
+fn base_lockfile(lockfile: &Path, git_ref: &str) -> Result<Option<String>> {
+    let dir = match lockfile.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .map_err(|e| anyhow!("Failed to run git: {}", e))
+    };
+
+    let commit = format!("{}^{{commit}}", git_ref);
+    let verify = git(&["rev-parse", "--verify", "--quiet", &commit])?;
+    if !verify.status.success() {
+        return Err(anyhow!(
+            "--changed-since {}: not a commit in the repository at {}",
+            git_ref,
+            dir.display()
+        ));
+    }
+
+    // `./` makes the path relative to `dir` rather than the repository root
+    let object = format!("{}:./Cargo.lock", git_ref);
+    let output = git(&["show", &object])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    String::from_utf8(output.stdout)
+        .map(Some)
+        .map_err(|e| anyhow!("Cargo.lock at {} is not UTF-8: {}", git_ref, e))
+}
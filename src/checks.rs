@@ -0,0 +1,127 @@
+/// Checks scoring below this are explained.
+pub const EXPLAIN_BELOW: f64 = 7.0;
+
+/// Where upstream documents every check, one anchor per check.
+const CHECKS_DOC: &str = "https://github.com/ossf/scorecard/blob/main/docs/checks.md";
+
+/// What a check measures and how a project usually improves it.
+pub struct CheckHint {
+    pub name: &'static str,
+    pub measures: &'static str,
+    pub remediation: &'static str,
+}
+
+const HINTS: &[CheckHint] = &[
+    CheckHint {
+        name: "Binary-Artifacts",
+        measures: "whether the repository contains checked-in binaries",
+        remediation: "remove generated binaries and build them from source in CI",
+    },
+    CheckHint {
+        name: "Branch-Protection",
+        measures: "whether the default and release branches are protected",
+        remediation: "enable branch protection with required reviews and status checks",
+    },
+    CheckHint {
+        name: "CI-Tests",
+        measures: "whether pull requests run CI tests before merging",
+        remediation: "run the test suite on every pull request",
+    },
+    CheckHint {
+        name: "CII-Best-Practices",
+        measures: "whether the project has an OpenSSF Best Practices badge",
+        remediation: "apply for the OpenSSF Best Practices badge",
+    },
+    CheckHint {
+        name: "Code-Review",
+        measures: "whether changes are reviewed before they are merged",
+        remediation: "require at least one approving review on every pull request",
+    },
+    CheckHint {
+        name: "Contributors",
+        measures: "whether contributors come from several organizations",
+        remediation: "grow a maintainer base beyond a single organization",
+    },
+    CheckHint {
+        name: "Dangerous-Workflow",
+        measures: "whether CI workflows use dangerous patterns such as untrusted checkouts",
+        remediation: "avoid pull_request_target with untrusted code and script injection",
+    },
+    CheckHint {
+        name: "Dependency-Update-Tool",
+        measures: "whether a tool such as Dependabot or Renovate keeps dependencies current",
+        remediation: "enable Dependabot or Renovate",
+    },
+    CheckHint {
+        name: "Fuzzing",
+        measures: "whether the project is fuzzed, e.g. by OSS-Fuzz or cargo-fuzz",
+        remediation: "add fuzz targets and run them continuously",
+    },
+    CheckHint {
+        name: "License",
+        measures: "whether the project declares a license",
+        remediation: "add a LICENSE file at the repository root",
+    },
+    CheckHint {
+        name: "Maintained",
+        measures: "whether the project had commits and issue activity in the last 90 days",
+        remediation: "keep merging changes and triaging issues, or mark the project archived",
+    },
+    CheckHint {
+        name: "Packaging",
+        measures: "whether releases are published from CI",
+        remediation: "publish releases from a CI workflow",
+    },
+    CheckHint {
+        name: "Pinned-Dependencies",
+        measures: "whether build and CI dependencies are pinned by hash",
+        remediation: "pin GitHub Actions and container images to full commit or digest hashes",
+    },
+    CheckHint {
+        name: "SAST",
+        measures: "whether static analysis runs on changes",
+        remediation: "run CodeQL or another static analyzer on every pull request",
+    },
+    CheckHint {
+        name: "SBOM",
+        measures: "whether releases ship a software bill of materials",
+        remediation: "generate an SBOM and attach it to each release",
+    },
+    CheckHint {
+        name: "Security-Policy",
+        measures: "whether the project explains how to report vulnerabilities",
+        remediation: "add a SECURITY.md with a private reporting channel",
+    },
+    CheckHint {
+        name: "Signed-Releases",
+        measures: "whether release artifacts are signed or have provenance",
+        remediation: "sign release artifacts or publish SLSA provenance",
+    },
+    CheckHint {
+        name: "Token-Permissions",
+        measures: "whether CI workflow tokens default to read-only permissions",
+        remediation: "set `permissions: read-all` at the top of each workflow and widen per job",
+    },
+    CheckHint {
+        name: "Vulnerabilities",
+        measures: "whether the project has open, unfixed vulnerabilities",
+        remediation: "fix or upgrade past the reported vulnerabilities",
+    },
+    CheckHint {
+        name: "Webhooks",
+        measures: "whether repository webhooks are protected by a secret",
+        remediation: "configure a secret on every webhook",
+    },
+];
+
+/// The built-in hint for a check, or `None` for checks newer than this table.
+pub fn hint(name: &str) -> Option<&'static CheckHint> {
+    HINTS
+        .iter()
+        .find(|hint| hint.name.eq_ignore_ascii_case(name))
+}
+
+/// Upstream documentation for a check.
+pub fn documentation_url(name: &str) -> String {
+    format!("{}#{}", CHECKS_DOC, name.to_ascii_lowercase())
+}
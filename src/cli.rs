@@ -0,0 +1,996 @@
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
+use clap::error::ErrorKind;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+
+use crate::audit::AuditSpec;
+use crate::cache::Cache;
+use crate::config::CONFIG_FILE;
+use crate::coverage::UnsupportedForges;
+use crate::deps::{self, CargoOptions, DepKind, Pin};
+use crate::policy::{CrateSpec, Scope};
+use crate::provider::Provider;
+use crate::scorecard::{CheckRequirement, CheckWeight};
+
+/// Generate the OpenSSF Scorecard for the dependencies in a Rust repo.
+#[derive(Parser, Debug)]
+#[command(name = "cargo-scorecard", bin_name = "cargo scorecard", version, about)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to the Cargo.toml of the project to score; repeat it, or give a directory
+    /// to scan for manifests, to score several projects in one report
+    #[arg(long, global = true, value_name = "PATH")]
+    pub manifest_path: Vec<PathBuf>,
+
+    /// Report format [default: table on a terminal, markdown otherwise]; repeat it, or
+    /// list several, with an --output after each one that shouldn't go to stdout
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub format: Vec<OutputFormat>,
+
+    /// Write the report in the --format given just before this to a file
+    #[arg(long, global = true, value_name = "PATH")]
+    pub output: Vec<PathBuf>,
+
+    /// Also write the report in FORMAT to PATH, e.g. `json:report.json`, besides the
+    /// --format printed to stdout (repeatable)
+    #[arg(long, global = true, value_name = "FORMAT:PATH", value_parser = parse_also_write)]
+    pub also_write: Vec<Output>,
+
+    /// Each --format with its --output, filled in when parsing.
+    #[arg(skip)]
+    pub outputs: Vec<Output>,
+
+    /// Print one line per crate from this template instead of a report, e.g.
+    /// "{name} {version} {score}"; an unknown placeholder lists the available ones
+    #[arg(
+        long,
+        global = true,
+        value_name = "TEMPLATE",
+        conflicts_with = "format"
+    )]
+    pub template: Option<String>,
+
+    /// Text for template placeholders the crate has no value for
+    #[arg(long, global = true, value_name = "TEXT", default_value = "-")]
+    pub template_missing: String,
+
+    /// Also score the repositories in this file, one URL per line (`#` starts a
+    /// comment), as rows named `owner/repo`; without a Cargo.toml, only those
+    #[arg(long, global = true, value_name = "PATH")]
+    pub repos_file: Option<PathBuf>,
+
+    /// Use the repositories in this TOML (or `.json`) file, mapping crate names to
+    /// repository URLs, over what crates.io says
+    #[arg(long, global = true, value_name = "PATH")]
+    pub repo_map: Option<PathBuf>,
+
+    /// Score the crates in this `cargo vendor` directory without running cargo
+    #[arg(long, global = true, value_name = "DIR")]
+    pub vendor_dir: Option<PathBuf>,
+
+    /// Number of decimal places used when printing scores
+    #[arg(long, global = true, value_name = "N", default_value_t = 1)]
+    pub score_precision: usize,
+
+    /// Scale used when printing scores (the OpenSSF scale is 0-10)
+    #[arg(long, global = true, value_name = "SCALE", value_enum, default_value_t = ScoreScale::Ten)]
+    pub score_scale: ScoreScale,
+
+    /// GitHub token used to resolve renamed repositories through the GitHub API
+    #[arg(
+        long,
+        global = true,
+        value_name = "TOKEN",
+        env = "GITHUB_TOKEN",
+        hide_env_values = true
+    )]
+    pub github_token: Option<String>,
+
+    /// Configuration file [default: .cargo-scorecard.toml if present]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Directory for cached API responses [default: ~/.cache/cargo-scorecard]
+    #[arg(long, global = true, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Do not read or write the on-disk cache
+    #[arg(long, global = true, conflicts_with = "offline")]
+    pub no_cache: bool,
+
+    /// Hours before a cached API response is fetched again
+    #[arg(long, global = true, value_name = "HOURS", default_value_t = 24)]
+    pub cache_ttl: u64,
+
+    /// Hours before a cached "never scanned" scorecard answer is checked again; capped
+    /// at --cache-ttl
+    #[arg(long, global = true, value_name = "HOURS", default_value_t = 6)]
+    pub negative_cache_ttl: u64,
+
+    /// Never touch the network; serve every lookup from the cache
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Pass --locked to cargo so resolution must match Cargo.lock exactly
+    #[arg(long, global = true)]
+    pub locked: bool,
+
+    /// Pass --frozen to cargo: --locked, and cargo itself stays offline
+    #[arg(long, global = true)]
+    pub frozen: bool,
+
+    /// Don't warn when Cargo.lock is out of date with Cargo.toml
+    #[arg(long, global = true)]
+    pub no_lock_check: bool,
+
+    /// Add a per-check breakdown for every scored crate
+    #[arg(long, global = true)]
+    pub detailed: bool,
+
+    /// Explain every check scoring below 7 and how to improve it
+    #[arg(long, global = true)]
+    pub explain: bool,
+
+    /// Fail when crates have no repository metadata; workspace and path crates are
+    /// exempt [default scope: all]
+    #[arg(
+        long,
+        global = true,
+        value_name = "SCOPE",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "all"
+    )]
+    pub fail_on_no_repository: Option<Scope>,
+
+    /// Skip checking whether repositories without a score exist, leaving bad
+    /// repository metadata indistinguishable from repositories never scanned
+    #[arg(long, global = true)]
+    pub no_verify_repos: bool,
+
+    /// Check that each GitHub or GitLab repository has a Cargo.toml for the crate
+    /// claiming it, marking the scores of those that don't as suspect
+    #[arg(long, global = true)]
+    pub verify_provenance: bool,
+
+    /// Fail when a crate's repository has no Cargo.toml for it. Implies
+    /// --verify-provenance
+    #[arg(long, global = true)]
+    pub fail_on_unverified: bool,
+
+    /// Fail when a repository is unreachable (checked even with --no-verify-repos)
+    #[arg(long, global = true)]
+    pub fail_on_dead_repo: bool,
+
+    /// Waive crates listed in cargo-deny's `[bans] skip` and `[advisories] ignore`, and
+    /// the advisories `[advisories] ignore` lists by ID [default: deny.toml next to the
+    /// manifest]
+    #[arg(long, global = true, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    pub deny_toml: Option<Option<PathBuf>>,
+
+    /// Leave out the project's own repository score above the dependency table
+    #[arg(long, global = true)]
+    pub no_self: bool,
+
+    /// Ignore `trusted_orgs` from the config and apply the policy to every crate
+    #[arg(long, global = true)]
+    pub no_trust: bool,
+
+    /// Print an overall A-F grade for the tree (cutoffs A 8.0, B 6.5, C 5.0, D 3.5;
+    /// configurable under `[grade]`)
+    #[arg(long, global = true)]
+    pub grade: bool,
+
+    /// Also show a custom score: the mean of the checks weighted as given, e.g.
+    /// `Code-Review=3,Fuzzing=0` (unlisted checks weigh 1, checks without data are
+    /// left out). The official score is kept as is and still drives the policy
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME=WEIGHT,...",
+        value_delimiter = ','
+    )]
+    pub check_weights: Vec<CheckWeight>,
+
+    /// Fail when a crate scores below this on the OpenSSF 0-10 scale
+    #[arg(long, global = true, value_name = "SCORE")]
+    pub fail_under: Option<f64>,
+
+    /// Fail when a scored crate's check scores below the value, e.g.
+    /// `Branch-Protection>=5`; repeatable
+    #[arg(long, global = true, value_name = "CHECK>=MIN")]
+    pub require_check: Vec<CheckRequirement>,
+
+    /// Which crates --require-check applies to
+    #[arg(long, global = true, value_name = "SCOPE", value_enum, default_value_t = Scope::All)]
+    pub require_check_scope: Scope,
+
+    /// Whether a crate whose required check has no data fails --require-check
+    #[arg(long, global = true, value_name = "POLICY", value_enum, default_value_t = PartialPolicy::Fail)]
+    pub on_missing_check: PartialPolicy,
+
+    /// Like --fail-under, for crates with a build script or proc-macro; the stricter
+    /// of the two applies to them
+    #[arg(long, global = true, value_name = "SCORE")]
+    pub fail_under_build_time: Option<f64>,
+
+    /// Only report direct dependencies, with how many crates each one alone pulls in
+    #[arg(long, global = true)]
+    pub direct_only: bool,
+
+    /// Only report crates that run code at build time (build scripts and proc-macros)
+    #[arg(long, global = true)]
+    pub only_build_time: bool,
+
+    /// Only report this crate, optionally limited to matching versions (e.g.
+    /// `openssl@<0.10`); repeatable. A crate that isn't in the tree is an error
+    #[arg(long, global = true, value_name = "CRATE[@REQ]")]
+    pub only: Vec<CrateSpec>,
+
+    /// Only report crates added, upgraded or downgraded since this git ref, by diffing
+    /// Cargo.lock against the one committed there (e.g. `origin/main` on a PR)
+    #[arg(long, global = true, value_name = "REF")]
+    pub changed_since: Option<String>,
+
+    /// Compare against a previous `--format json` report and list score changes
+    #[arg(long, global = true, value_name = "PATH")]
+    pub baseline: Option<PathBuf>,
+
+    /// Fail when a crate scores lower than in the --baseline report
+    #[arg(long, global = true, requires = "baseline")]
+    pub fail_on_regression: bool,
+
+    /// Limit --fail-on-regression to crates pulled in by this kind of dependency;
+    /// repeatable [default: all kinds]
+    #[arg(long, global = true, value_name = "KIND", value_enum)]
+    pub kind: Vec<DepKind>,
+
+    /// Fail when the version in use was published fewer than this many days ago
+    #[arg(long, global = true, value_name = "DAYS")]
+    pub min_version_age: Option<u32>,
+
+    /// Like --min-version-age, for direct dependencies only; the stricter of the two
+    /// applies to them
+    #[arg(long, global = true, value_name = "DAYS")]
+    pub min_direct_version_age: Option<u32>,
+
+    /// Fail when any of these crates (comma separated, or `all`) is resolved at more
+    /// than one version
+    #[arg(long, global = true, value_name = "CRATES", value_delimiter = ',')]
+    pub fail_on_duplicates: Vec<String>,
+
+    /// Fail when more than this percentage of crates (not trusted or waived) have no
+    /// score data: not scanned (yet), no repository or an unreachable one, or a failed
+    /// lookup
+    #[arg(long, global = true, value_name = "PERCENT")]
+    pub max_unknown: Option<f64>,
+
+    /// Fail when more than this many crates end up without a score for any reason,
+    /// listing them by cause; a guard against losing coverage. Trusted and waived
+    /// crates count too; path and workspace crates don't
+    #[arg(long, global = true, value_name = "N")]
+    pub max_unscored: Option<usize>,
+
+    /// Fail when a crate's scorecard scan is older than this many days, or undated
+    #[arg(long, global = true, value_name = "DAYS")]
+    pub max_scan_age: Option<u32>,
+
+    /// Whether crates on forges the scorecard API doesn't cover count as having no
+    /// score data [default: count]
+    #[arg(long, global = true, value_name = "HOW", value_enum)]
+    pub unsupported_forges: Option<UnsupportedForges>,
+
+    /// Look up published advisories for each crate version on OSV.dev
+    #[arg(long, global = true)]
+    pub advisories: bool,
+
+    /// Only report advisories published on or after this date (YYYY-MM-DD, compared
+    /// against the UTC date of OSV's `published` timestamp; undated advisories are
+    /// always reported); --detailed still lists older ones. Implies --advisories
+    #[arg(long, global = true, value_name = "DATE")]
+    pub advisories_since: Option<NaiveDate>,
+
+    /// Fail when a crate has a reported advisory. Implies --advisories
+    #[arg(long, global = true)]
+    pub fail_on_advisory: bool,
+
+    /// Fail on any data quality issue: the same as --fail-on-no-repository=all (which
+    /// also covers invalid repository URLs), --fail-on-dead-repo, --max-unknown 0
+    /// (never scanned, failed lookups, unsupported forges), --max-scan-age 180 and
+    /// --fail-on-duplicates all. Each of those given on its own, or in the config,
+    /// still takes precedence
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Fail if this crate appears anywhere in the tree, optionally limited to
+    /// matching versions (e.g. `openssl@<0.10`); repeatable
+    #[arg(long, global = true, value_name = "CRATE[@REQ]")]
+    pub deny: Vec<CrateSpec>,
+
+    /// Abort before any lookups when the tree has more crates than this
+    #[arg(long, global = true, value_name = "N", default_value_t = 2000)]
+    pub max_crates: usize,
+
+    /// Stop fetching after this many seconds and report what completed
+    #[arg(long, global = true, value_name = "SECS")]
+    pub max_time: Option<u64>,
+
+    /// Look crates up one at a time, in order, on a single thread: slower, but lighter
+    /// on small CI runners and easier to follow when debugging a flaky lookup. For a
+    /// handful of crates it costs little; for a large tree, keep the default
+    #[arg(long, global = true)]
+    pub serial: bool,
+
+    /// Order the results: `score`, lowest first, or `risk`, highest first, which also
+    /// shows the Reach and Risk columns [default: as resolved]
+    #[arg(long, global = true, value_name = "ORDER", value_enum)]
+    pub sort: Option<SortOrder>,
+
+    /// Results table columns to show, in this order, for the markdown and terminal
+    /// formats [default: the basics plus whatever the run collected]
+    #[arg(
+        long,
+        global = true,
+        value_name = "COLUMN,...",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub columns: Vec<Column>,
+
+    /// Whether a report cut short by --max-time fails the run (exit code 3)
+    #[arg(long, global = true, value_name = "POLICY", value_enum, default_value_t = PartialPolicy::Fail)]
+    pub on_partial: PartialPolicy,
+
+    /// Post a summary to this Slack-compatible webhook when done
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        env = "SCORECARD_NOTIFY_WEBHOOK",
+        hide_env_values = true
+    )]
+    pub notify_webhook: Option<String>,
+
+    /// Custom webhook body; {crate_count}, {fail_count}, {aggregate}, {summary} (crates
+    /// per grade band), {worst_crates} and {report_url} are replaced with the run's values
+    #[arg(
+        long,
+        global = true,
+        value_name = "TEMPLATE",
+        requires = "notify_webhook"
+    )]
+    pub notify_template: Option<String>,
+
+    /// Number of lowest-scoring crates listed in the notification
+    #[arg(long, global = true, value_name = "N", default_value_t = 5)]
+    pub notify_worst: usize,
+
+    /// Link to the full report, included in the notification
+    #[arg(long, global = true, value_name = "URL")]
+    pub report_url: Option<String>,
+
+    /// Where repository scores come from
+    #[arg(long, global = true, value_name = "NAME", value_enum, default_value_t)]
+    pub provider: Provider,
+
+    /// JSON file of scores for `--provider file`, mapping `host/owner/repo` to a 0-10
+    /// score or to a scorecard-shaped result (`score`, `date`, `checks`)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub provider_file: Option<PathBuf>,
+
+    /// Treat the scorecard API as down when this many of its first requests fail
+    /// with no answer in between, and skip the remaining lookups (0 never does)
+    #[arg(long, global = true, value_name = "N", default_value_t = 10)]
+    pub api_down_after: usize,
+
+    /// Also write every scorecard response, unmodified, to
+    /// `DIR/<host>/<owner>/<repo>.json`
+    #[arg(long, global = true, value_name = "DIR")]
+    pub raw_scorecard: Option<PathBuf>,
+
+    /// Save every raw API response (URL, status, response headers and body; never
+    /// request headers or tokens) as `DIR/<host>/<crate>--<path>.txt`, for troubleshooting
+    #[arg(long, global = true, value_name = "DIR")]
+    pub debug_dump: Option<PathBuf>,
+
+    /// With --debug-dump, only save error statuses and JSON that doesn't parse
+    #[arg(long, global = true, requires = "debug_dump")]
+    pub debug_dump_failures_only: bool,
+
+    /// Print the JSON Schema of the --format json report and exit
+    #[arg(long, global = true)]
+    pub schema: bool,
+
+    /// Print each configuration setting in effect and where it was set:
+    /// .cargo-scorecard.toml, Cargo.toml metadata or a flag overriding both; then exit
+    #[arg(long, global = true)]
+    pub show_config: bool,
+
+    /// Also write a compact JSON summary here: counts by status and score band, the
+    /// aggregate score, the policy outcome, failing crates and coverage (see
+    /// `cargo scorecard schema summary`); written whatever the exit code
+    #[arg(long, global = true, value_name = "PATH")]
+    pub summary_file: Option<PathBuf>,
+
+    /// When no policy gate fails, print one success line instead of the report; a
+    /// failing run prints the report in each --format as usual (--output files are
+    /// written either way)
+    #[arg(long, global = true)]
+    pub summary_only_on_failure: bool,
+
+    /// Append this run's aggregate score, dependency and failing crate counts to this
+    /// file, one JSON object per line
+    #[arg(long, global = true, value_name = "PATH")]
+    pub history_db: Option<PathBuf>,
+
+    /// Print a sparkline of the aggregate score over the runs in --history-db, and the
+    /// change since the last one, to stderr
+    #[arg(long, global = true, requires = "history_db")]
+    pub trend: bool,
+
+    /// Print phase timings, request, retry and cache counters and the slowest
+    /// requests to stderr when done; also added to --format json
+    #[arg(long, global = true)]
+    pub stats: bool,
+
+    /// Keep running, scoring again whenever Cargo.lock changes
+    #[arg(long, global = true)]
+    pub watch: bool,
+
+    /// Browse the results in an interactive table instead of printing a report: `s`
+    /// sorts, `/` filters, Enter opens a crate's checks, advisories and dependency
+    /// path, `q` quits; --output files are still written
+    #[arg(long, global = true, conflicts_with_all = ["template", "watch"])]
+    pub tui: bool,
+
+    /// Print each --watch report below the last instead of clearing the screen
+    #[arg(long, global = true, requires = "watch")]
+    pub no_clear: bool,
+
+    /// Add archived status, stars and last push date from GitHub/GitLab
+    #[arg(long, global = true)]
+    pub github_enrich: bool,
+
+    /// Score this version of a crate instead of the resolved one, to preview an
+    /// upgrade; the repository score stays the same, but yanked status and advisories
+    /// follow the version, and pinned rows are marked hypothetical (repeatable)
+    #[arg(long, global = true, value_name = "NAME@VERSION")]
+    pub pin: Vec<Pin>,
+
+    /// Show no progress at all; otherwise a fetch counter goes to stderr, redrawn in
+    /// place on a terminal and as a plain line every few seconds when captured
+    #[arg(long, global = true)]
+    pub no_progress: bool,
+
+    /// Print debug logs to stderr (`RUST_LOG` takes precedence when set)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Fetch everything a later run needs into the cache and print only a summary
+    Warm,
+    /// Score a published crate and its dependency tree, resolved from crates.io
+    Audit {
+        /// The crate to audit, as `name` or `name@<req>` (e.g. `serde_yaml@0.9`)
+        #[arg(value_name = "CRATE[@REQ]")]
+        spec: AuditSpec,
+    },
+    /// Score two published crates side by side, marking which does better on each
+    /// check, advisories, downloads and freshness; no project tree is involved
+    Compare {
+        /// The first crate, as `name` or `name@<req>`
+        #[arg(value_name = "CRATE[@REQ]")]
+        first: AuditSpec,
+        /// The crate to compare it with
+        #[arg(value_name = "CRATE[@REQ]")]
+        second: AuditSpec,
+    },
+    /// Waive a crate in `.cargo-scorecard.toml`, or list or remove waivers
+    Ignore {
+        /// The crate to waive, as `name` or `name@<req>`
+        #[arg(
+            value_name = "CRATE[@REQ]",
+            required_unless_present_any = ["list", "remove"],
+            conflicts_with_all = ["list", "remove"]
+        )]
+        spec: Option<CrateSpec>,
+        /// Why the risk is accepted
+        #[arg(long)]
+        reason: Option<String>,
+        /// Last day the waiver applies
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        expires: Option<NaiveDate>,
+        /// Add the waiver without a --reason
+        #[arg(long)]
+        force: bool,
+        /// List the waivers, flagging expired ones
+        #[arg(long, conflicts_with = "remove")]
+        list: bool,
+        /// Remove the waivers for a crate
+        #[arg(long, value_name = "CRATE")]
+        remove: Option<String>,
+    },
+    /// Fetch the crates that failed with an error in a `--format json` report again,
+    /// and report on the merged rows; an --output without a --format gets JSON
+    Retry {
+        /// The report to retry
+        report: PathBuf,
+    },
+    /// Print a completion script for the `cargo-scorecard` command
+    ///
+    /// The script completes the tool when it is run as `cargo-scorecard`. Typed as
+    /// `cargo scorecard`, completion is left to cargo's own script, which completes the
+    /// subcommand name but none of its flags.
+    ///
+    /// Install it where the shell looks for completions:
+    ///
+    ///   bash:        mkdir -p ~/.local/share/bash-completion/completions
+    ///                cargo-scorecard completions bash > ~/.local/share/bash-completion/completions/cargo-scorecard
+    ///
+    ///   zsh:         mkdir -p ~/.zfunc
+    ///                cargo-scorecard completions zsh > ~/.zfunc/_cargo-scorecard
+    ///                (with `fpath+=~/.zfunc` before `compinit` in ~/.zshrc)
+    ///
+    ///   fish:        mkdir -p ~/.config/fish/completions
+    ///                cargo-scorecard completions fish > ~/.config/fish/completions/cargo-scorecard.fish
+    ///
+    ///   PowerShell:  cargo-scorecard completions powershell >> $PROFILE
+    #[command(verbatim_doc_comment)]
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the JSON Schema for `--format json` output, or for `--summary-file`
+    Schema {
+        #[arg(id = "schema_kind", value_name = "KIND", value_enum, default_value_t = SchemaKind::Report)]
+        kind: SchemaKind,
+    },
+    /// List every URL a later `--offline` run needs that isn't cached yet, for fetching
+    /// on another machine
+    ExportRequests {
+        /// Where to write the request list
+        path: PathBuf,
+    },
+    /// Copy responses fetched for `export-requests` into the cache
+    ///
+    /// Responses are checked against SHA256SUMS, which catches files corrupted or cut
+    /// short in transit. A SHA256SUMS carried alongside the responses does not catch
+    /// tampering, since whoever alters a response can rewrite it too; for that, bring
+    /// it over a separate trusted channel and pass it with --checksums.
+    ImportResponses {
+        /// Directory holding `<id>.json` for each request
+        dir: PathBuf,
+        /// The request list [default: DIR/requests.json]
+        #[arg(long, value_name = "PATH")]
+        requests: Option<PathBuf>,
+        /// Checksums of the responses, in `sha256sum` format [default: DIR/SHA256SUMS]
+        #[arg(long, value_name = "PATH")]
+        checksums: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// An aligned table sized to the terminal
+    Table,
+    /// GitHub-flavored markdown tables
+    Markdown,
+    /// Markdown for PR comments: weak crates' check breakdowns in collapsible
+    /// sections, kept under GitHub's comment size limit
+    GhMarkdown,
+    /// A versioned JSON report with one object per crate (see `cargo scorecard --schema`)
+    Json,
+    /// SARIF 2.1.0, for upload to GitHub code scanning
+    Sarif,
+    /// JUnit XML with one test case per crate, failed by its policy failures and
+    /// advisories, for CI test report views
+    Junit,
+    /// Prometheus text exposition format: a gauge per scored crate version plus
+    /// totals, to push to a Pushgateway from a scheduled scan
+    Prometheus,
+    /// Just the number of crates breaking the policy; it is also the exit code,
+    /// capped at 255 (so 0 means success)
+    Count,
+}
+
+/// A report to render and where it goes.
+#[derive(Clone, Debug)]
+pub struct Output {
+    pub format: OutputFormat,
+    /// `None` for stdout.
+    pub path: Option<PathBuf>,
+}
+
+/// Parse an `--also-write FORMAT:PATH` pair.
+fn parse_also_write(s: &str) -> Result<Output, String> {
+    let (format, path) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected FORMAT:PATH, e.g. json:report.json, got {}", s))?;
+    let format = OutputFormat::from_str(format, true).map_err(|_| {
+        let formats: Vec<String> = OutputFormat::value_variants()
+            .iter()
+            .filter_map(|format| format.to_possible_value())
+            .map(|value| value.get_name().to_string())
+            .collect();
+        format!(
+            "unknown format {}; expected one of {}",
+            format,
+            formats.join(", ")
+        )
+    })?;
+    if path.is_empty() {
+        return Err(format!(
+            "missing the path after {}:",
+            s.trim_end_matches(':')
+        ));
+    }
+    Ok(Output {
+        format,
+        path: Some(PathBuf::from(path)),
+    })
+}
+
+/// Give each `--output` to the `--format` just before it, leaving the rest on stdout,
+/// then add the `--also-write` files.
+fn pair_outputs(args: &Args, matches: &ArgMatches) -> Result<Vec<Output>, String> {
+    let format_indices = matches.indices_of("format").into_iter().flatten();
+    let output_indices = matches.indices_of("output").into_iter().flatten();
+    let mut outputs: Vec<(usize, Output)> = format_indices
+        .zip(&args.format)
+        .map(|(index, format)| {
+            let output = Output {
+                format: *format,
+                path: None,
+            };
+            (index, output)
+        })
+        .collect();
+    for (index, path) in output_indices.zip(&args.output) {
+        let format = outputs
+            .iter_mut()
+            .rev()
+            .find(|(format_index, _)| *format_index < index)
+            .map(|(_, output)| output)
+            .filter(|output| output.path.is_none());
+        match format {
+            Some(format) => format.path = Some(path.clone()),
+            // `retry` writes the merged report back in the format it read
+            None if matches!(args.command, Some(Command::Retry { .. })) => outputs.push((
+                index,
+                Output {
+                    format: OutputFormat::Json,
+                    path: Some(path.clone()),
+                },
+            )),
+            None => {
+                return Err(format!(
+                    "--output {} needs a --format of its own just before it",
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    let outputs: Vec<Output> = outputs
+        .into_iter()
+        .map(|(_, output)| output)
+        .chain(args.also_write.iter().cloned())
+        .collect();
+    let stdout: Vec<String> = outputs
+        .iter()
+        .filter(|output| output.path.is_none())
+        .filter_map(|output| output.format.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if stdout.len() > 1 {
+        return Err(format!(
+            "only one report can go to stdout, but {} have no --output; give each of \
+             them but one an --output",
+            stdout.join(", ")
+        ));
+    }
+    let mut paths: Vec<&PathBuf> = outputs.iter().filter_map(|o| o.path.as_ref()).collect();
+    paths.sort();
+    if let Some(pair) = paths.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!("{} is given as an output twice", pair[0].display()));
+    }
+    for path in paths {
+        if path.is_dir() {
+            return Err(format!("{} is a directory, not a file", path.display()));
+        }
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty())
+            && !dir.is_dir()
+        {
+            return Err(format!(
+                "cannot write {}: directory {} does not exist",
+                path.display(),
+                dir.display()
+            ));
+        }
+    }
+    Ok(outputs)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SchemaKind {
+    /// The `--format json` report
+    Report,
+    /// The `--summary-file` sidecar
+    Summary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    /// Lowest score first, crates without one last
+    Score,
+    /// Highest risk first: (10 - score) × ln(1 + reach), where reach counts the
+    /// packages in the tree depending on the crate
+    Risk,
+}
+
+/// A column of the results table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Name,
+    Version,
+    Repository,
+    Score,
+    /// The --check-weights score
+    CustomScore,
+    /// Scored, Not scanned, Trusted and so on
+    Status,
+    /// Checks scoring 0
+    ChecksFailed,
+    Project,
+    /// Needs `cargo scorecard audit`
+    Depth,
+    /// Needs `cargo scorecard audit`
+    IntroducedBy,
+    Exemption,
+    BuildTime,
+    UniqueDeps,
+    Reach,
+    Risk,
+    /// Needs --github-enrich
+    Archived,
+    /// Needs --github-enrich
+    Stars,
+    /// Needs --github-enrich
+    LastPush,
+    Published,
+    /// Needs --advisories
+    Advisories,
+    /// Added, upgraded or downgraded; needs --changed-since
+    Change,
+    /// Registry, git or path
+    Source,
+}
+
+impl Column {
+    /// The name `--columns` takes.
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .expect("no column is skipped")
+            .get_name()
+            .to_string()
+    }
+
+    /// The table header.
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Name => "Crate Name",
+            Column::Version => "Version",
+            Column::Repository => "Repository URL",
+            Column::Score => "Security Score",
+            Column::CustomScore => "Custom Score",
+            Column::Status => "Status",
+            Column::ChecksFailed => "Failed Checks",
+            Column::Project => "Project",
+            Column::Depth => "Depth",
+            Column::IntroducedBy => "Introduced By",
+            Column::Exemption => "Exemption",
+            Column::BuildTime => "Build-time Code",
+            Column::UniqueDeps => "Unique Deps",
+            Column::Reach => "Reach",
+            Column::Risk => "Risk",
+            Column::Archived => "Archived",
+            Column::Stars => "Stars",
+            Column::LastPush => "Last Push",
+            Column::Published => "Published",
+            Column::Advisories => "Advisories",
+            Column::Change => "Change",
+            Column::Source => "Source",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PartialPolicy {
+    Fail,
+    Pass,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ScoreScale {
+    #[value(name = "10")]
+    Ten,
+    #[value(name = "100")]
+    Hundred,
+}
+
+impl ScoreScale {
+    fn factor(self) -> f64 {
+        match self {
+            ScoreScale::Ten => 1.0,
+            ScoreScale::Hundred => 10.0,
+        }
+    }
+}
+
+impl Args {
+    /// Parse the command line, accepting both `cargo scorecard` and `cargo-scorecard`.
+    pub fn parse_from_env() -> Self {
+        // Cargo passes the subcommand name as the first argument.
+        let args = std::env::args_os()
+            .enumerate()
+            .filter(|(i, arg)| *i != 1 || arg != "scorecard")
+            .map(|(_, arg)| arg);
+        let matches = Self::command().get_matches_from(args);
+        let mut parsed = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        parsed.outputs = pair_outputs(&parsed, &matches).unwrap_or_else(|message| {
+            Self::command()
+                .error(ErrorKind::ArgumentConflict, message)
+                .exit()
+        });
+        parsed
+    }
+
+    /// The on-disk cache location, or `None` when caching is disabled.
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        if self.no_cache {
+            return None;
+        }
+        self.cache_dir.clone().or_else(Cache::default_dir)
+    }
+
+    /// The reports to render, defaulting to a table on stdout when it is a terminal and
+    /// markdown otherwise.
+    pub fn outputs(&self) -> Vec<Output> {
+        let mut outputs = self.outputs.clone();
+        // Without a --format, the default one still goes to stdout beside --also-write files
+        if self.format.is_empty() {
+            let format = if std::io::stdout().is_terminal() {
+                OutputFormat::Table
+            } else {
+                OutputFormat::Markdown
+            };
+            outputs.insert(0, Output { format, path: None });
+        }
+        outputs
+    }
+
+    /// The format of the report printed to stdout, if any.
+    pub fn stdout_format(&self) -> Option<OutputFormat> {
+        self.outputs()
+            .into_iter()
+            .find(|output| output.path.is_none())
+            .map(|output| output.format)
+    }
+
+    /// The configuration file in use, or where `cargo scorecard ignore` creates one.
+    pub fn config_path(&self) -> PathBuf {
+        self.config
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(CONFIG_FILE))
+    }
+
+    /// The manifest being scored, or the first of several.
+    pub fn manifest(&self) -> &Path {
+        self.manifest_path
+            .first()
+            .map_or(Path::new("Cargo.toml"), PathBuf::as_path)
+    }
+
+    /// Replace directories given to `--manifest-path` with the manifests under them.
+    pub fn expand_manifests(&mut self) -> Result<()> {
+        let mut manifests = Vec::new();
+        for path in &self.manifest_path {
+            if path.is_dir() {
+                let found = deps::find_manifests(path)?;
+                if found.is_empty() {
+                    return Err(anyhow!("No Cargo.toml found under {}", path.display()));
+                }
+                manifests.extend(found);
+            } else {
+                manifests.push(path.clone());
+            }
+        }
+        self.manifest_path = manifests;
+        Ok(())
+    }
+
+    /// Whether repositories are checked for the crates claiming them.
+    pub fn verifies_provenance(&self) -> bool {
+        self.verify_provenance || self.fail_on_unverified
+    }
+
+    /// Whether OSV advisories are looked up.
+    pub fn checks_advisories(&self) -> bool {
+        self.advisories || self.advisories_since.is_some() || self.fail_on_advisory
+    }
+
+    /// Whether more than one project is being scored.
+    pub fn is_multi_project(&self) -> bool {
+        self.manifest_path.len() > 1
+    }
+
+    /// Cargo.lock next to the manifest being scored.
+    pub fn lockfile_path(&self) -> PathBuf {
+        let dir = self.manifest().parent().unwrap_or(Path::new(""));
+        dir.join("Cargo.lock")
+    }
+
+    /// Options forwarded to every cargo invocation.
+    pub fn cargo_options(&self) -> CargoOptions<'_> {
+        CargoOptions {
+            manifest_path: self.manifest_path.first().map(PathBuf::as_path),
+            offline: self.offline || cargo_net_offline(),
+            locked: self.locked,
+            frozen: self.frozen,
+        }
+    }
+
+    /// How the dependency list was resolved, recorded in the report.
+    pub fn resolution(&self) -> &'static str {
+        if let Some(Command::Audit { .. }) = self.command {
+            return "crates.io (newest matching versions; local feature and target selection may differ)";
+        }
+        match self.vendor_dir {
+            Some(_) => "vendored",
+            None => self.cargo_options().resolution(),
+        }
+    }
+
+    /// Format a raw OpenSSF score for human-readable output.
+    pub fn format_score(&self, score: f64) -> String {
+        format!(
+            "{:.*}",
+            self.score_precision,
+            score * self.score_scale.factor()
+        )
+    }
+}
+
+/// Write the completion script for `shell` to stdout, from the same definition the
+/// command line is parsed with.
+///
+/// Shells register completions per command word, and `cargo` belongs to cargo's own
+/// script, so the script is for the `cargo-scorecard` binary rather than `bin_name`.
+pub fn print_completions(shell: Shell) {
+    clap_complete::generate(
+        shell,
+        &mut Args::command(),
+        "cargo-scorecard",
+        &mut std::io::stdout(),
+    );
+}
+
+/// Whether `CARGO_NET_OFFLINE=true` tells cargo to stay off the network.
+fn cargo_net_offline() -> bool {
+    std::env::var("CARGO_NET_OFFLINE").is_ok_and(|value| value == "true")
+}
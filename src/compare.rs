@@ -0,0 +1,323 @@
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use comfy_table::{CellAlignment, ContentArrangement, Table, presets};
+use tokio::runtime::Runtime;
+use tokio::time::Instant;
+
+use crate::audit::{self, AuditSpec};
+use crate::cache::CacheKind;
+use crate::cli::Args;
+use crate::deps::{BuildTime, CrateInfo};
+use crate::enrich::{self, RepoMetadata};
+use crate::fetch::{self, Context};
+use crate::osv;
+use crate::progress::Progress;
+use crate::render::table::supports_unicode;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Which of the two crates does better on a row.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Winner {
+    First,
+    Second,
+    Tie,
+    /// Not comparable: one side has no data, or the row is only informational.
+    None,
+}
+
+impl Winner {
+    /// The better of two values, where `Greater` is better; `None` when either is missing.
+    fn of<T: PartialOrd>(first: Option<T>, second: Option<T>) -> Winner {
+        match (first, second) {
+            (Some(first), Some(second)) if first > second => Winner::First,
+            (Some(first), Some(second)) if first < second => Winner::Second,
+            (Some(_), Some(_)) => Winner::Tie,
+            _ => Winner::None,
+        }
+    }
+
+    /// Like [`Winner::of`], for values where lower is better.
+    fn lower<T: PartialOrd>(first: Option<T>, second: Option<T>) -> Winner {
+        Winner::of(second, first)
+    }
+}
+
+/// One dimension the crates are compared on.
+struct Row {
+    dimension: String,
+    first: String,
+    second: String,
+    winner: Winner,
+}
+
+/// A crate to compare: its row in a report, plus download counts from crates.io.
+struct Candidate {
+    score: CrateScore,
+    downloads: Option<u64>,
+    recent_downloads: Option<u64>,
+}
+
+/// `cargo scorecard compare A B`: score two published crates side by side.
+pub fn run(
+    args: &Args,
+    ctx: &Context,
+    runtime: &Runtime,
+    first: &AuditSpec,
+    second: &AuditSpec,
+) -> Result<ExitCode> {
+    let [first, second] = runtime.block_on(candidates(args, ctx, [first, second]))?;
+    print!("{}", render(&first, &second, args));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Resolve both crates to their newest matching versions and look them up like a report
+/// would, advisories included.
+async fn candidates(args: &Args, ctx: &Context, specs: [&AuditSpec; 2]) -> Result<[Candidate; 2]> {
+    let mut crates = Vec::with_capacity(specs.len());
+    for spec in specs {
+        let version = audit::newest_matching(ctx, &spec.name, &spec.req).await?;
+        crates.push(CrateInfo {
+            name: spec.name.clone(),
+            version: format!("v{}", version),
+            direct: true,
+            kind: None,
+            vendored: None,
+            depth: None,
+            introduced_by: None,
+            projects: Vec::new(),
+            build_time: BuildTime::default(),
+            unique_dependencies: None,
+            reach: None,
+            pinned_from: None,
+            listed_repository: None,
+            mapped_repository: None,
+            changed: None,
+            source: None,
+        });
+    }
+
+    let deadline = args
+        .max_time
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+    let progress = Progress::new(crates.len(), false);
+    let mut scores = fetch::fetch_all(ctx, &crates, deadline, &progress).await;
+    osv::attach_advisories(ctx, &mut scores).await;
+    if args.github_enrich {
+        enrich::enrich(ctx, &mut scores).await;
+    }
+
+    let mut candidates = Vec::with_capacity(scores.len());
+    for score in scores {
+        // Already cached by the version lookup
+        let json = ctx
+            .cache
+            .get_or_fetch(CacheKind::Crate, &score.name, || {
+                fetch::fetch_crate_metadata(&ctx.http, &score.name)
+            })
+            .await?;
+        candidates.push(Candidate {
+            downloads: json["crate"]["downloads"].as_u64(),
+            recent_downloads: json["crate"]["recent_downloads"].as_u64(),
+            score,
+        });
+    }
+    Ok(candidates
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("two crates in, two rows out")))
+}
+
+/// The comparison table, followed by a tally of which crate wins more dimensions.
+fn render(first: &Candidate, second: &Candidate, args: &Args) -> String {
+    let rows = rows(first, second, args);
+    let (a, b) = (&first.score, &second.score);
+    let unicode = supports_unicode();
+
+    let mut table = Table::new();
+    table
+        .load_style(if unicode {
+            presets::UTF8_FULL_CONDENSED
+        } else {
+            presets::ASCII_FULL_CONDENSED
+        })
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["", a.name.as_str(), b.name.as_str(), "Better"]);
+    for row in &rows {
+        let winner = match row.winner {
+            Winner::First => a.name.as_str(),
+            Winner::Second => b.name.as_str(),
+            Winner::Tie => "=",
+            Winner::None => "",
+        };
+        table.add_row(vec![
+            row.dimension.as_str(),
+            row.first.as_str(),
+            row.second.as_str(),
+            winner,
+        ]);
+    }
+    for column in table.column_iter_mut().skip(1).take(2) {
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+
+    let wins = |winner| rows.iter().filter(|row| row.winner == winner).count();
+    format!(
+        "{}\n\n{} is better on {} dimensions, {} on {}; {} are even\n",
+        table,
+        a.name,
+        wins(Winner::First),
+        b.name,
+        wins(Winner::Second),
+        wins(Winner::Tie)
+    )
+}
+
+/// Every dimension both crates are compared on, informational rows first.
+fn rows(first: &Candidate, second: &Candidate, args: &Args) -> Vec<Row> {
+    let (a, b) = (&first.score, &second.score);
+    let today = Utc::now().date_naive();
+    let mut rows = vec![
+        Row {
+            dimension: "Version".to_string(),
+            first: a.version.clone(),
+            second: b.version.clone(),
+            winner: Winner::None,
+        },
+        Row {
+            dimension: "Repository".to_string(),
+            first: a.repository.clone().unwrap_or_else(|| "-".to_string()),
+            second: b.repository.clone().unwrap_or_else(|| "-".to_string()),
+            winner: Winner::None,
+        },
+    ];
+
+    let score = |c: &CrateScore| match c.status {
+        ScoreStatus::Scored(score) => Some(score),
+        _ => None,
+    };
+    let score_text = |c: &CrateScore| match c.status {
+        ScoreStatus::Scored(score) => args.format_score(score),
+        ref status => status.category().to_string(),
+    };
+    rows.push(Row {
+        dimension: "Score".to_string(),
+        first: score_text(a),
+        second: score_text(b),
+        winner: Winner::of(score(a), score(b)),
+    });
+
+    let scan = |c: &CrateScore| c.scorecard.as_ref().and_then(|result| result.date());
+    rows.push(Row {
+        dimension: "Last scanned".to_string(),
+        first: date_text(scan(a), today),
+        second: date_text(scan(b), today),
+        winner: Winner::of(scan(a), scan(b)),
+    });
+
+    // Checks in the order the first crate lists them, then any only the second has
+    let mut checks: Vec<&str> = Vec::new();
+    for result in [&a.scorecard, &b.scorecard].into_iter().flatten() {
+        for check in &result.checks {
+            if !checks.contains(&check.name.as_str()) {
+                checks.push(&check.name);
+            }
+        }
+    }
+    for name in checks {
+        let check = |c: &CrateScore| {
+            c.scorecard
+                .as_ref()?
+                .scored_checks()
+                .find(|check| check.name == name)?
+                .score
+        };
+        let text = |score: Option<f64>| score.map_or_else(|| "-".to_string(), |s| s.to_string());
+        rows.push(Row {
+            dimension: format!("  {}", name),
+            first: text(check(a)),
+            second: text(check(b)),
+            winner: Winner::of(check(a), check(b)),
+        });
+    }
+
+    let advisories = |c: &CrateScore| c.advisories.as_ref().map(Vec::len);
+    let advisory_text = |c: &CrateScore| match &c.advisories {
+        Some(advisories) if advisories.is_empty() => "none".to_string(),
+        Some(advisories) => advisories
+            .iter()
+            .map(|advisory| advisory.id.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => "not checked".to_string(),
+    };
+    rows.push(Row {
+        dimension: "Advisories".to_string(),
+        first: advisory_text(a),
+        second: advisory_text(b),
+        winner: Winner::lower(advisories(a), advisories(b)),
+    });
+
+    let count = |n: Option<u64>| n.map_or_else(|| "-".to_string(), |n| n.to_string());
+    rows.push(Row {
+        dimension: "Downloads".to_string(),
+        first: count(first.downloads),
+        second: count(second.downloads),
+        winner: Winner::of(first.downloads, second.downloads),
+    });
+    rows.push(Row {
+        dimension: "Downloads (90 days)".to_string(),
+        first: count(first.recent_downloads),
+        second: count(second.recent_downloads),
+        winner: Winner::of(first.recent_downloads, second.recent_downloads),
+    });
+
+    let published = |c: &CrateScore| c.published_at.map(|at: DateTime<Utc>| at.date_naive());
+    rows.push(Row {
+        dimension: "Released".to_string(),
+        first: date_text(published(a), today),
+        second: date_text(published(b), today),
+        winner: Winner::of(published(a), published(b)),
+    });
+
+    if args.github_enrich {
+        let (ma, mb) = (a.repo_metadata.as_ref(), b.repo_metadata.as_ref());
+        let stars = |m: Option<&RepoMetadata>| m.map(|m| m.stars);
+        rows.push(Row {
+            dimension: "Stars".to_string(),
+            first: count(stars(ma)),
+            second: count(stars(mb)),
+            winner: Winner::of(stars(ma), stars(mb)),
+        });
+        let push = |m: Option<&RepoMetadata>| m.and_then(|m| m.last_push).map(|at| at.date_naive());
+        rows.push(Row {
+            dimension: "Last push".to_string(),
+            first: date_text(push(ma), today),
+            second: date_text(push(mb), today),
+            winner: Winner::of(push(ma), push(mb)),
+        });
+        // Not archived beats archived
+        let active = |m: Option<&RepoMetadata>| m.map(|m| !m.archived);
+        let archived_text = |m: Option<&RepoMetadata>| match m {
+            Some(m) if m.archived => "yes".to_string(),
+            Some(_) => "no".to_string(),
+            None => "-".to_string(),
+        };
+        rows.push(Row {
+            dimension: "Archived".to_string(),
+            first: archived_text(ma),
+            second: archived_text(mb),
+            winner: Winner::of(active(ma), active(mb)),
+        });
+    }
+    rows
+}
+
+/// e.g. `2024-05-06 (200 days ago)`, or `-` when unknown.
+fn date_text(date: Option<NaiveDate>, today: NaiveDate) -> String {
+    match date {
+        Some(date) => format!("{} ({} days ago)", date, (today - date).num_days()),
+        None => "-".to_string(),
+    }
+}
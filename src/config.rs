@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::coverage::UnsupportedForges;
+use crate::grade::GradeCutoffs;
+use crate::policy::{CrateSpec, Scope};
+
+/// Name of the per-project configuration file.
+pub const CONFIG_FILE: &str = ".cargo-scorecard.toml";
+/// Where settings in Cargo.toml live, lowest precedence first.
+const WORKSPACE_METADATA: &str = "Cargo.toml [workspace.metadata.scorecard]";
+const PACKAGE_METADATA: &str = "Cargo.toml [package.metadata.scorecard]";
+
+/// Settings read from `.cargo-scorecard.toml`, over any in Cargo.toml metadata.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Per-host request limits, keyed by host name (e.g. `crates.io`).
+    pub limits: HashMap<String, HostLimitConfig>,
+    /// Fail when crates in this scope have no repository metadata.
+    pub fail_on_no_repository: Option<Scope>,
+    /// Fail when a crate's repository no longer exists.
+    pub fail_on_dead_repo: bool,
+    /// Fail when a crate scores below this on the OpenSSF 0-10 scale.
+    pub fail_under: Option<f64>,
+    /// Like `fail_under`, for crates with a build script or proc-macro.
+    pub fail_under_build_time: Option<f64>,
+    /// Fail when the version in use was published fewer than this many days ago.
+    pub min_version_age: Option<u32>,
+    /// Like `min_version_age`, for direct dependencies only.
+    pub min_direct_version_age: Option<u32>,
+    /// Fail when more than this percentage of crates have no score data.
+    pub max_unknown: Option<f64>,
+    /// Fail when more than this many crates end up without a score.
+    pub max_unscored: Option<usize>,
+    /// Whether crates on forges the scorecard API doesn't cover count for `max_unknown`.
+    pub unsupported_forges: Option<UnsupportedForges>,
+    /// Crate names, or `all`, that must resolve to a single version.
+    pub fail_on_duplicates: Vec<String>,
+    /// Repository prefixes such as `github.com/rust-lang` whose crates are exempt from policy.
+    pub trusted_orgs: Vec<String>,
+    /// Crates not to flag as pointing at another crate's repository, once checked by hand.
+    pub allow_repo_mismatch: Vec<String>,
+    /// Cutoffs for `--grade`.
+    pub grade: GradeCutoffs,
+    /// Crates that fail the run wherever they appear, as `name` or `name@<req>`.
+    pub deny: Vec<CrateSpec>,
+    /// `[[ignore]]` waivers exempting crates from policy, managed with
+    /// `cargo scorecard ignore`.
+    pub ignore: Vec<Ignore>,
+    /// Each key set, with its value and the file it came from, for `--show-config`.
+    #[serde(skip)]
+    pub settings: Vec<Setting>,
+}
+
+/// A setting in effect and where it was set.
+#[derive(Debug)]
+pub struct Setting {
+    pub key: String,
+    pub value: toml::Value,
+    pub source: String,
+}
+
+/// A waiver: `crate` (`name` or `name@<req>`), why the risk is accepted and,
+/// optionally, the last day it applies as a `"YYYY-MM-DD"` string.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Ignore {
+    #[serde(rename = "crate")]
+    pub spec: CrateSpec,
+    pub reason: Option<String>,
+    pub expires: Option<NaiveDate>,
+}
+
+impl Ignore {
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires.is_some_and(|expires| expires < today)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HostLimitConfig {
+    /// Maximum requests per second (0 disables rate limiting).
+    pub rps: Option<f64>,
+    /// Maximum requests in flight at once.
+    pub concurrency: Option<usize>,
+}
+
+impl Config {
+    /// Load the configuration from `path`, or from `.cargo-scorecard.toml` in the
+    /// current directory when it exists.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        Self::load_layered(path, Vec::new())
+    }
+
+    /// Like [`Config::load`], on top of `layers` of settings from elsewhere, lowest
+    /// precedence first, each named by its source. A key set in more than one place
+    /// takes its value from the last, with a warning when the values differ.
+    pub fn load_layered(
+        path: Option<&Path>,
+        mut layers: Vec<(String, toml::Table)>,
+    ) -> Result<Self> {
+        let path = match path {
+            Some(path) => Some(path),
+            None if Path::new(CONFIG_FILE).exists() => Some(Path::new(CONFIG_FILE)),
+            None => None,
+        };
+        if let Some(path) = path {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+            let table = toml::from_str(&content)
+                .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+            layers.push((path.display().to_string(), table));
+        }
+
+        let mut merged: Vec<Setting> = Vec::new();
+        for (source, table) in layers {
+            // Checked on its own, so a mistake is blamed on the right file
+            toml::Value::Table(table.clone())
+                .try_into::<Config>()
+                .map_err(|e| anyhow!("Failed to parse {}: {}", source, e))?;
+            for (key, value) in table {
+                match merged.iter_mut().find(|setting| setting.key == key) {
+                    Some(setting) => {
+                        if setting.value != value {
+                            warn!(
+                                "`{}` is set in both {} and {}; using the one in {}",
+                                key, setting.source, source, source
+                            );
+                        }
+                        setting.value = value;
+                        setting.source = source.clone();
+                    }
+                    None => merged.push(Setting {
+                        key,
+                        value,
+                        source: source.clone(),
+                    }),
+                }
+            }
+        }
+
+        let table: toml::Table = merged
+            .iter()
+            .map(|setting| (setting.key.clone(), setting.value.clone()))
+            .collect();
+        let mut config: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| anyhow!("Failed to combine the configuration: {}", e))?;
+        config.settings = merged;
+        Ok(config)
+    }
+
+    /// Where `key` was set, if anywhere.
+    pub fn source(&self, key: &str) -> Option<&str> {
+        self.settings
+            .iter()
+            .find(|setting| setting.key == key)
+            .map(|setting| setting.source.as_str())
+    }
+
+    /// The `scorecard` tables in Cargo.toml metadata as configuration layers, workspace
+    /// first so the package's win.
+    pub fn manifest_layers(
+        workspace: Option<serde_json::Value>,
+        package: Option<serde_json::Value>,
+    ) -> Result<Vec<(String, toml::Table)>> {
+        [(WORKSPACE_METADATA, workspace), (PACKAGE_METADATA, package)]
+            .into_iter()
+            .filter_map(|(source, value)| Some((source, value?)))
+            .map(|(source, value)| match toml::Value::try_from(value) {
+                Ok(toml::Value::Table(table)) => Ok((source.to_string(), table)),
+                Ok(_) => Err(anyhow!("{} is not a table", source)),
+                Err(e) => Err(anyhow!("Failed to read {}: {}", source, e)),
+            })
+            .collect()
+    }
+}
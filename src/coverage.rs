@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::score::{CrateScore, ScoreStatus};
+
+/// How crates on forges the scorecard API doesn't cover count towards coverage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum UnsupportedForges {
+    /// As dependencies without a score.
+    #[default]
+    Count,
+    /// Not at all, as if they weren't in the tree.
+    Exclude,
+}
+
+/// How much of the tree has score data, over crates not exempt from policy.
+#[derive(Serialize, JsonSchema)]
+pub struct Coverage {
+    /// Crates considered: not trusted, waived, local or excluded as on an unsupported forge.
+    pub dependencies: usize,
+    /// Crates without a score: never scanned or still being scanned, no repository or
+    /// one that is gone, a failed lookup, and unsupported forges unless excluded.
+    pub unknown: usize,
+    /// Share of `dependencies` that are not unknown, 0-100; 100 for an empty tree.
+    pub percent: f64,
+    /// `unknown` by summary category, e.g. `Not scanned`.
+    pub unknown_by_category: BTreeMap<&'static str, usize>,
+}
+
+impl Coverage {
+    pub fn of(scores: &[CrateScore], unsupported_forges: UnsupportedForges) -> Self {
+        let considered: Vec<&CrateScore> = considered(scores, unsupported_forges).collect();
+        let unknown_by_category =
+            by_category(considered.iter().copied().filter(|c| is_unknown(&c.status)));
+        let unknown = unknown_by_category.values().sum();
+        let percent = match considered.len() {
+            0 => 100.0,
+            total => 100.0 * (total - unknown) as f64 / total as f64,
+        };
+        Coverage {
+            dependencies: considered.len(),
+            unknown,
+            percent,
+            unknown_by_category,
+        }
+    }
+
+    /// Share of the considered crates without a score, 0-100.
+    pub fn unknown_percent(&self) -> f64 {
+        100.0 - self.percent
+    }
+
+    /// e.g. `82.5% of 40 dependencies have score data (unknown: Not scanned 6, Error 1)`.
+    pub fn line(&self) -> String {
+        let mut line = format!(
+            "{:.1}% of {} dependencies have score data",
+            self.percent, self.dependencies
+        );
+        if self.unknown > 0 {
+            line.push_str(&format!(
+                " (unknown: {})",
+                list_categories(&self.unknown_by_category)
+            ));
+        }
+        line
+    }
+}
+
+/// The crates coverage is measured over.
+pub fn considered(
+    scores: &[CrateScore],
+    unsupported_forges: UnsupportedForges,
+) -> impl Iterator<Item = &CrateScore> {
+    scores
+        .iter()
+        // Path dependencies are the project's own code
+        .filter(|c| !c.trusted && c.waiver.is_none() && !c.is_local())
+        .filter(move |c| {
+            unsupported_forges == UnsupportedForges::Count
+                || !matches!(c.status, ScoreStatus::UnsupportedForge(_))
+        })
+}
+
+/// How many of `crates` there are in each status's summary category.
+pub fn by_category<'a>(
+    crates: impl IntoIterator<Item = &'a CrateScore>,
+) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for crate_score in crates {
+        *counts.entry(crate_score.status.category()).or_default() += 1;
+    }
+    counts
+}
+
+/// e.g. `No repository 3, Not scanned 2`, from [`by_category`].
+pub fn list_categories(counts: &BTreeMap<&str, usize>) -> String {
+    counts
+        .iter()
+        .map(|(category, count)| format!("{} {}", category, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Statuses that leave a crate without score data, as opposed to an incomplete run
+/// (uncached, skipped or interrupted), which the report flags as partial instead.
+///
+/// A scan still in progress counts: the data may come tomorrow, but a gate passed
+/// today shouldn't rest on it.
+pub fn is_unknown(status: &ScoreStatus) -> bool {
+    matches!(
+        status,
+        ScoreStatus::NotScanned
+            | ScoreStatus::Pending
+            | ScoreStatus::NoRepository
+            | ScoreStatus::Unreachable(_)
+            | ScoreStatus::Error(_)
+            | ScoreStatus::UnsupportedForge(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::score::tests::crate_score;
+
+    #[test]
+    fn unreachable_and_pending_crates_have_no_score_data() {
+        let scores = [
+            crate_score("scored", ScoreStatus::Scored(7.0)),
+            crate_score("gone", ScoreStatus::Unreachable(404)),
+            crate_score("scanning", ScoreStatus::Pending),
+            crate_score("unscanned", ScoreStatus::NotScanned),
+        ];
+        let coverage = Coverage::of(&scores, UnsupportedForges::Count);
+        assert_eq!(coverage.dependencies, 4);
+        assert_eq!(coverage.unknown, 3);
+        assert_eq!(coverage.percent, 25.0);
+        assert_eq!(
+            coverage.line(),
+            "25.0% of 4 dependencies have score data \
+             (unknown: Not scanned 1, Pending 1, Unreachable 1)"
+        );
+    }
+
+    #[test]
+    fn incomplete_runs_and_exempt_crates_are_not_unknown() {
+        let mut trusted = crate_score("trusted", ScoreStatus::NotScanned);
+        trusted.trusted = true;
+        let scores = [
+            crate_score("scored", ScoreStatus::Scored(7.0)),
+            crate_score("uncached", ScoreStatus::Uncached),
+            crate_score("interrupted", ScoreStatus::Interrupted),
+            crate_score("path-dep", ScoreStatus::Local),
+            crate_score(
+                "gitea",
+                ScoreStatus::UnsupportedForge("gitea.example".into()),
+            ),
+            trusted,
+        ];
+        let coverage = Coverage::of(&scores, UnsupportedForges::Exclude);
+        assert_eq!(coverage.dependencies, 3);
+        assert_eq!(coverage.unknown, 0);
+        assert_eq!(coverage.line(), "100.0% of 3 dependencies have score data");
+    }
+
+    #[test]
+    fn path_crates_are_not_considered() {
+        let mut mapped = crate_score("mapped-path-dep", ScoreStatus::NotScanned);
+        mapped.source = Some(crate::deps::CrateSource::Path);
+        let scores = [crate_score("scored", ScoreStatus::Scored(7.0)), mapped];
+        let coverage = Coverage::of(&scores, UnsupportedForges::Count);
+        assert_eq!((coverage.dependencies, coverage.unknown), (1, 0));
+    }
+}
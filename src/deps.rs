@@ -0,0 +1,872 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow};
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::changed::LockChange;
+
+#[derive(Serialize, Deserialize)]
+pub struct CrateInfo {
+    pub name: String,
+    pub version: String,
+    /// Whether a workspace member depends on this crate directly.
+    pub direct: bool,
+    /// The strongest kind of dependency edge that pulls the crate in, when known.
+    pub kind: Option<DepKind>,
+    /// Set when the crate was read from `--vendor-dir` rather than resolved by cargo.
+    pub vendored: Option<Vendored>,
+    /// Distance from the audited crate, for `cargo scorecard audit`.
+    pub depth: Option<usize>,
+    /// The crate that first pulled this one in, for `cargo scorecard audit`.
+    pub introduced_by: Option<String>,
+    /// The projects using the crate, when several manifests are scored together.
+    pub projects: Vec<String>,
+    /// Whether the crate runs code at build time; all false when unknown.
+    pub build_time: BuildTime,
+    /// For a direct dependency, how many crates only it pulls in; `None` otherwise or
+    /// when unknown.
+    pub unique_dependencies: Option<usize>,
+    /// How many packages in the tree depend on the crate, directly or not; `None` when
+    /// unknown.
+    pub reach: Option<usize>,
+    /// The resolved version a `--pin` replaced; `version` is then hypothetical.
+    pub pinned_from: Option<String>,
+    /// The repository of a `--repos-file` entry, which is not a crate: it is scored
+    /// without a crates.io lookup.
+    pub listed_repository: Option<String>,
+    /// The repository `--repo-map` gives for the crate, used over crates.io's.
+    pub mapped_repository: Option<String>,
+    /// How the version differs from the `--changed-since` lockfile.
+    pub changed: Option<LockChange>,
+    /// Where cargo gets the crate from, when `cargo metadata` says.
+    pub source: Option<CrateSource>,
+}
+
+impl CrateInfo {
+    /// Whether the crate is vendored without a matching Cargo.lock entry.
+    pub fn is_unlocked(&self) -> bool {
+        self.vendored.as_ref().is_some_and(|v| !v.in_lockfile)
+    }
+}
+
+/// Where cargo gets a crate from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase", tag = "kind")]
+pub enum CrateSource {
+    /// crates.io or another registry.
+    Registry,
+    /// A git dependency; `url` is the repository, without the revision.
+    Git { url: String },
+    /// A path dependency outside the workspace: local code, not scored.
+    Path,
+}
+
+impl CrateSource {
+    /// Parse a `cargo metadata` package source such as `registry+https://...`,
+    /// `sparse+https://...` or `git+https://github.com/o/r?rev=abc#abc123`; path
+    /// dependencies have none.
+    fn parse(source: Option<&str>) -> Option<CrateSource> {
+        let Some(source) = source else {
+            return Some(CrateSource::Path);
+        };
+        let (kind, url) = source.split_once('+')?;
+        match kind {
+            "registry" | "sparse" => Some(CrateSource::Registry),
+            "git" => {
+                let end = url.find(['?', '#']).unwrap_or(url.len());
+                Some(CrateSource::Git {
+                    url: url[..end].to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CrateSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrateSource::Registry => f.write_str("registry"),
+            CrateSource::Git { url } => write!(f, "git ({})", url),
+            CrateSource::Path => f.write_str("path"),
+        }
+    }
+}
+
+/// A `--pin name@version` override, scoring that version instead of the resolved one.
+#[derive(Clone, Debug)]
+pub struct Pin {
+    pub name: String,
+    pub version: Version,
+}
+
+impl FromStr for Pin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, version) = s
+            .split_once('@')
+            .ok_or_else(|| anyhow!("expected name@version, got {}", s))?;
+        if name.is_empty() {
+            return Err(anyhow!("missing crate name in {}", s));
+        }
+        let version = Version::parse(version.trim_start_matches('v'))
+            .map_err(|e| anyhow!("invalid version in {}: {}", s, e))?;
+        Ok(Pin {
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+/// Swap in each `--pin` version for every resolved version of that crate. Pinned
+/// crates are looked up on crates.io even when vendored, since the vendored copy is
+/// the resolved version.
+pub fn apply_pins(crates: &mut [CrateInfo], pins: &[Pin]) {
+    for pin in pins {
+        let version = format!("v{}", pin.version);
+        let mut found = false;
+        for crate_info in crates.iter_mut().filter(|c| c.name == pin.name) {
+            found = true;
+            if crate_info.version != version {
+                crate_info.pinned_from =
+                    Some(std::mem::replace(&mut crate_info.version, version.clone()));
+                crate_info.vendored = None;
+            }
+        }
+        if !found {
+            warn!("--pin {}@{} matches no dependency", pin.name, pin.version);
+        }
+    }
+}
+
+/// Code a crate runs while its dependents build.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BuildTime {
+    /// Has a `build.rs` (cargo's `custom-build` target).
+    pub build_script: bool,
+    pub proc_macro: bool,
+}
+
+impl BuildTime {
+    pub fn any(self) -> bool {
+        self.build_script || self.proc_macro
+    }
+}
+
+impl std::fmt::Display for BuildTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match (self.build_script, self.proc_macro) {
+            (true, true) => "build script, proc-macro",
+            (true, false) => "build script",
+            (false, true) => "proc-macro",
+            (false, false) => "",
+        })
+    }
+}
+
+/// How a crate is used by the workspace.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DepKind {
+    /// Compiled into the shipped artifacts.
+    Normal,
+    /// Only needed by build scripts and proc macros.
+    Build,
+    /// Only needed by tests, examples and benchmarks.
+    Dev,
+}
+
+impl std::fmt::Display for DepKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DepKind::Normal => "normal",
+            DepKind::Build => "build",
+            DepKind::Dev => "dev",
+        })
+    }
+}
+
+/// What a vendored crate's own manifest says about it.
+#[derive(Serialize, Deserialize)]
+pub struct Vendored {
+    pub repository: Option<String>,
+    pub homepage: Option<String>,
+    pub documentation: Option<String>,
+    /// Whether Cargo.lock has this exact name, version and checksum.
+    pub in_lockfile: bool,
+}
+
+/// Options shared by every cargo invocation.
+#[derive(Clone, Copy)]
+pub struct CargoOptions<'a> {
+    pub manifest_path: Option<&'a Path>,
+    pub offline: bool,
+    /// Refuse to resolve anything Cargo.lock does not already say.
+    pub locked: bool,
+    /// `--locked` and `--offline` together.
+    pub frozen: bool,
+}
+
+impl CargoOptions<'_> {
+    /// How strictly dependency resolution followed Cargo.lock, for the report.
+    pub fn resolution(&self) -> &'static str {
+        match (self.frozen, self.locked, self.offline) {
+            (true, _, _) => "frozen",
+            (false, true, true) => "locked, offline",
+            (false, true, false) => "locked",
+            (false, false, true) => "offline",
+            (false, false, false) => "unlocked",
+        }
+    }
+}
+
+/// Every Cargo.toml under `dir`, not descending into a directory once it has one:
+/// a workspace root already covers its members.
+pub fn find_manifests(dir: &Path) -> Result<Vec<PathBuf>> {
+    let manifest = dir.join("Cargo.toml");
+    if manifest.is_file() {
+        return Ok(vec![manifest]);
+    }
+
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| anyhow!("Failed to read {}: {}", dir.display(), e))?;
+    let mut subdirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| !name.starts_with('.') && name != "target")
+        })
+        .collect();
+    subdirs.sort();
+
+    let mut manifests = Vec::new();
+    for subdir in subdirs {
+        manifests.extend(find_manifests(&subdir)?);
+    }
+    Ok(manifests)
+}
+
+/// How a project is named in the report: the directory holding its manifest.
+pub fn project_label(manifest: &Path) -> String {
+    match manifest.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.display().to_string(),
+        _ => ".".to_string(),
+    }
+}
+
+/// Resolve several projects and merge their trees, recording which projects use each
+/// crate. A crate is direct if any project uses it directly, and keeps the strongest
+/// dependency kind any project pulls it in with.
+pub fn get_project_dependencies(
+    manifests: &[PathBuf],
+    options: &CargoOptions,
+) -> Result<Vec<CrateInfo>> {
+    let mut merged: BTreeMap<(String, String), CrateInfo> = BTreeMap::new();
+    for manifest in manifests {
+        let options = CargoOptions {
+            manifest_path: Some(manifest),
+            ..*options
+        };
+        let label = project_label(manifest);
+        for crate_info in get_dependencies(&options)? {
+            let key = (crate_info.name.clone(), crate_info.version.clone());
+            let (direct, kind, reach) = (crate_info.direct, crate_info.kind, crate_info.reach);
+            let entry = merged.entry(key).or_insert(crate_info);
+            entry.direct |= direct;
+            entry.reach = entry.reach.max(reach);
+            entry.kind = entry.kind.into_iter().chain(kind).min();
+            entry.projects.push(label.clone());
+        }
+    }
+    Ok(merged.into_values().collect())
+}
+
+/// Run `cargo tree` with the given extra arguments and return its output.
+fn run_cargo_tree(args: &[&str], options: &CargoOptions) -> Result<String> {
+    run_cargo("tree", args, options)
+}
+
+/// Run a cargo subcommand with the shared options and return its output.
+fn run_cargo(subcommand: &str, args: &[&str], options: &CargoOptions) -> Result<String> {
+    let mut command = std::process::Command::new("cargo");
+    command.arg(subcommand).args(args);
+    if let Some(manifest_path) = options.manifest_path {
+        command.arg("--manifest-path").arg(manifest_path);
+    }
+    if options.frozen {
+        command.arg("--frozen");
+    } else {
+        if options.locked {
+            command.arg("--locked");
+        }
+        if options.offline {
+            command.arg("--offline");
+        }
+    }
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to run cargo {}: {}", subcommand, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let hint = if (options.locked || options.frozen) && stderr.contains("Cargo.lock") {
+            "\nhint: Cargo.lock is out of date; run `cargo update` or drop --locked/--frozen"
+        } else {
+            ""
+        };
+        return Err(anyhow!(
+            "cargo {} failed: {}{}",
+            subcommand,
+            stderr.trim(),
+            hint
+        ));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|e| anyhow!("cargo {} printed invalid UTF-8: {}", subcommand, e))
+}
+
+/// Whether Cargo.lock is out of date with the manifests, so resolving would change it.
+///
+/// Asks `cargo update --workspace --locked --offline`, which only touches workspace
+/// members and refuses to write the lockfile; a missing lockfile isn't stale.
+pub fn lockfile_is_stale(options: &CargoOptions) -> Result<bool> {
+    let options = CargoOptions {
+        locked: true,
+        offline: true,
+        frozen: false,
+        ..*options
+    };
+    match run_cargo("update", &["--workspace"], &options) {
+        Ok(_) => Ok(false),
+        Err(e) if e.to_string().contains("cannot create the lock file") => Ok(false),
+        Err(e)
+            if e.to_string().contains("cannot update the lock file")
+                || e.to_string().contains("needs to be updated") =>
+        {
+            Ok(true)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// A crate as `(name, version)`, the version always carrying cargo's `v` prefix.
+type Package = (String, String);
+
+/// Run `cargo tree --prefix depth` and return the packages below the workspace roots.
+///
+/// The roots themselves are left out wherever they appear, so a workspace member
+/// another member depends on is not reported as a dependency; path dependencies
+/// outside the workspace are kept.
+fn cargo_tree(args: &[&str], options: &CargoOptions) -> Result<BTreeSet<Package>> {
+    let output = run_cargo_tree(&[&["--prefix", "depth"], args].concat(), options)?;
+    Ok(parse_tree(&output))
+}
+
+/// The packages in `cargo tree --prefix depth` output, without the roots.
+fn parse_tree(output: &str) -> BTreeSet<Package> {
+    let lines: Vec<(usize, Package)> = output.lines().filter_map(parse_line).collect();
+    let roots: HashSet<&Package> = lines
+        .iter()
+        .filter(|(depth, _)| *depth == 0)
+        .map(|(_, package)| package)
+        .collect();
+    lines
+        .iter()
+        .filter(|(_, package)| !roots.contains(package))
+        .map(|(_, package)| package.clone())
+        .collect()
+}
+
+/// The `cargo metadata` output for the workspace.
+///
+/// Like `cargo tree`, only the host platform's dependencies are read, so cargo doesn't
+/// need to download crates for other platforms.
+fn cargo_metadata(options: &CargoOptions) -> Result<Value> {
+    let host = host_triple()?;
+    let output = run_cargo(
+        "metadata",
+        &["--format-version", "1", "--filter-platform", &host],
+        options,
+    )?;
+    serde_json::from_str(&output)
+        .map_err(|e| anyhow!("Failed to parse cargo metadata output: {}", e))
+}
+
+/// The `scorecard` tables under `[workspace.metadata]` and the root package's
+/// `[package.metadata]`, in that order, as `cargo metadata` reports them.
+pub fn scorecard_metadata(options: &CargoOptions) -> Result<(Option<Value>, Option<Value>)> {
+    let output = run_cargo("metadata", &["--format-version", "1", "--no-deps"], options)?;
+    let metadata: Value = serde_json::from_str(&output)
+        .map_err(|e| anyhow!("Failed to parse cargo metadata output: {}", e))?;
+    let manifest = options
+        .manifest_path
+        .map_or_else(|| PathBuf::from("Cargo.toml"), Path::to_path_buf);
+    let manifest = manifest.canonicalize().unwrap_or(manifest);
+    let table = |value: &Value| Some(value["scorecard"].clone()).filter(|t| !t.is_null());
+
+    let workspace = table(&metadata["metadata"]);
+    let package = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|package| package["manifest_path"].as_str().map(Path::new) == Some(&manifest))
+        .and_then(|package| table(&package["metadata"]));
+    Ok((workspace, package))
+}
+
+/// Packages that run code at build time.
+fn build_time_packages(metadata: &Value) -> HashMap<Package, BuildTime> {
+    let mut packages = HashMap::new();
+    for package in metadata["packages"].as_array().into_iter().flatten() {
+        let (Some(name), Some(version)) = (package["name"].as_str(), package["version"].as_str())
+        else {
+            continue;
+        };
+        let kinds: Vec<&str> = package["targets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|target| target["kind"].as_array().into_iter().flatten())
+            .filter_map(Value::as_str)
+            .collect();
+        let build_time = BuildTime {
+            build_script: kinds.contains(&"custom-build"),
+            proc_macro: kinds.contains(&"proc-macro"),
+        };
+        if build_time.any() {
+            packages.insert((name.to_string(), format!("v{}", version)), build_time);
+        }
+    }
+    packages
+}
+
+/// Each package's source.
+fn package_sources(metadata: &Value) -> HashMap<Package, CrateSource> {
+    let mut sources = HashMap::new();
+    for package in metadata["packages"].as_array().into_iter().flatten() {
+        let (Some(name), Some(version)) = (package["name"].as_str(), package["version"].as_str())
+        else {
+            continue;
+        };
+        if let Some(source) = CrateSource::parse(package["source"].as_str()) {
+            sources.insert((name.to_string(), format!("v{}", version)), source);
+        }
+    }
+    sources
+}
+
+/// The Cargo.toml of every package built from a local path, workspace members
+/// included: edits to them can change the tree without touching Cargo.lock.
+pub fn local_manifests(options: &CargoOptions) -> Result<Vec<PathBuf>> {
+    let metadata = cargo_metadata(options)?;
+    Ok(metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|package| package["source"].is_null())
+        .filter_map(|package| package["manifest_path"].as_str())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// The resolve graph from `cargo metadata`: each package id's name and version, and
+/// the ids it depends on.
+type ResolveGraph<'a> = (HashMap<&'a str, Package>, HashMap<&'a str, Vec<&'a str>>);
+
+fn resolve_graph(metadata: &Value) -> ResolveGraph<'_> {
+    let packages: HashMap<&str, Package> = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|package| {
+            let id = package["id"].as_str()?;
+            let name = package["name"].as_str()?;
+            let version = package["version"].as_str()?;
+            Some((id, (name.to_string(), format!("v{}", version))))
+        })
+        .collect();
+    let graph: HashMap<&str, Vec<&str>> = metadata["resolve"]["nodes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|node| {
+            let deps = node["deps"].as_array().into_iter().flatten();
+            Some((
+                node["id"].as_str()?,
+                deps.filter_map(|dep| dep["pkg"].as_str()).collect(),
+            ))
+        })
+        .collect();
+    (packages, graph)
+}
+
+/// How many crates each direct dependency alone pulls in: those that every path from
+/// the workspace passes through it to reach. Shared subgraphs count for no one.
+fn unique_dependencies(metadata: &Value) -> HashMap<Package, usize> {
+    let (packages, graph) = resolve_graph(metadata);
+    let members: Vec<&str> = metadata["workspace_members"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+
+    let everything = reachable(&graph, &members, None);
+    let direct: HashSet<&str> = members
+        .iter()
+        .flat_map(|member| graph.get(member).into_iter().flatten().copied())
+        .filter(|id| !members.contains(id))
+        .collect();
+    direct
+        .into_iter()
+        .filter_map(|id| {
+            let without = reachable(&graph, &members, Some(id));
+            let unique = everything
+                .iter()
+                .filter(|other| **other != id && !without.contains(*other))
+                .count();
+            Some((packages.get(id)?.clone(), unique))
+        })
+        .collect()
+}
+
+/// How many packages in the tree depend on each package, directly or through others;
+/// workspace members count, the package itself does not.
+fn reach(metadata: &Value) -> HashMap<Package, usize> {
+    let (packages, graph) = resolve_graph(metadata);
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (id, deps) in &graph {
+        for dep in deps {
+            dependents.entry(dep).or_default().push(id);
+        }
+    }
+    packages
+        .iter()
+        .map(|(id, package)| {
+            let count = reachable(&dependents, &[id], None).len() - 1;
+            (package.clone(), count)
+        })
+        .collect()
+}
+
+/// Package ids reachable from `roots`, never entering `skip`.
+fn reachable<'a>(
+    graph: &HashMap<&'a str, Vec<&'a str>>,
+    roots: &[&'a str],
+    skip: Option<&str>,
+) -> HashSet<&'a str> {
+    let mut seen: HashSet<&str> = roots.iter().copied().collect();
+    let mut queue: Vec<&str> = roots.to_vec();
+    while let Some(id) = queue.pop() {
+        for dep in graph.get(id).into_iter().flatten() {
+            if Some(*dep) != skip && seen.insert(dep) {
+                queue.push(dep);
+            }
+        }
+    }
+    seen
+}
+
+/// The build configuration a dependency tree was resolved for, so a saved report says
+/// what it reflects.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Resolution {
+    /// How the tree was resolved, as in the report header: e.g. `locked`, `vendored`.
+    pub source: String,
+    /// The target triple the tree was resolved for; absent when cargo wasn't run.
+    pub target: Option<String>,
+    /// The features cargo enabled for each workspace package, by package name.
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+impl Resolution {
+    /// Add the target and workspace features cargo resolves with `options`.
+    pub fn add_features(&mut self, options: &CargoOptions) -> Result<()> {
+        let metadata = cargo_metadata(options)?;
+        let members: HashSet<&str> = metadata["workspace_members"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        let names: HashMap<&str, &str> = metadata["packages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|package| Some((package["id"].as_str()?, package["name"].as_str()?)))
+            .collect();
+        for node in metadata["resolve"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            let Some(id) = node["id"].as_str().filter(|id| members.contains(id)) else {
+                continue;
+            };
+            let Some(name) = names.get(id) else {
+                continue;
+            };
+            let features = node["features"].as_array().into_iter().flatten();
+            self.features.insert(
+                name.to_string(),
+                features
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
+        self.target = Some(host_triple()?);
+        Ok(())
+    }
+}
+
+/// The target triple rustc builds for by default.
+fn host_triple() -> Result<String> {
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = std::process::Command::new(rustc)
+        .arg("-vV")
+        .output()
+        .map_err(|e| anyhow!("Failed to run rustc: {}", e))?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("rustc -vV did not print a host triple"))
+}
+
+/// Parse one `cargo tree --prefix depth` line such as `2serde v1.0.200 (*)`.
+///
+/// Trailing annotations are ignored: the `(*)` repeat marker, `(proc-macro)`, and the
+/// path or git source of non-registry packages. Lines without a leading depth, like
+/// `[build-dependencies]` headings, and lines whose second token is not a semver
+/// version, like `--edges features` entries (`2serde feature "derive"`), yield `None`.
+fn parse_line(line: &str) -> Option<(usize, Package)> {
+    let line = line.trim();
+    let depth_len = line.find(|c: char| !c.is_ascii_digit())?;
+    let depth = line[..depth_len].parse().ok()?;
+    let mut tokens = line[depth_len..].split_whitespace();
+    let name = tokens.next()?;
+    let version = tokens.next()?;
+    let bare = version.strip_prefix('v').unwrap_or(version);
+    Version::parse(bare).ok()?;
+    Some((depth, (name.to_string(), format!("v{}", bare))))
+}
+
+pub fn get_dependencies(options: &CargoOptions) -> Result<Vec<CrateInfo>> {
+    let all = cargo_tree(&[], options)?;
+    let direct = cargo_tree(&["--depth", "1"], options)?;
+
+    // A crate reachable through normal edges alone ships in production; one that also
+    // needs build edges runs at build time; anything else only serves tests
+    let normal = cargo_tree(&["--edges", "normal"], options)?;
+    let build = cargo_tree(&["--edges", "normal,build"], options)?;
+    let (build_time, unique, reach, sources) = match cargo_metadata(options) {
+        Ok(metadata) => (
+            build_time_packages(&metadata),
+            unique_dependencies(&metadata),
+            reach(&metadata),
+            package_sources(&metadata),
+        ),
+        Err(e) => {
+            warn!(
+                "{}; build-time code, unique dependency counts, reach and sources are left out",
+                e
+            );
+            Default::default()
+        }
+    };
+
+    let dependencies = all
+        .into_iter()
+        .map(|package| {
+            let kind = if normal.contains(&package) {
+                DepKind::Normal
+            } else if build.contains(&package) {
+                DepKind::Build
+            } else {
+                DepKind::Dev
+            };
+            let direct = direct.contains(&package);
+            let build_time = build_time.get(&package).copied().unwrap_or_default();
+            let unique_dependencies = unique.get(&package).copied();
+            let reach = reach.get(&package).copied();
+            let source = sources.get(&package).cloned();
+            let (name, version) = package;
+            CrateInfo {
+                name,
+                version,
+                direct,
+                kind: Some(kind),
+                vendored: None,
+                depth: None,
+                introduced_by: None,
+                projects: Vec::new(),
+                build_time,
+                unique_dependencies,
+                reach,
+                pinned_from: None,
+                listed_repository: None,
+                mapped_repository: None,
+                changed: None,
+                source,
+            }
+        })
+        .collect();
+
+    Ok(dependencies)
+}
+
+/// Every package depending on the given crate, directly or not, including itself.
+pub fn dependents(options: &CargoOptions, name: &str, version: &str) -> Result<BTreeSet<Package>> {
+    let spec = format!("{}@{}", name, version.trim_start_matches('v'));
+    let output = run_cargo_tree(&["--invert", &spec, "--prefix", "depth"], options)?;
+    Ok(output
+        .lines()
+        .filter_map(parse_line)
+        .map(|(_, package)| package)
+        .collect())
+}
+
+/// One chain of dependents from a workspace member down to the given crate,
+/// each entry formatted as `name version`.
+pub fn dependency_path(options: &CargoOptions, name: &str, version: &str) -> Result<Vec<String>> {
+    let spec = format!("{}@{}", name, version.trim_start_matches('v'));
+    let output = run_cargo_tree(&["--invert", &spec, "--prefix", "depth"], options)?;
+
+    // The inverted tree starts at the crate; follow its first branch up to a root
+    let mut path = Vec::new();
+    for (depth, (name, version)) in output.lines().filter_map(parse_line) {
+        if depth != path.len() {
+            break;
+        }
+        path.push(format!("{} {}", name, version));
+    }
+    path.reverse();
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str) -> Package {
+        (name.to_string(), version.to_string())
+    }
+
+    #[test]
+    fn parses_annotated_lines() {
+        let cases = [
+            ("1anyhow v1.0.100", 1, "anyhow", "v1.0.100"),
+            ("4proc-macro2 v1.0.103 (*)", 4, "proc-macro2", "v1.0.103"),
+            (
+                "2serde_derive v1.0.228 (proc-macro)",
+                2,
+                "serde_derive",
+                "v1.0.228",
+            ),
+            ("1util v0.2.0 (/tmp/ws/util)", 1, "util", "v0.2.0"),
+            (
+                "1gitdep v0.1.0 (file:///tmp/gitdep#cb3128e8)",
+                1,
+                "gitdep",
+                "v0.1.0",
+            ),
+            ("0util v0.2.0 (/tmp/ws/util) (*)", 0, "util", "v0.2.0"),
+            (
+                "3windows-sys v0.59.0-rc.1",
+                3,
+                "windows-sys",
+                "v0.59.0-rc.1",
+            ),
+            ("  12syn 2.0.108", 12, "syn", "v2.0.108"),
+        ];
+        for (line, depth, name, version) in cases {
+            assert_eq!(
+                parse_line(line),
+                Some((depth, package(name, version))),
+                "{:?}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_lines_that_are_not_packages() {
+        for line in [
+            "",
+            "[build-dependencies]",
+            "[dev-dependencies]",
+            "serde v1.0.228",
+            "2serde feature \"derive\"",
+            "3serde_core feature \"default\" (*)",
+            "1anyhow",
+            "1anyhow v1",
+        ] {
+            assert_eq!(parse_line(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn parses_a_workspace_tree() {
+        // `cargo tree --prefix depth` for a two-member workspace where `app` depends on
+        // `util` by path, and the two pull in different major versions of thiserror
+        let packages = parse_tree(include_str!("../tests/fixtures/cargo-tree.txt"));
+
+        assert!(!packages.contains(&package("app", "v0.1.0")));
+        assert!(
+            !packages.contains(&package("util", "v0.2.0")),
+            "workspace members are not dependencies"
+        );
+        for (name, version) in [
+            ("gitdep", "v0.1.0"),
+            ("serde_derive", "v1.0.228"),
+            ("thiserror", "v1.0.69"),
+            ("thiserror", "v2.0.21"),
+            ("thiserror-impl", "v1.0.69"),
+            ("thiserror-impl", "v2.0.21"),
+            ("syn", "v2.0.108"),
+            ("syn", "v3.0.7"),
+        ] {
+            assert!(
+                packages.contains(&package(name, version)),
+                "{} {}",
+                name,
+                version
+            );
+        }
+        // proc-macro2 appears once in full and five times as `(*)`
+        assert_eq!(
+            packages
+                .iter()
+                .filter(|(name, _)| name == "proc-macro2")
+                .count(),
+            1
+        );
+        assert_eq!(packages.len(), 13);
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::deps::Resolution;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// A crate as it appeared in the `--baseline` report.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BaselineEntry {
+    pub version: String,
+    /// `None` when the crate had no score in the baseline.
+    pub score: Option<f64>,
+}
+
+/// How a crate's score moved since the baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    /// Not in the baseline.
+    New,
+    /// Scored both times; the difference is current minus baseline.
+    Scored(f64),
+    /// Missing a score on one side.
+    Unscored,
+}
+
+impl Change {
+    pub fn of(crate_score: &CrateScore) -> Change {
+        let Some(baseline) = &crate_score.baseline else {
+            return Change::New;
+        };
+        match (&crate_score.status, baseline.score) {
+            (ScoreStatus::Scored(current), Some(previous)) => Change::Scored(current - previous),
+            _ => Change::Unscored,
+        }
+    }
+
+    pub fn is_regression(self) -> bool {
+        matches!(self, Change::Scored(delta) if delta < 0.0)
+    }
+}
+
+/// Attach the matching `--baseline` entry to each crate, preferring the same version.
+///
+/// The baseline is a previous `--format json` report; only the name, version and score
+/// of each entry are read, so reports from older releases still load. A baseline
+/// resolved for another target or feature set is compared all the same, with a warning.
+pub fn apply_baseline(
+    path: &Path,
+    scores: &mut [CrateScore],
+    resolution: &Resolution,
+) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let report: Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    if let Ok(baseline) =
+        serde_json::from_value::<Resolution>(report["metadata"]["resolution"].clone())
+        && (baseline.target != resolution.target || baseline.features != resolution.features)
+    {
+        warn!(
+            "{} was resolved for a different target or feature set; score changes may \
+             come from that rather than from the crates",
+            path.display()
+        );
+    }
+    // Reports before the crates were wrapped in an object were a bare array
+    let crates = report.get("crates").unwrap_or(&report);
+    let entries: Vec<(&str, BaselineEntry)> = crates
+        .as_array()
+        .ok_or_else(|| anyhow!("{} is not a --format json report", path.display()))?
+        .iter()
+        .filter_map(|entry| {
+            let name = entry["name"].as_str()?;
+            let version = entry["version"].as_str()?.to_string();
+            let score = entry["status"]["scored"].as_f64();
+            Some((name, BaselineEntry { version, score }))
+        })
+        .collect();
+
+    for crate_score in scores {
+        let mut same_name = entries.iter().filter(|(name, _)| *name == crate_score.name);
+        let same_version = same_name
+            .clone()
+            .find(|(_, entry)| entry.version == crate_score.version);
+        crate_score.baseline = same_version
+            .or_else(|| same_name.next())
+            .map(|(_, e)| e.clone());
+    }
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+use tracing::{debug, warn};
+
+/// Response headers never written to a dump, whatever their value.
+const REDACTED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// Writes raw API responses for `--debug-dump`, one file per response.
+pub struct Dump {
+    dir: PathBuf,
+    /// Only keep error statuses and JSON responses that don't parse.
+    failures_only: bool,
+    /// Files written for each crate, for pointing at them from its errors.
+    by_crate: Mutex<HashMap<String, Vec<PathBuf>>>,
+}
+
+impl Dump {
+    pub fn new(dir: PathBuf, failures_only: bool) -> Self {
+        Dump {
+            dir,
+            failures_only,
+            by_crate: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Save one response as `<dir>/<host>/<crate>--<path>.txt`: the request line, the
+    /// status, the response headers and the body. Request headers, where tokens live,
+    /// are never written.
+    pub fn write(
+        &self,
+        method: &Method,
+        url: &Url,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+        crate_name: Option<&str>,
+    ) {
+        if self.failures_only && status.is_success() && !unparsable_json(headers, body) {
+            return;
+        }
+
+        let mut content = format!("{} {}\n{}\n", method, url, status);
+        for (name, value) in headers {
+            if !REDACTED_HEADERS.contains(&name.as_str()) {
+                content.push_str(&format!(
+                    "{}: {}\n",
+                    name,
+                    String::from_utf8_lossy(value.as_bytes())
+                ));
+            }
+        }
+        content.push('\n');
+        let mut content = content.into_bytes();
+        content.extend_from_slice(body);
+
+        let host = url.host_str().unwrap_or("unknown-host");
+        let mut stem = sanitize(url.path().trim_matches('/'));
+        if let Some(crate_name) = crate_name {
+            stem = format!("{}--{}", crate_name, stem);
+        }
+        match create_unique(&self.dir.join(sanitize(host)), &stem, &content) {
+            Ok(path) => {
+                debug!(url = %url, status = %status, dump = %path.display(), "dumped response");
+                if let Some(crate_name) = crate_name {
+                    self.by_crate
+                        .lock()
+                        .unwrap()
+                        .entry(crate_name.to_string())
+                        .or_default()
+                        .push(path);
+                }
+            }
+            Err(e) => warn!("Failed to write a --debug-dump file for {}: {}", url, e),
+        }
+    }
+
+    /// The files dumped while looking `crate_name` up.
+    pub fn files_for(&self, crate_name: &str) -> Vec<PathBuf> {
+        self.by_crate
+            .lock()
+            .unwrap()
+            .get(crate_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// A body labelled as JSON that isn't.
+fn unparsable_json(headers: &HeaderMap, body: &[u8]) -> bool {
+    let is_json = headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"));
+    is_json && serde_json::from_slice::<serde_json::Value>(body).is_err()
+}
+
+/// A file name from a URL path or crate name, keeping it to one path component.
+fn sanitize(s: &str) -> String {
+    let name: String = s
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '@') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.trim_start_matches('.') {
+        "" => "root".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Write `<dir>/<stem>.txt`, or `<stem>-2.txt` and so on when it is taken, e.g. by a
+/// second request for the same URL.
+fn create_unique(dir: &Path, stem: &str, content: &[u8]) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let mut n = 1;
+    loop {
+        let path = match n {
+            1 => dir.join(format!("{}.txt", stem)),
+            n => dir.join(format!("{}-{}.txt", stem, n)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(content)?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::score::CrateScore;
+
+/// A crate resolved at more than one version.
+#[derive(Serialize, JsonSchema)]
+pub struct Duplicate<'a> {
+    pub name: &'a str,
+    pub versions: Vec<DuplicateVersion<'a>>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct DuplicateVersion<'a> {
+    #[serde(skip)]
+    pub crate_score: &'a CrateScore,
+    pub version: &'a str,
+    /// Direct dependencies pulling this version in, as `name version`; empty when unknown,
+    /// e.g. for vendored crates.
+    pub required_by: &'a [String],
+}
+
+/// Every crate name appearing with more than one version, by name.
+pub fn find(scores: &[CrateScore]) -> Vec<Duplicate<'_>> {
+    let mut by_name: BTreeMap<&str, Vec<&CrateScore>> = BTreeMap::new();
+    for crate_score in scores {
+        by_name
+            .entry(&crate_score.name)
+            .or_default()
+            .push(crate_score);
+    }
+    by_name
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(name, versions)| Duplicate {
+            name,
+            versions: versions
+                .into_iter()
+                .map(|crate_score| DuplicateVersion {
+                    crate_score,
+                    version: &crate_score.version,
+                    required_by: &crate_score.required_by,
+                })
+                .collect(),
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::cache::CacheKind;
+use crate::fetch::Context;
+use crate::http::Http;
+use crate::repo::Repository;
+use crate::score::CrateScore;
+
+/// Repository health signals from the hosting forge.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RepoMetadata {
+    pub archived: bool,
+    pub stars: u64,
+    pub last_push: Option<DateTime<Utc>>,
+}
+
+/// Attach forge metadata to every crate with a GitHub or GitLab repository.
+///
+/// github.com repositories are fetched in GraphQL batches when a token is
+/// available; everything else falls back to one REST call per repository.
+pub async fn enrich(ctx: &Context, scores: &mut [CrateScore]) {
+    let repos: BTreeSet<&Repository> = scores.iter().filter_map(|c| c.repo.as_ref()).collect();
+
+    let mut metadata: HashMap<Repository, RepoMetadata> = HashMap::new();
+    let mut misses = Vec::new();
+    for repo in repos {
+        match ctx.cache.get(CacheKind::RepoMetadata, &repo.to_string()) {
+            Some(value) => {
+                if let Ok(repo_metadata) = serde_json::from_value(value) {
+                    metadata.insert(repo.clone(), repo_metadata);
+                }
+            }
+            None if ctx.cache.is_offline() => {}
+            None => misses.push(repo.clone()),
+        }
+    }
+
+    let (batched, single): (Vec<_>, Vec<_>) = misses
+        .into_iter()
+        .partition(|repo| repo.host == "github.com" && ctx.github.has_token());
+
+    let mut fetched = HashMap::new();
+    if !batched.is_empty() {
+        match ctx.github.repo_metadata_batch(&ctx.http, &batched).await {
+            Ok(results) => fetched.extend(results),
+            Err(e) => warn!("Failed to fetch GitHub metadata: {}", e),
+        }
+    }
+    let results = futures::future::join_all(single.iter().map(|repo| async move {
+        let result = if repo.host == "github.com" {
+            ctx.github.repo_metadata(&ctx.http, repo).await
+        } else {
+            gitlab_metadata(&ctx.http, repo).await
+        };
+        (repo, result)
+    }))
+    .await;
+    for (repo, result) in results {
+        match result {
+            Ok(repo_metadata) => {
+                fetched.insert(repo.clone(), repo_metadata);
+            }
+            Err(e) => debug!(repo = %repo, error = %e, "failed to fetch repository metadata"),
+        }
+    }
+
+    for (repo, repo_metadata) in fetched {
+        let value = serde_json::to_value(&repo_metadata).unwrap_or(Value::Null);
+        ctx.cache
+            .put(CacheKind::RepoMetadata, &repo.to_string(), &value);
+        if let Some(repo_metadata) = repo_metadata {
+            metadata.insert(repo, repo_metadata);
+        }
+    }
+
+    for crate_score in scores {
+        crate_score.repo_metadata = crate_score
+            .repo
+            .as_ref()
+            .and_then(|repo| metadata.get(repo).cloned());
+    }
+}
+
+/// Fetch metadata from a GitLab instance's REST API.
+async fn gitlab_metadata(http: &Http, repo: &Repository) -> Result<Option<RepoMetadata>> {
+    if !repo.host.starts_with("gitlab.") {
+        return Ok(None);
+    }
+    let url = format!(
+        "https://{}/api/v4/projects/{}",
+        repo.host,
+        repo.path.replace('/', "%2F")
+    );
+
+    let response = http
+        .send(
+            http.client()
+                .get(&url)
+                .header("User-Agent", "cargo-scorecard/0.1.0"),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to fetch GitLab metadata for {}: {}", repo, e))?;
+    debug!(url = %url, status = %response.status(), "GitLab API response");
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "GitLab API request failed for {}: {}",
+            repo,
+            response.status()
+        ));
+    }
+    let json: Value = response
+        .json()
+        .await
+        .map_err(|e| anyhow!("Failed to parse GitLab JSON for {}: {}", repo, e))?;
+
+    Ok(Some(RepoMetadata {
+        archived: json["archived"].as_bool().unwrap_or_default(),
+        stars: json["star_count"].as_u64().unwrap_or_default(),
+        last_push: json["last_activity_at"]
+            .as_str()
+            .and_then(|d| d.parse().ok()),
+    }))
+}
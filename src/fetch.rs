@@ -0,0 +1,790 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use futures::channel::mpsc;
+use futures::future::{Either, LocalBoxFuture, Shared, join_all};
+use futures::{FutureExt, StreamExt};
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::cache::{Cache, CacheKind, Uncached};
+use crate::deps::{CrateInfo, CrateSource};
+use crate::field::{self, Field};
+use crate::github::GitHub;
+use crate::http::{self, Http};
+use crate::interrupt;
+use crate::outage::OutageDetector;
+use crate::progress::Progress;
+use crate::provider::ScoreProvider;
+use crate::repo::{self, Repository};
+use crate::score::{self, CrateScore, ScoreStatus, SelfScore, Yank};
+use crate::scorecard::ScorecardResult;
+
+/// How long to wait on a repository when checking that it still exists.
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Crates in the scorecard stage at once; the per-host limits are the real ceilings.
+const SCORE_WORKERS: usize = 64;
+/// How long to wait before asking again for a scorecard that is still being computed.
+const PENDING_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Shared state for the fetch phase.
+pub struct Context {
+    pub http: Http,
+    pub cache: Cache,
+    pub github: GitHub,
+    /// Check whether unscanned repositories exist (off with `--no-verify-repos`).
+    pub verify_repos: bool,
+    /// Where to dump each scorecard response as is (`--raw-scorecard`).
+    pub raw_scorecard: Option<PathBuf>,
+    /// Gives up on the scorecard API after `--api-down-after` failures in a row.
+    pub scorecard_outage: OutageDetector,
+    /// Look crates up one at a time, in order (`--serial`).
+    pub serial: bool,
+    /// Where repository scores come from (`--provider`).
+    pub provider: Box<dyn ScoreProvider>,
+}
+
+/// crates.io answers 403 to clients breaking its crawler policy; retrying won't help.
+fn crates_io_forbidden() -> anyhow::Error {
+    anyhow!(
+        "crates.io refused the request (403 Forbidden); this usually means the User-Agent \
+         or request rate was rejected by its data access policy"
+    )
+}
+
+/// The crates.io API URL for a crate's metadata.
+pub fn crate_url(crate_name: &str) -> String {
+    format!("https://crates.io/api/v1/crates/{}", crate_name)
+}
+
+/// The scorecard API URL for a repository's result.
+pub fn scorecard_url(repo: &Repository) -> String {
+    format!("https://api.securityscorecards.dev/projects/{}", repo)
+}
+
+pub async fn fetch_crate_metadata(http: &Http, crate_name: &str) -> Result<Value> {
+    let url = crate_url(crate_name);
+
+    let response = http
+        .send(
+            http.client()
+                .get(&url)
+                .header("User-Agent", "cargo-scorecard/0.1.0"),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to fetch crate repo url for {}: {}", crate_name, e))?;
+    debug!(url = %url, status = %response.status(), "crates.io response");
+
+    if response.status() == StatusCode::FORBIDDEN {
+        return Err(crates_io_forbidden());
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "API request failed for {}: {}",
+            crate_name,
+            response.status()
+        ));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| anyhow!("Failed to parse JSON for {}: {}", crate_name, e))
+}
+
+/// Fetch the dependency list crates.io publishes for one version of a crate.
+pub async fn fetch_crate_dependencies(
+    http: &Http,
+    crate_name: &str,
+    version: &str,
+) -> Result<Value> {
+    let url = format!(
+        "https://crates.io/api/v1/crates/{}/{}/dependencies",
+        crate_name, version
+    );
+
+    let response = http
+        .send(
+            http.client()
+                .get(&url)
+                .header("User-Agent", "cargo-scorecard/0.1.0"),
+        )
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Failed to fetch dependencies of {} {}: {}",
+                crate_name,
+                version,
+                e
+            )
+        })?;
+    debug!(url = %url, status = %response.status(), "crates.io response");
+
+    if response.status() == StatusCode::FORBIDDEN {
+        return Err(crates_io_forbidden());
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "API request failed for {} {}: {}",
+            crate_name,
+            version,
+            response.status()
+        ));
+    }
+
+    response.json().await.map_err(|e| {
+        anyhow!(
+            "Failed to parse dependencies JSON for {} {}: {}",
+            crate_name,
+            version,
+            e
+        )
+    })
+}
+
+/// What crates.io says about a crate and the version in use.
+struct RegistryEntry {
+    repository: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    /// Set when the version in use has been yanked.
+    yanked: Option<Yank>,
+    published_at: Option<DateTime<Utc>>,
+    crate_created_at: Option<DateTime<Utc>>,
+    /// The link `repository` was inferred from, when the crate has none of its own.
+    repository_inferred_from: Option<&'static str>,
+}
+
+impl RegistryEntry {
+    /// Without a `repository`, fall back to a GitHub or GitLab repository behind the
+    /// homepage or, failing that, the documentation link.
+    fn infer_repository(mut self) -> Self {
+        if self.repository.is_some() {
+            return self;
+        }
+        let leads = [
+            ("homepage", &self.homepage),
+            ("documentation", &self.documentation),
+        ];
+        let inferred = leads
+            .into_iter()
+            .find_map(|(source, link)| Some((source, repo::infer_from_link(link.as_deref()?)?)));
+        if let Some((source, repo)) = inferred {
+            debug!(repository = %repo, source, "repository inferred from crate links");
+            self.repository = Some(format!("https://{}", repo));
+            self.repository_inferred_from = Some(source);
+        }
+        self
+    }
+}
+
+async fn fetch_registry_entry(ctx: &Context, crate_info: &CrateInfo) -> Result<RegistryEntry> {
+    let crate_name = crate_info.name.as_str();
+    let json = ctx
+        .cache
+        .get_or_fetch(CacheKind::Crate, crate_name, || {
+            fetch_crate_metadata(&ctx.http, crate_name)
+        })
+        .await?;
+
+    // Only the repository is essential; other links degrade to `None`, logged under --verbose
+    let link = |field: &str| field::optional_str(&json, "crates.io", &["crate", field]);
+    let lead = |field: &str| link(field).ok().flatten();
+
+    // The yank message is only set when the publisher gave one, and crates.io has no
+    // yank date; the version's last update is the closest it offers
+    let version = crate_info.version.trim_start_matches('v');
+    let entry = json["versions"]
+        .as_array()
+        .and_then(|versions| versions.iter().find(|v| v["num"] == version));
+    if entry.is_none() && crate_info.pinned_from.is_some() {
+        return Err(anyhow!(
+            "--pin {}@{}: crates.io has no such version",
+            crate_name,
+            version
+        ));
+    }
+    let timestamp = |value: &Value| value.as_str().and_then(|s| s.parse::<DateTime<Utc>>().ok());
+    let published_at = entry.and_then(|v| timestamp(&v["created_at"]));
+    let yanked = entry
+        .filter(|v| v["yanked"].as_bool() == Some(true))
+        .map(|v| Yank {
+            reason: v["yank_message"].as_str().map(|s| s.to_string()),
+            updated_at: v["updated_at"].as_str().map(|s| s.to_string()),
+        });
+
+    Ok(RegistryEntry {
+        repository: valid_repository(link("repository")?),
+        homepage: lead("homepage"),
+        documentation: lead("documentation"),
+        yanked,
+        published_at,
+        crate_created_at: lead("created_at").and_then(|s| s.parse().ok()),
+        repository_inferred_from: None,
+    }
+    .infer_repository())
+}
+
+/// Treat junk such as email addresses or local paths as no repository at all.
+fn valid_repository(repository: Option<String>) -> Option<String> {
+    match repository {
+        Some(url) if !repo::is_valid_url(&url) => {
+            info!(repository = %url, "ignoring invalid repository URL");
+            None
+        }
+        repository => repository,
+    }
+}
+
+/// Fetch the scorecard JSON for a repository, returning `Null` if it was never scanned.
+///
+/// Once the API looks down (see [`OutageDetector`]), fails without sending anything.
+async fn fetch_scorecard(ctx: &Context, repo: &Repository) -> Result<Value> {
+    let outage = &ctx.scorecard_outage;
+    if outage.is_down() {
+        return Err(outage.skipped());
+    }
+    let url = scorecard_url(repo);
+
+    let http = &ctx.http;
+    let response = http
+        .send(
+            http.client()
+                .get(&url)
+                .header("accept", "application/json")
+                .header("User-Agent", "cargo-scorecard/0.1.0"),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to fetch security score for {}: {}", repo, e))
+        .inspect_err(|e| outage.failed(e))?;
+    debug!(url = %url, status = %response.status(), "scorecard response");
+
+    if response.status() == StatusCode::NOT_FOUND {
+        outage.answered();
+        return Ok(Value::Null);
+    }
+    if !response.status().is_success() {
+        let e = anyhow!(
+            "Security scorecard API request failed for {}: {}",
+            repo,
+            response.status()
+        );
+        outage.failed(&e);
+        return Err(e);
+    }
+    outage.answered();
+
+    response
+        .json()
+        .await
+        .map_err(|e| anyhow!("Failed to parse security score JSON for {}: {}", repo, e))
+}
+
+/// The scorecard JSON for a repository, from the cache when it is still current.
+///
+/// A cached result is current when the commit it scanned (`repo.commit`) is still the
+/// tip of the default branch, or, when that can't be determined, while it is within
+/// the cache TTL.
+async fn cached_scorecard(ctx: &Context, repo: &Repository) -> Result<Value> {
+    let key = repo.to_string();
+    // Peeked without counting, so `--stats` sees one lookup per repository
+    if !ctx.cache.is_offline()
+        && let Some(cached) = ctx.cache.peek(CacheKind::Scorecard, &key)
+        && let Some(commit) = cached["repo"]["commit"].as_str()
+    {
+        match ctx.github.head_commit(&ctx.http, repo).await {
+            Ok(Some(head)) if head == commit => {
+                debug!(repo = %repo, commit = %commit, "cached scorecard matches the default branch");
+                ctx.cache.count(CacheKind::Scorecard, true);
+                ctx.cache.put(CacheKind::Scorecard, &key, &cached);
+                return Ok(cached);
+            }
+            Ok(Some(head)) => {
+                debug!(repo = %repo, cached = %commit, head = %head, "default branch moved");
+                ctx.cache.count(CacheKind::Scorecard, false);
+                let json = fetch_scorecard(ctx, repo).await?;
+                ctx.cache.put(CacheKind::Scorecard, &key, &json);
+                return Ok(json);
+            }
+            Ok(None) => {}
+            Err(e) => debug!(repo = %repo, error = %e, "falling back to the cache TTL"),
+        }
+    }
+
+    ctx.cache
+        .get_or_fetch(CacheKind::Scorecard, &key, || fetch_scorecard(ctx, repo))
+        .await
+}
+
+/// A scorecard body without a `score`, which the API serves while a scan is still running.
+///
+/// A null `score` counts too; a `score` of any other type is left for parsing to reject.
+fn is_pending(json: &Value) -> bool {
+    match field::lookup(json, &["score"]) {
+        Field::Absent if json.is_object() => {
+            debug!(shape = %field::shape(json), "scorecard has no score field");
+            true
+        }
+        Field::Null => true,
+        _ => false,
+    }
+}
+
+/// Fetch the scorecard result for a repository, returning `None` if it was never scanned.
+///
+/// A result without a score means the scan is still running; it is retried once and
+/// then returned as is, but never kept in the cache.
+async fn fetch_security_score(ctx: &Context, repo: &Repository) -> Result<Option<ScorecardResult>> {
+    let mut json = cached_scorecard(ctx, repo).await?;
+
+    if is_pending(&json) && !ctx.cache.is_offline() {
+        debug!(repo = %repo, "scorecard is still being computed; retrying");
+        tokio::time::sleep(PENDING_RETRY_DELAY).await;
+        json = fetch_scorecard(ctx, repo).await?;
+        if is_pending(&json) {
+            ctx.cache.remove(CacheKind::Scorecard, &repo.to_string());
+        } else {
+            ctx.cache
+                .put(CacheKind::Scorecard, &repo.to_string(), &json);
+        }
+    }
+
+    if json.is_null() {
+        return Ok(None);
+    }
+    if let Some(dir) = &ctx.raw_scorecard {
+        write_raw_scorecard(dir, repo, &json);
+    }
+    let result: ScorecardResult = serde_json::from_value(json)
+        .map_err(|e| anyhow!("Unexpected scorecard JSON for {}: {}", repo, e))?;
+    Ok(Some(result))
+}
+
+/// Save a scorecard response as `<dir>/<host>/<owner>/<repo>.json`.
+fn write_raw_scorecard(dir: &Path, repo: &Repository, json: &Value) {
+    let path = dir.join(&repo.host).join(format!("{}.json", repo.path));
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            let body = serde_json::to_string_pretty(json).expect("a JSON value always serializes");
+            std::fs::write(&path, body)
+        });
+    if let Err(e) = written {
+        warn!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+/// Fetch the scorecard result, retrying under the canonical name if a GitHub repo was renamed.
+pub async fn fetch_repo_score(ctx: &Context, repo: &Repository) -> Result<Option<ScorecardResult>> {
+    if let Some(result) = fetch_security_score(ctx, repo).await? {
+        return Ok(Some(result));
+    }
+
+    // A failed lookup only means we can't follow a rename, not that scoring failed
+    let canonical = match ctx.github.canonical_repo(&ctx.http, &ctx.cache, repo).await {
+        Ok(Some(canonical)) => canonical,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            debug!(repository = %repo, error = %e, "rename lookup failed");
+            return Ok(None);
+        }
+    };
+    info!(from = %repo, to = %canonical, "repository was renamed");
+    fetch_security_score(ctx, &canonical).await
+}
+
+/// Tell a deleted or private repository apart from one that was merely never scanned.
+///
+/// Returns the status and whether the repository is known to exist; `None` when the
+/// host gave no clear answer (rate limits, timeouts, odd statuses).
+async fn verify_repo(ctx: &Context, repo: &Repository) -> (ScoreStatus, Option<bool>) {
+    let status = ctx
+        .cache
+        .get_or_fetch(CacheKind::RepoStatus, &repo.to_string(), || async {
+            let url = format!("https://{}", repo);
+            let head = ctx.http.client().head(&url);
+            let mut response = ctx.http.send(head.timeout(VERIFY_TIMEOUT)).await?;
+            // Some forges reject HEAD; retry those with a plain GET
+            if response.status() == StatusCode::METHOD_NOT_ALLOWED {
+                let get = ctx.http.client().get(&url);
+                response = ctx.http.send(get.timeout(VERIFY_TIMEOUT)).await?;
+            }
+            debug!(url = %url, status = %response.status(), "repository check");
+            // Only definitive answers are cached; rate limits and outages are asked again
+            match response.status().as_u16() {
+                code @ (200..=399 | 404 | 410) => Ok(Value::from(code)),
+                code => Err(anyhow!("{} answered {} for {}", repo.host, code, repo)),
+            }
+        })
+        .await;
+
+    match status.ok().and_then(|status| status.as_u64()) {
+        Some(code @ (404 | 410)) => (ScoreStatus::Unreachable(code as u16), Some(false)),
+        Some(200..=399) => (ScoreStatus::NotScanned, Some(true)),
+        _ => (ScoreStatus::NotScanned, None),
+    }
+}
+
+/// What looking up a repository link found.
+#[derive(Clone)]
+struct RepositoryLookup {
+    status: ScoreStatus,
+    scorecard: Option<ScorecardResult>,
+    /// Whether the repository exists, when it was checked.
+    exists: Option<bool>,
+}
+
+/// Score a repository link, checking unscanned repositories exist when verifying.
+async fn repository_status(
+    ctx: &Context,
+    repository: Option<&str>,
+    repo: Option<&Repository>,
+) -> RepositoryLookup {
+    let mut exists = None;
+    let mut scorecard = None;
+    let status = match (repository, repo) {
+        (None, _) => ScoreStatus::NoRepository,
+        (Some(_), None) => ScoreStatus::NotScanned,
+        (Some(_), Some(repo)) if !ctx.provider.supports(repo) => {
+            ScoreStatus::UnsupportedForge(repo.host.clone())
+        }
+        (Some(_), Some(repo)) => match ctx.provider.score(ctx, repo).await {
+            Ok(Some(result)) => {
+                exists = Some(true);
+                let status = match result.score {
+                    Some(score) => ScoreStatus::Scored(score),
+                    None => ScoreStatus::Pending,
+                };
+                scorecard = Some(result);
+                status
+            }
+            Ok(None) if ctx.verify_repos => {
+                let (status, verified) = verify_repo(ctx, repo).await;
+                exists = verified;
+                status
+            }
+            Ok(None) => ScoreStatus::NotScanned,
+            Err(e) if e.is::<Uncached>() => ScoreStatus::Uncached,
+            Err(e) => ScoreStatus::Error(e.to_string()),
+        },
+    };
+    RepositoryLookup {
+        status,
+        scorecard,
+        exists,
+    }
+}
+
+/// Score the project's own repository.
+pub async fn fetch_self_score(ctx: &Context, repository: Option<String>) -> SelfScore {
+    let repository = valid_repository(repository);
+    let repo = repository.as_deref().and_then(Repository::parse);
+    let lookup = repository_status(ctx, repository.as_deref(), repo.as_ref()).await;
+    SelfScore {
+        repository,
+        repo,
+        status: lookup.status,
+    }
+}
+
+/// Repository lookups shared by every crate pointing at the same repository, keyed by
+/// its normalized `host/owner/repo` form.
+type SharedLookups<'a> = Mutex<HashMap<String, Shared<LocalBoxFuture<'a, RepositoryLookup>>>>;
+
+/// Fetch every crate's score, giving up on whatever is outstanding once `deadline` has passed.
+///
+/// The fetch runs as two overlapping stages: crates.io lookups feed a channel of repository
+/// links, drained by a pool of scorecard fetchers that look each repository up once however
+/// many crates share it. Failures are recorded in the row's status rather than dropping the
+/// crate, and rows come back in the order of `crates`. Under `--serial`, each crate goes
+/// through both stages before the next one starts.
+pub async fn fetch_all(
+    ctx: &Context,
+    crates: &[CrateInfo],
+    deadline: Option<Instant>,
+    progress: &Progress,
+) -> Vec<CrateScore> {
+    let lookups = SharedLookups::default();
+    let lookups = &lookups;
+    let mut rows = if ctx.serial {
+        let mut rows = Vec::with_capacity(crates.len());
+        for (index, crate_info) in crates.iter().enumerate() {
+            let links = registry_stage(ctx, crate_info, deadline).await;
+            rows.push((
+                index,
+                scorecard_stage(ctx, lookups, crate_info, links, deadline).await,
+            ));
+            progress.tick();
+        }
+        rows
+    } else {
+        let (sender, receiver) = mpsc::unbounded();
+        let registry = async move {
+            join_all(crates.iter().enumerate().map(|(index, crate_info)| {
+                let sender = sender.clone();
+                async move {
+                    let links = registry_stage(ctx, crate_info, deadline).await;
+                    // The scorecard stage keeps receiving until every sender is gone
+                    let _ = sender.unbounded_send((index, links));
+                }
+            }))
+            .await;
+        };
+        let scoring = receiver
+            .map(
+                |(index, links): (usize, Result<RegistryEntry, ScoreStatus>)| {
+                    let crate_info = &crates[index];
+                    async move {
+                        let row = scorecard_stage(ctx, lookups, crate_info, links, deadline).await;
+                        progress.tick();
+                        (index, row)
+                    }
+                },
+            )
+            .buffer_unordered(SCORE_WORKERS)
+            .collect::<Vec<_>>();
+        let ((), rows) = futures::future::join(registry, scoring).await;
+        rows
+    };
+    progress.finish();
+    rows.sort_by_key(|(index, _)| *index);
+    if let Some(dump) = ctx.http.dump() {
+        for (_, row) in &mut rows {
+            if let ScoreStatus::Error(e) = &mut row.status {
+                let files = dump.files_for(&row.name);
+                if !files.is_empty() {
+                    let files: Vec<String> =
+                        files.iter().map(|f| f.display().to_string()).collect();
+                    e.push_str(&format!(" (responses saved in {})", files.join(", ")));
+                }
+            }
+        }
+    }
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+/// A crate's links from crates.io (or its manifest), or the status of why there are none.
+async fn registry_stage(
+    ctx: &Context,
+    crate_info: &CrateInfo,
+    deadline: Option<Instant>,
+) -> Result<RegistryEntry, ScoreStatus> {
+    // A `--repo-map` entry still scores a path dependency
+    if crate_info.source == Some(CrateSource::Path) && crate_info.mapped_repository.is_none() {
+        return Err(ScoreStatus::Local);
+    }
+    let fetch = http::CRATE.scope(crate_info.name.clone(), registry_links(ctx, crate_info));
+    match with_deadline(deadline, fetch).await {
+        Ok(Ok(links)) => Ok(links),
+        Ok(Err(e)) if e.is::<Uncached>() => Err(ScoreStatus::Uncached),
+        Ok(Err(e)) => Err(ScoreStatus::Error(e.to_string())),
+        Err(status) => Err(status),
+    }
+}
+
+/// A crate's row, once its repository has been looked up.
+async fn scorecard_stage<'a>(
+    ctx: &'a Context,
+    lookups: &SharedLookups<'a>,
+    crate_info: &CrateInfo,
+    links: Result<RegistryEntry, ScoreStatus>,
+    deadline: Option<Instant>,
+) -> CrateScore {
+    match links {
+        Ok(links) => {
+            match with_deadline(deadline, lookup(ctx, lookups, crate_info, &links)).await {
+                Ok(lookup) => crate_score(crate_info, links, lookup),
+                Err(status) => CrateScore::unfetched(crate_info, status),
+            }
+        }
+        Err(status) => CrateScore::unfetched(crate_info, status),
+    }
+}
+
+/// Run `future` to completion, or give up with the status of what cut it short: the
+/// `deadline` passing, or Ctrl-C. Nothing new starts once Ctrl-C was pressed.
+async fn with_deadline<T>(
+    deadline: Option<Instant>,
+    future: impl Future<Output = T>,
+) -> Result<T, ScoreStatus> {
+    if interrupt::is_interrupted() {
+        return Err(ScoreStatus::Interrupted);
+    }
+    let limited = async move {
+        match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, future)
+                .await
+                .map_err(|_| ScoreStatus::SkippedDeadline),
+            None => Ok(future.await),
+        }
+    };
+    let abandoned = async {
+        interrupt::abandoned().await;
+        Err(ScoreStatus::Interrupted)
+    };
+    futures::pin_mut!(limited, abandoned);
+    match futures::future::select(limited, abandoned).await {
+        Either::Left((result, _)) | Either::Right((result, _)) => result,
+    }
+}
+
+/// The repository and other links for a crate, straight from the manifest for vendored
+/// crates, from the list for `--repos-file` entries and from the URL for git
+/// dependencies, with any `--repo-map` repository taking precedence.
+#[tracing::instrument(
+    skip_all,
+    fields(crate.name = %crate_info.name, crate.version = %crate_info.version),
+    err(level = "debug")
+)]
+async fn registry_links(ctx: &Context, crate_info: &CrateInfo) -> Result<RegistryEntry> {
+    if let Some(repository) = &crate_info.listed_repository {
+        return Ok(RegistryEntry {
+            repository: Some(repository.clone()),
+            homepage: None,
+            documentation: None,
+            yanked: None,
+            published_at: None,
+            crate_created_at: None,
+            repository_inferred_from: None,
+        });
+    }
+    let mut entry = match (&crate_info.vendored, &crate_info.source) {
+        // The crate may not be on crates.io at all, or be another crate of the same name
+        (None, Some(CrateSource::Git { url })) => RegistryEntry {
+            repository: valid_repository(Some(url.clone())),
+            homepage: None,
+            documentation: None,
+            yanked: None,
+            published_at: None,
+            crate_created_at: None,
+            repository_inferred_from: None,
+        },
+        (Some(vendored), _) => RegistryEntry {
+            repository: valid_repository(vendored.repository.clone()),
+            homepage: vendored.homepage.clone(),
+            documentation: vendored.documentation.clone(),
+            yanked: None,
+            published_at: None,
+            crate_created_at: None,
+            repository_inferred_from: None,
+        }
+        .infer_repository(),
+        (None, _) => fetch_registry_entry(ctx, crate_info).await?,
+    };
+    if let Some(repository) = &crate_info.mapped_repository {
+        entry.repository = Some(repository.clone());
+        entry.repository_inferred_from = None;
+    }
+    Ok(entry)
+}
+
+/// Look up a crate's repository, joining any lookup of the same repository already started.
+async fn lookup<'a>(
+    ctx: &'a Context,
+    lookups: &SharedLookups<'a>,
+    crate_info: &CrateInfo,
+    links: &RegistryEntry,
+) -> RepositoryLookup {
+    let Some(repository) = links.repository.clone() else {
+        return repository_status(ctx, None, None).await;
+    };
+    // Keyed by the normalized repository, so `.git`, trailing-slash and deep-link
+    // spellings of one repository share a lookup
+    let repo = Repository::parse(&repository);
+    let key = repo
+        .as_ref()
+        .map_or_else(|| repository.clone(), Repository::to_string);
+    let shared = lookups
+        .lock()
+        .unwrap()
+        .entry(key)
+        .or_insert_with(|| {
+            // Slow requests are attributed to the crate that started the lookup
+            let lookup =
+                async move { repository_status(ctx, Some(&repository), repo.as_ref()).await };
+            http::CRATE
+                .scope(crate_info.name.clone(), lookup)
+                .boxed_local()
+                .shared()
+        })
+        .clone();
+    shared.await
+}
+
+/// Assemble a crate's row from its crates.io links and repository lookup.
+fn crate_score(
+    crate_info: &CrateInfo,
+    links: RegistryEntry,
+    lookup: RepositoryLookup,
+) -> CrateScore {
+    CrateScore {
+        name: crate_info.name.clone(),
+        version: crate_info.version.clone(),
+        direct: crate_info.direct,
+        kind: crate_info.kind,
+        depth: crate_info.depth,
+        introduced_by: crate_info.introduced_by.clone(),
+        projects: crate_info.projects.clone(),
+        required_by: Vec::new(),
+        build_time: crate_info.build_time,
+        unique_dependencies: crate_info.unique_dependencies,
+        reach: crate_info.reach,
+        risk: score::risk(&lookup.status, crate_info.reach),
+        listed: crate_info.listed_repository.is_some(),
+        pinned_from: crate_info.pinned_from.clone(),
+        changed: crate_info.changed.clone(),
+        source: crate_info.source.clone(),
+        repo: links.repository.as_deref().and_then(Repository::parse),
+        repository: links.repository,
+        repository_inferred_from: links.repository_inferred_from.map(str::to_string),
+        repo_exists: lookup.exists,
+        repo_verified: None,
+        repo_mismatch: false,
+        homepage: links.homepage,
+        documentation: links.documentation,
+        yanked: links.yanked,
+        published_at: links.published_at,
+        crate_created_at: links.crate_created_at,
+        status: lookup.status,
+        attempts: 1,
+        custom_score: None,
+        scorecard: lookup.scorecard,
+        advisories: None,
+        repo_metadata: None,
+        baseline: None,
+        trusted: false,
+        waiver: None,
+        unlocked: crate_info.is_unlocked(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn a_body_without_score_is_pending() {
+        let partial: Value =
+            serde_json::from_str(include_str!("../tests/fixtures/scorecard-pending.json")).unwrap();
+        assert!(is_pending(&partial));
+        assert!(is_pending(&json!({"date": "2024-06-20", "score": null})));
+    }
+
+    #[test]
+    fn a_scored_or_malformed_body_is_not_pending() {
+        assert!(!is_pending(&json!({"date": "2024-06-20", "score": 7.3})));
+        assert!(!is_pending(&json!({"score": "7.3"})));
+        assert!(!is_pending(&json!([])));
+        assert!(!is_pending(&json!("not found")));
+    }
+}
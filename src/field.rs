@@ -0,0 +1,187 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use tracing::debug;
+
+/// A field looked up in an upstream API response.
+#[derive(Debug, PartialEq)]
+pub enum Field<'a> {
+    /// The field, or an object on its path, is missing: the API's shape has changed.
+    Absent,
+    /// The API sent the field with no value.
+    Null,
+    Value(&'a Value),
+}
+
+/// Follow `path` through nested objects, e.g. `["crate", "repository"]`.
+pub fn lookup<'a>(json: &'a Value, path: &[&str]) -> Field<'a> {
+    let mut value = json;
+    for key in path {
+        match value.as_object().and_then(|object| object.get(*key)) {
+            Some(next) => value = next,
+            None => return Field::Absent,
+        }
+    }
+    match value {
+        Value::Null => Field::Null,
+        value => Field::Value(value),
+    }
+}
+
+/// A string field that may be null but must be present.
+///
+/// `api` names the service in errors, e.g. `crates.io`. A missing field or a value
+/// of another type is an error, with the response's shape logged under `--verbose`.
+pub fn optional_str(json: &Value, api: &str, path: &[&str]) -> Result<Option<String>> {
+    match lookup(json, path) {
+        Field::Null => Ok(None),
+        Field::Value(Value::String(s)) => Ok(Some(s.clone())),
+        Field::Value(value) => {
+            debug!(api, field = %path.join("."), shape = %shape(json), "unexpected response shape");
+            let found = type_name(value);
+            let article = if found.starts_with(['a', 'o']) {
+                "an"
+            } else {
+                "a"
+            };
+            Err(anyhow!(
+                "{} response has {} {} where `{}` should be a string; the API may have changed",
+                api,
+                article,
+                found,
+                path.join(".")
+            ))
+        }
+        Field::Absent => {
+            debug!(api, field = %path.join("."), shape = %shape(json), "unexpected response shape");
+            Err(anyhow!(
+                "{} response has no `{}` field; the API may have changed (run with --verbose \
+                 to see what it returned)",
+                api,
+                path.join(".")
+            ))
+        }
+    }
+}
+
+/// The keys of a response, two levels deep, without their values.
+pub fn shape(json: &Value) -> String {
+    match json.as_object() {
+        Some(object) => {
+            let keys: Vec<String> = object
+                .iter()
+                .map(|(key, value)| match value.as_object() {
+                    Some(inner) => {
+                        let inner: Vec<&str> = inner.keys().map(String::as_str).collect();
+                        format!("{}: {{{}}}", key, inner.join(", "))
+                    }
+                    None => format!("{}: {}", key, type_name(value)),
+                })
+                .collect();
+            format!("{{{}}}", keys.join(", "))
+        }
+        None => type_name(json).to_string(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A crates.io `/api/v1/crates/<name>` response, trimmed to the fields we read.
+    fn crate_response() -> Value {
+        json!({
+            "crate": {
+                "name": "anyhow",
+                "repository": "https://github.com/dtolnay/anyhow",
+                "homepage": null,
+                "documentation": "https://docs.rs/anyhow"
+            },
+            "versions": []
+        })
+    }
+
+    #[test]
+    fn reads_the_current_shape() {
+        let json = crate_response();
+        assert_eq!(
+            optional_str(&json, "crates.io", &["crate", "repository"]).unwrap(),
+            Some("https://github.com/dtolnay/anyhow".to_string())
+        );
+        assert_eq!(
+            optional_str(&json, "crates.io", &["crate", "homepage"]).unwrap(),
+            None
+        );
+        assert_eq!(
+            lookup(&json, &["crate", "name"]),
+            Field::Value(&json!("anyhow"))
+        );
+    }
+
+    #[test]
+    fn tells_absent_from_null() {
+        let json = crate_response();
+        assert_eq!(lookup(&json, &["crate", "homepage"]), Field::Null);
+        assert_eq!(lookup(&json, &["crate", "license"]), Field::Absent);
+        assert_eq!(lookup(&json, &["package", "repository"]), Field::Absent);
+        assert_eq!(lookup(&json, &[]), Field::Value(&json));
+    }
+
+    #[test]
+    fn a_renamed_field_is_an_error() {
+        let mut json = crate_response();
+        let repository = json["crate"]["repository"].take();
+        json["crate"]["repository_url"] = repository;
+        json["crate"].as_object_mut().unwrap().remove("repository");
+
+        let error = optional_str(&json, "crates.io", &["crate", "repository"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "crates.io response has no `crate.repository` field; the API may have changed \
+             (run with --verbose to see what it returned)"
+        );
+    }
+
+    #[test]
+    fn a_moved_object_is_an_error() {
+        let json = json!({ "package": crate_response()["crate"].clone() });
+        assert!(optional_str(&json, "crates.io", &["crate", "repository"]).is_err());
+        assert_eq!(lookup(&json!([1, 2]), &["crate"]), Field::Absent);
+    }
+
+    #[test]
+    fn a_retyped_field_is_an_error() {
+        let mut json = crate_response();
+        json["crate"]["repository"] = json!({ "url": "https://github.com/dtolnay/anyhow" });
+
+        let error = optional_str(&json, "crates.io", &["crate", "repository"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "crates.io response has an object where `crate.repository` should be a string; \
+             the API may have changed"
+        );
+    }
+
+    #[test]
+    fn shape_lists_keys_without_values() {
+        let json =
+            json!({ "date": "2024-06-20", "repo": { "name": "x", "commit": "y" }, "score": 7.3 });
+        assert_eq!(
+            shape(&json),
+            "{date: string, repo: {commit, name}, score: number}"
+        );
+        assert_eq!(shape(&json!(null)), "null");
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use reqwest::{Client, StatusCode, header, redirect};
+use serde_json::{Value, json};
+use tracing::{debug, warn};
+
+use crate::cache::{Cache, CacheKind};
+use crate::enrich::RepoMetadata;
+use crate::http::Http;
+use crate::repo::Repository;
+
+/// Repositories per GraphQL query, well under GitHub's node limits.
+const GRAPHQL_BATCH_SIZE: usize = 50;
+
+/// Access to GitHub, used to resolve renamed repositories and fetch repository metadata.
+pub struct GitHub {
+    token: Option<String>,
+    /// Client that reports redirects instead of following them.
+    no_redirect: Client,
+}
+
+/// The REST API URL for a github.com repository.
+pub fn repo_api_url(repo: &Repository) -> String {
+    format!("https://api.github.com/repos/{}", repo.path)
+}
+
+impl GitHub {
+    pub fn new(token: Option<String>) -> Result<Self> {
+        let no_redirect = Client::builder()
+            .redirect(redirect::Policy::none())
+            .build()
+            .map_err(|e| anyhow!("Failed to build HTTP client: {}", e))?;
+
+        Ok(GitHub { token, no_redirect })
+    }
+
+    /// Resolve the canonical location of a github.com repository.
+    ///
+    /// Returns `Some` only when the repository has moved. Uses the REST API when a
+    /// token is configured and falls back to observing the web redirect otherwise.
+    pub async fn canonical_repo(
+        &self,
+        http: &Http,
+        cache: &Cache,
+        repo: &Repository,
+    ) -> Result<Option<Repository>> {
+        if repo.host != "github.com" {
+            return Ok(None);
+        }
+
+        let canonical = cache
+            .get_or_fetch(CacheKind::GitHubCanonical, &repo.to_string(), || async {
+                let resolved = match &self.token {
+                    Some(token) => self.canonical_from_api(http, token, repo).await?,
+                    None => self.canonical_from_redirect(http, repo).await?,
+                };
+                Ok(resolved
+                    .filter(|canonical| canonical != repo)
+                    .map_or(Value::Null, |canonical| Value::from(canonical.to_string())))
+            })
+            .await?;
+
+        Ok(canonical
+            .as_str()
+            .and_then(|canonical| Repository::parse(&format!("https://{}", canonical))))
+    }
+
+    async fn canonical_from_api(
+        &self,
+        http: &Http,
+        token: &str,
+        repo: &Repository,
+    ) -> Result<Option<Repository>> {
+        let url = repo_api_url(repo);
+
+        // The API answers renamed repositories with a redirect, which the client follows.
+        let response = http
+            .send(
+                http.client()
+                    .get(&url)
+                    .header("accept", "application/vnd.github+json")
+                    .header("User-Agent", "cargo-scorecard/0.1.0")
+                    .bearer_auth(token),
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to fetch GitHub metadata for {}: {}", repo, e))?;
+        debug!(url = %url, status = %response.status(), "GitHub API response");
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "GitHub API request failed for {}: {}",
+                repo,
+                response.status()
+            ));
+        }
+
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse GitHub JSON for {}: {}", repo, e))?;
+
+        Ok(json["full_name"]
+            .as_str()
+            .and_then(|full_name| Repository::parse(&format!("https://github.com/{}", full_name))))
+    }
+
+    async fn canonical_from_redirect(
+        &self,
+        http: &Http,
+        repo: &Repository,
+    ) -> Result<Option<Repository>> {
+        let url = format!("https://{}", repo);
+
+        let response = http
+            .send(
+                self.no_redirect
+                    .head(&url)
+                    .header("User-Agent", "cargo-scorecard/0.1.0"),
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to check {} for redirects: {}", url, e))?;
+        debug!(url = %url, status = %response.status(), "GitHub redirect check");
+
+        if !response.status().is_redirection() {
+            return Ok(None);
+        }
+
+        Ok(response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(Repository::parse))
+    }
+
+    /// The commit at the tip of a github.com repository's default branch.
+    ///
+    /// Returns `None` without a token, for other forges, or when the repository is gone,
+    /// since an anonymous lookup per repository would soon hit the rate limit.
+    pub async fn head_commit(&self, http: &Http, repo: &Repository) -> Result<Option<String>> {
+        let Some(token) = &self.token else {
+            return Ok(None);
+        };
+        if repo.host != "github.com" {
+            return Ok(None);
+        }
+        let url = format!("https://api.github.com/repos/{}/commits/HEAD", repo.path);
+
+        let response = http
+            .send(
+                http.client()
+                    .get(&url)
+                    .header("accept", "application/vnd.github.sha")
+                    .header("User-Agent", "cargo-scorecard/0.1.0")
+                    .bearer_auth(token),
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to fetch the default branch of {}: {}", repo, e))?;
+        debug!(url = %url, status = %response.status(), "GitHub API response");
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "GitHub API request failed for {}: {}",
+                repo,
+                response.status()
+            ));
+        }
+
+        let sha = response
+            .text()
+            .await
+            .map_err(|e| anyhow!("Failed to read the default branch of {}: {}", repo, e))?;
+        Ok(Some(sha.trim().to_string()))
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Fetch metadata for many repositories with batched GraphQL queries.
+    ///
+    /// Requires a token. Repositories that do not exist map to `None`; batches
+    /// are skipped once the token's rate limit is exhausted.
+    pub async fn repo_metadata_batch(
+        &self,
+        http: &Http,
+        repos: &[Repository],
+    ) -> Result<HashMap<Repository, Option<RepoMetadata>>> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or_else(|| anyhow!("GitHub GraphQL API requires a token"))?;
+
+        let mut metadata = HashMap::new();
+        for batch in repos.chunks(GRAPHQL_BATCH_SIZE) {
+            let fields = batch
+                .iter()
+                .enumerate()
+                .filter_map(|(i, repo)| {
+                    let (owner, name) = repo.path.split_once('/')?;
+                    Some(format!(
+                        "r{}: repository(owner: {}, name: {}) {{ isArchived stargazerCount pushedAt }}",
+                        i,
+                        Value::from(owner),
+                        Value::from(name)
+                    ))
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let query = format!("query {{ rateLimit {{ remaining }} {} }}", fields);
+
+            let response = http
+                .send(
+                    http.client()
+                        .post("https://api.github.com/graphql")
+                        .header("User-Agent", "cargo-scorecard/0.1.0")
+                        .bearer_auth(token)
+                        .json(&json!({ "query": query })),
+                )
+                .await
+                .map_err(|e| anyhow!("Failed to query GitHub GraphQL API: {}", e))?;
+            debug!(status = %response.status(), repos = batch.len(), "GitHub GraphQL response");
+
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "GitHub GraphQL request failed: {}",
+                    response.status()
+                ));
+            }
+            let json: Value = response
+                .json()
+                .await
+                .map_err(|e| anyhow!("Failed to parse GitHub GraphQL JSON: {}", e))?;
+
+            // Missing repositories come back as `null` data alongside an error entry
+            for (i, repo) in batch.iter().enumerate() {
+                let node = &json["data"][format!("r{}", i)];
+                let repo_metadata = (!node.is_null()).then(|| RepoMetadata {
+                    archived: node["isArchived"].as_bool().unwrap_or_default(),
+                    stars: node["stargazerCount"].as_u64().unwrap_or_default(),
+                    last_push: node["pushedAt"].as_str().and_then(|d| d.parse().ok()),
+                });
+                metadata.insert(repo.clone(), repo_metadata);
+            }
+
+            if json["data"]["rateLimit"]["remaining"].as_u64() == Some(0) {
+                warn!(
+                    "GitHub GraphQL rate limit exhausted; remaining repositories are not enriched"
+                );
+                break;
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Fetch metadata for a single repository through the REST API.
+    pub async fn repo_metadata(
+        &self,
+        http: &Http,
+        repo: &Repository,
+    ) -> Result<Option<RepoMetadata>> {
+        let url = repo_api_url(repo);
+        let mut request = http
+            .client()
+            .get(&url)
+            .header("accept", "application/vnd.github+json")
+            .header("User-Agent", "cargo-scorecard/0.1.0");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = http
+            .send(request)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch GitHub metadata for {}: {}", repo, e))?;
+        debug!(url = %url, status = %response.status(), "GitHub API response");
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "GitHub API request failed for {}: {}",
+                repo,
+                response.status()
+            ));
+        }
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse GitHub JSON for {}: {}", repo, e))?;
+
+        Ok(Some(RepoMetadata {
+            archived: json["archived"].as_bool().unwrap_or_default(),
+            stars: json["stargazers_count"].as_u64().unwrap_or_default(),
+            last_push: json["pushed_at"].as_str().and_then(|d| d.parse().ok()),
+        }))
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Lowest aggregate score that earns each letter; anything below `d` is an F.
+///
+/// Defaults, on the OpenSSF 0-10 scale: A ≥ 8.0, B ≥ 6.5, C ≥ 5.0, D ≥ 3.5.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct GradeCutoffs {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+}
+
+impl Default for GradeCutoffs {
+    fn default() -> Self {
+        GradeCutoffs {
+            a: 8.0,
+            b: 6.5,
+            c: 5.0,
+            d: 3.5,
+        }
+    }
+}
+
+/// The mean score of every scored crate, each weighted equally.
+pub fn aggregate(scores: &[CrateScore]) -> Option<f64> {
+    let scored: Vec<f64> = scores
+        .iter()
+        .filter_map(|c| match c.status {
+            ScoreStatus::Scored(score) => Some(score),
+            _ => None,
+        })
+        .collect();
+    if scored.is_empty() {
+        return None;
+    }
+    Some(scored.iter().sum::<f64>() / scored.len() as f64)
+}
+
+/// How many scored crates earn each letter under `cutoffs`, every letter listed.
+pub fn bands(scores: &[CrateScore], cutoffs: &GradeCutoffs) -> BTreeMap<char, usize> {
+    let mut bands: BTreeMap<char, usize> = "ABCDF".chars().map(|letter| (letter, 0)).collect();
+    for crate_score in scores {
+        if let ScoreStatus::Scored(score) = crate_score.status {
+            *bands.entry(letter(score, cutoffs)).or_default() += 1;
+        }
+    }
+    bands
+}
+
+/// One letter grade for the whole dependency tree.
+pub struct Grade {
+    pub letter: char,
+    /// The aggregate score the letter was derived from.
+    pub score: f64,
+}
+
+impl Grade {
+    /// Grade the [`aggregate`] score of the tree; `None` when no crate has a score.
+    pub fn for_scores(scores: &[CrateScore], cutoffs: &GradeCutoffs) -> Option<Self> {
+        let score = aggregate(scores)?;
+        Some(Grade {
+            letter: letter(score, cutoffs),
+            score,
+        })
+    }
+}
+
+/// The letter a score earns under `cutoffs`.
+pub fn letter(score: f64, cutoffs: &GradeCutoffs) -> char {
+    [
+        ('A', cutoffs.a),
+        ('B', cutoffs.b),
+        ('C', cutoffs.c),
+        ('D', cutoffs.d),
+    ]
+    .into_iter()
+    .find(|(_, cutoff)| score >= *cutoff)
+    .map_or('F', |(letter, _)| letter)
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.letter)
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::cli::Args;
+use crate::grade;
+use crate::policy::Violations;
+use crate::score::CrateScore;
+
+/// Bars from lowest to highest, and the ASCII stand-ins for terminals without Unicode.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_BARS: [char; 8] = ['_', '.', '-', '~', '=', '+', '*', '#'];
+
+/// Most recent runs drawn in the `--trend` sparkline.
+const TREND_RUNS: usize = 30;
+
+/// One run as recorded in the `--history-db` file, a JSON object per line.
+#[derive(Serialize, Deserialize)]
+pub struct Run {
+    pub recorded_at: DateTime<Utc>,
+    /// Mean score of the scored crates; absent when none had a score.
+    pub aggregate_score: Option<f64>,
+    pub dependencies: usize,
+    /// Crates breaking the policy.
+    pub failing: usize,
+}
+
+impl Run {
+    pub fn new(scores: &[CrateScore], violations: &Violations) -> Self {
+        Run {
+            recorded_at: Utc::now(),
+            aggregate_score: grade::aggregate(scores),
+            dependencies: scores.len(),
+            failing: violations.failing_crates(),
+        }
+    }
+}
+
+/// The runs recorded in `path`, oldest first; none when the file doesn't exist yet.
+///
+/// Lines that don't parse, e.g. one cut short by a killed run, are skipped.
+pub fn load(path: &Path) -> Result<Vec<Run>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+    };
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(run) => Some(run),
+            Err(e) => {
+                debug!(error = %e, "skipping unreadable history line");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Append `run` to the history in `path`, creating the file and its directory.
+pub fn record(path: &Path, run: &Run) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let line = serde_json::to_string(run).expect("a run always serializes");
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The `--trend` line: a sparkline of the aggregate score over the last runs and the
+/// change since the previous run with a score, e.g.
+/// `Score trend (8 runs): ▃▄▄▅ ▆▇ 6.8, +0.3 since 2026-10-14`.
+///
+/// Runs without an aggregate score leave a gap in the sparkline.
+pub fn trend(runs: &[Run], args: &Args, unicode: bool) -> String {
+    let runs = &runs[runs.len().saturating_sub(TREND_RUNS)..];
+    let scored: Vec<(&Run, f64)> = runs
+        .iter()
+        .filter_map(|run| Some((run, run.aggregate_score?)))
+        .collect();
+    let Some(&(_, latest)) = scored.last() else {
+        return "Score trend: no scored runs recorded yet".to_string();
+    };
+
+    // Scaled to the range shown, so small moves stay visible; a flat line sits mid-height
+    let bars = if unicode { BARS } else { ASCII_BARS };
+    let (low, high) = scored
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(low, high), &(_, score)| {
+            (low.min(score), high.max(score))
+        });
+    let sparkline: String = runs
+        .iter()
+        .map(|run| match run.aggregate_score {
+            Some(_) if (high - low).abs() < f64::EPSILON => bars[bars.len() / 2],
+            Some(score) => {
+                let step = (score - low) / (high - low) * (bars.len() - 1) as f64;
+                bars[step.round() as usize]
+            }
+            None => ' ',
+        })
+        .collect();
+
+    let change = match scored.len().checked_sub(2).map(|i| scored[i]) {
+        Some((previous, score)) => {
+            let delta = args.format_score(latest - score);
+            let sign = if delta.starts_with('-') { "" } else { "+" };
+            format!(
+                ", {}{} since {}",
+                sign,
+                delta,
+                previous.recorded_at.date_naive()
+            )
+        }
+        None => ", the first scored run".to_string(),
+    };
+    format!(
+        "Score trend ({} run{}): {} {}{}",
+        runs.len(),
+        if runs.len() == 1 { "" } else { "s" },
+        sparkline,
+        args.format_score(latest),
+        change
+    )
+}
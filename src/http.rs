@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::config::HostLimitConfig;
+use crate::dump::Dump;
+
+/// Attempts per idempotent request, including the first.
+const MAX_ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled for each one after it.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// How long a host that answers 429 without a usable `Retry-After` is left alone.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+/// Longest `Retry-After` honoured, so one odd header can't stall the run.
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+/// How many of the slowest requests `--stats` lists.
+const SLOWEST_KEPT: usize = 10;
+
+tokio::task_local! {
+    /// The crate a request is made for, so `--stats` can attribute slow requests.
+    pub static CRATE: String;
+}
+
+/// Built-in limits for the hosts we talk to; anything else uses `DEFAULT_LIMIT`.
+const HOST_LIMITS: &[(&str, HostLimit)] = &[
+    // https://crates.io/data-access: at most one request per second.
+    (
+        "crates.io",
+        HostLimit {
+            rps: Some(1.0),
+            concurrency: 1,
+        },
+    ),
+    (
+        "api.securityscorecards.dev",
+        HostLimit {
+            rps: None,
+            concurrency: 32,
+        },
+    ),
+    (
+        "api.github.com",
+        HostLimit {
+            rps: None,
+            concurrency: 8,
+        },
+    ),
+    (
+        "github.com",
+        HostLimit {
+            rps: None,
+            concurrency: 8,
+        },
+    ),
+    (
+        "raw.githubusercontent.com",
+        HostLimit {
+            rps: None,
+            concurrency: 8,
+        },
+    ),
+    (
+        "api.osv.dev",
+        HostLimit {
+            rps: None,
+            concurrency: 16,
+        },
+    ),
+];
+
+const DEFAULT_LIMIT: HostLimit = HostLimit {
+    rps: None,
+    concurrency: 16,
+};
+
+#[derive(Clone, Copy, Debug)]
+struct HostLimit {
+    rps: Option<f64>,
+    concurrency: usize,
+}
+
+/// Rate limiter, concurrency ceiling and counters for a single host.
+struct HostGate {
+    semaphore: Semaphore,
+    interval: Option<Duration>,
+    next_slot: tokio::sync::Mutex<Instant>,
+    requests: AtomicU64,
+    retries: AtomicU64,
+    waited_nanos: AtomicU64,
+    /// Attempts that found every concurrency permit taken.
+    concurrency_blocked: AtomicU64,
+    /// Attempts held back for a later rate-limit slot.
+    rate_limited: AtomicU64,
+    /// After a 429, when requests to the host may resume.
+    cooldown_until: Mutex<Option<Instant>>,
+}
+
+impl HostGate {
+    fn new(limit: HostLimit) -> Self {
+        HostGate {
+            semaphore: Semaphore::new(limit.concurrency.max(1)),
+            interval: limit
+                .rps
+                .filter(|rps| *rps > 0.0)
+                .map(|rps| Duration::from_secs_f64(1.0 / rps)),
+            next_slot: tokio::sync::Mutex::new(Instant::now()),
+            requests: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            waited_nanos: AtomicU64::new(0),
+            concurrency_blocked: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0),
+            cooldown_until: Mutex::new(None),
+        }
+    }
+
+    /// Wait out any cooldown and then the next rate-limit slot, returning when the
+    /// request may start.
+    async fn wait_for_slot(&self) {
+        let cooldown = *self.cooldown_until.lock().unwrap();
+        if let Some(until) = cooldown.filter(|until| *until > Instant::now()) {
+            self.rate_limited.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep_until(until).await;
+        }
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + interval;
+            slot
+        };
+        if slot > Instant::now() {
+            self.rate_limited.fetch_add(1, Ordering::Relaxed);
+        }
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Hold back every request to the host for `delay`, unless a cooldown already
+    /// lasts longer; returns whether this one took effect.
+    fn cool_down(&self, delay: Duration) -> bool {
+        let until = Instant::now() + delay;
+        let mut cooldown = self.cooldown_until.lock().unwrap();
+        if cooldown.is_some_and(|current| current >= until) {
+            return false;
+        }
+        *cooldown = Some(until);
+        true
+    }
+}
+
+/// How long a 429 asks us to stay away: its `Retry-After`, or a default.
+fn cooldown(response: &Response) -> Duration {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| retry_after(value, Utc::now()))
+        .unwrap_or(DEFAULT_COOLDOWN)
+        .min(MAX_COOLDOWN)
+}
+
+/// Parse a `Retry-After` value, either delta-seconds or an HTTP-date; a date already
+/// past means no wait at all.
+fn retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Whether a failed attempt is worth repeating.
+///
+/// Connection errors, timeouts, 429 and 5xx responses are transient. Every other
+/// status is final: 400, 401, 403 and 404 won't change on a second try.
+fn should_retry(outcome: &reqwest::Result<Response>) -> bool {
+    match outcome {
+        Ok(response) => {
+            response.status() == StatusCode::TOO_MANY_REQUESTS
+                || response.status().is_server_error()
+        }
+        Err(e) => e.is_connect() || e.is_timeout(),
+    }
+}
+
+/// Request counters for one host, as reported by `--stats`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct HostStats {
+    pub host: String,
+    /// Attempts sent, retries included.
+    pub requests: u64,
+    pub retries: u64,
+    /// Time spent queued behind the concurrency and rate limits.
+    pub waited_secs: f64,
+    /// Attempts that had to wait for a free concurrency slot.
+    pub concurrency_blocked: u64,
+    /// Attempts that had to wait for a rate-limit slot.
+    pub rate_limited: u64,
+}
+
+/// One of the slowest requests of the run.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct SlowRequest {
+    /// Host and path; query strings are dropped.
+    pub url: String,
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+    pub secs: f64,
+}
+
+/// Write a response to the dump, handing back an identical one to read.
+async fn dumped(dump: &Dump, method: &Method, response: Response) -> reqwest::Result<Response> {
+    let url = response.url().clone();
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    let crate_name = CRATE.try_with(String::clone).ok();
+    dump.write(method, &url, status, &headers, &body, crate_name.as_deref());
+
+    let mut rebuilt = ::http::Response::new(body);
+    *rebuilt.status_mut() = status;
+    *rebuilt.version_mut() = version;
+    *rebuilt.headers_mut() = headers;
+    Ok(Response::from(rebuilt))
+}
+
+/// HTTP layer applying independent rate limits and concurrency ceilings per host.
+pub struct Http {
+    client: Client,
+    overrides: HashMap<String, HostLimitConfig>,
+    gates: Mutex<HashMap<String, Arc<HostGate>>>,
+    /// The slowest idempotent requests so far, slowest first.
+    slowest: Mutex<Vec<SlowRequest>>,
+    /// Where `--debug-dump` writes responses.
+    dump: Option<Dump>,
+}
+
+impl Http {
+    pub fn new(client: Client, overrides: HashMap<String, HostLimitConfig>) -> Self {
+        Http {
+            client,
+            overrides,
+            gates: Mutex::new(HashMap::new()),
+            slowest: Mutex::new(Vec::new()),
+            dump: None,
+        }
+    }
+
+    /// Save responses for `--debug-dump`.
+    pub fn with_dump(mut self, dump: Dump) -> Self {
+        self.dump = Some(dump);
+        self
+    }
+
+    pub fn dump(&self) -> Option<&Dump> {
+        self.dump.as_ref()
+    }
+
+    /// The shared client; requests built from it should be sent through [`Http::send`].
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Send a request once the limits for its host allow it.
+    ///
+    /// GET and HEAD requests are retried with backoff when [`should_retry`] says so;
+    /// anything else is sent exactly once.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let (client, request) = request.build_split();
+        let mut request = request?;
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        let gate = self.gate(&host);
+        let idempotent = matches!(*request.method(), Method::GET | Method::HEAD);
+        // Like the timings, a webhook's URL and response are secret: only our APIs are dumped
+        let dump = self
+            .dump
+            .as_ref()
+            .filter(|_| idempotent || HOST_LIMITS.iter().any(|(name, _)| *name == host));
+        let method = request.method().clone();
+
+        let mut attempt = 1;
+        loop {
+            let retry = request
+                .try_clone()
+                .filter(|_| idempotent && attempt < MAX_ATTEMPTS);
+
+            let started = Instant::now();
+            let permit = match gate.semaphore.try_acquire() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    gate.concurrency_blocked.fetch_add(1, Ordering::Relaxed);
+                    gate.semaphore.acquire().await.expect("semaphore closed")
+                }
+            };
+            gate.wait_for_slot().await;
+            gate.waited_nanos
+                .fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+            gate.requests.fetch_add(1, Ordering::Relaxed);
+            // Only idempotent requests are timed: a webhook URL is a secret
+            let url = idempotent.then(|| {
+                let url = request.url();
+                format!("{}{}", url.host_str().unwrap_or_default(), url.path())
+            });
+            let sent = Instant::now();
+            let outcome = client.execute(request).await;
+            drop(permit);
+            if let Some(url) = url {
+                self.record_duration(url, sent.elapsed());
+            }
+            // One 429 pauses the whole host, so the pool doesn't keep asking in parallel
+            if let Ok(response) = &outcome
+                && response.status() == StatusCode::TOO_MANY_REQUESTS
+            {
+                let delay = cooldown(response);
+                if gate.cool_down(delay) {
+                    debug!(host = %host, ?delay, "429 Too Many Requests; pausing requests to host");
+                }
+            }
+
+            match retry {
+                Some(next) if should_retry(&outcome) => {
+                    let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                    debug!(url = %next.url(), attempt, ?delay, "retrying request");
+                    gate.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                    attempt += 1;
+                }
+                _ => {
+                    return match (dump, outcome) {
+                        (Some(dump), Ok(response)) => dumped(dump, &method, response).await,
+                        (_, outcome) => outcome,
+                    };
+                }
+            }
+        }
+    }
+
+    /// Per-host counters, sorted by host name.
+    pub fn stats(&self) -> Vec<HostStats> {
+        let gates = self.gates.lock().unwrap();
+        let mut stats: Vec<HostStats> = gates
+            .iter()
+            .map(|(host, gate)| HostStats {
+                host: host.clone(),
+                requests: gate.requests.load(Ordering::Relaxed),
+                retries: gate.retries.load(Ordering::Relaxed),
+                waited_secs: Duration::from_nanos(gate.waited_nanos.load(Ordering::Relaxed))
+                    .as_secs_f64(),
+                concurrency_blocked: gate.concurrency_blocked.load(Ordering::Relaxed),
+                rate_limited: gate.rate_limited.load(Ordering::Relaxed),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.host.cmp(&b.host));
+        stats
+    }
+
+    /// The slowest requests of the run, slowest first.
+    pub fn slowest(&self) -> Vec<SlowRequest> {
+        self.slowest.lock().unwrap().clone()
+    }
+
+    fn record_duration(&self, url: String, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let mut slowest = self.slowest.lock().unwrap();
+        if slowest.len() == SLOWEST_KEPT && slowest.last().is_some_and(|r| r.secs >= secs) {
+            return;
+        }
+        let crate_name = CRATE.try_with(String::clone).ok();
+        let index = slowest.partition_point(|r| r.secs >= secs);
+        slowest.insert(
+            index,
+            SlowRequest {
+                url,
+                crate_name,
+                secs,
+            },
+        );
+        slowest.truncate(SLOWEST_KEPT);
+    }
+
+    fn gate(&self, host: &str) -> Arc<HostGate> {
+        let mut gates = self.gates.lock().unwrap();
+        let gate = gates
+            .entry(host.to_string())
+            .or_insert_with(|| Arc::new(HostGate::new(self.limit(host))));
+        Arc::clone(gate)
+    }
+
+    fn limit(&self, host: &str) -> HostLimit {
+        let mut limit = HOST_LIMITS
+            .iter()
+            .find(|(name, _)| *name == host)
+            .map_or(DEFAULT_LIMIT, |(_, limit)| *limit);
+        if let Some(config) = self.overrides.get(host) {
+            if config.rps.is_some() {
+                limit.rps = config.rps;
+            }
+            if let Some(concurrency) = config.concurrency {
+                limit.concurrency = concurrency;
+            }
+        }
+        limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16) -> reqwest::Result<Response> {
+        let response = http::Response::builder().status(status).body("").unwrap();
+        Ok(Response::from(response))
+    }
+
+    #[test]
+    fn retries_rate_limits_and_server_errors() {
+        for status in [429, 500, 502, 503, 504, 599] {
+            assert!(
+                should_retry(&response(status)),
+                "{} should be retried",
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn other_statuses_are_final() {
+        for status in [200, 204, 301, 304, 400, 401, 403, 404, 410, 422] {
+            assert!(
+                !should_retry(&response(status)),
+                "{} should be final",
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn retries_connection_errors_only() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let client = reqwest::Client::new();
+
+        // Nothing listens on port 1
+        let refused = runtime.block_on(client.get("http://127.0.0.1:1/").send());
+        assert!(should_retry(&refused));
+
+        let malformed = runtime.block_on(client.get("http://").send());
+        assert!(malformed.is_err());
+        assert!(!should_retry(&malformed));
+    }
+
+    #[test]
+    fn reads_retry_after_seconds_and_dates() {
+        let now = DateTime::parse_from_rfc2822("Sun, 06 Nov 1994 08:49:37 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(retry_after(" 120 ", now), Some(Duration::from_secs(120)));
+        assert_eq!(
+            retry_after("Sun, 06 Nov 1994 08:51:37 GMT", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn clamps_retry_after_dates() {
+        let late = (Utc::now() + chrono::Duration::days(1)).to_rfc2822();
+        let response = http::Response::builder()
+            .status(429)
+            .header("retry-after", late.replace("+0000", "GMT"))
+            .body("")
+            .unwrap();
+        assert_eq!(cooldown(&Response::from(response)), MAX_COOLDOWN);
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::{Result, anyhow};
+use chrono::{NaiveDate, Utc};
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, value};
+use tracing::warn;
+
+use crate::cli::{Args, Command};
+use crate::config::Config;
+use crate::deps;
+use crate::policy::CrateSpec;
+
+/// `cargo scorecard ignore`: edit the `[[ignore]]` waivers in the configuration file.
+pub fn run(args: &Args) -> Result<ExitCode> {
+    let Some(Command::Ignore {
+        spec,
+        reason,
+        expires,
+        force,
+        list,
+        remove,
+    }) = &args.command
+    else {
+        unreachable!("only called for the ignore subcommand");
+    };
+    let path = args.config_path();
+
+    if *list {
+        print!("{}", list_waivers(&path)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    if let Some(name) = remove {
+        let removed = remove_waivers(&path, name)?;
+        println!(
+            "Removed {} waiver{} for {} from {}",
+            removed,
+            if removed == 1 { "" } else { "s" },
+            name,
+            path.display()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let spec = spec
+        .as_ref()
+        .expect("clap requires a crate without --list or --remove");
+    let reason = reason.as_deref().filter(|reason| !reason.trim().is_empty());
+    if reason.is_none() && !force {
+        return Err(anyhow!(
+            "Say why {} is acceptable with --reason, or pass --force to waive it without one",
+            spec
+        ));
+    }
+    warn_if_not_in_tree(args, spec);
+    let updated = add_waiver(&path, spec, reason, *expires)?;
+    println!(
+        "{} the waiver for {} in {}",
+        if updated { "Updated" } else { "Added" },
+        spec,
+        path.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Add a waiver for `spec`, or update the reason and expiry of the one already there;
+/// returns whether it was an update. Other content and comments are kept as they are.
+fn add_waiver(
+    path: &Path,
+    spec: &CrateSpec,
+    reason: Option<&str>,
+    expires: Option<NaiveDate>,
+) -> Result<bool> {
+    let mut document = read(path)?;
+    let ignores = ignores_mut(&mut document, path)?;
+    let key = spec.to_string();
+    let existing = ignores
+        .iter()
+        .position(|entry| entry.get("crate").and_then(Item::as_str) == Some(key.as_str()));
+    let index = match existing {
+        Some(index) => index,
+        None => {
+            let mut entry = Table::new();
+            entry["crate"] = value(key.as_str());
+            ignores.push(entry);
+            ignores.len() - 1
+        }
+    };
+    let entry = ignores
+        .get_mut(index)
+        .expect("the entry was just found or added");
+    if let Some(reason) = reason {
+        entry["reason"] = value(reason);
+    }
+    if let Some(expires) = expires {
+        entry["expires"] = value(expires.to_string());
+    }
+    write(path, &document)?;
+    Ok(existing.is_some())
+}
+
+/// Remove every waiver for `name`, whatever its version requirement; given as
+/// `name@<req>`, only that exact entry goes.
+fn remove_waivers(path: &Path, name: &str) -> Result<usize> {
+    if !path.exists() {
+        return Err(anyhow!("{} does not exist", path.display()));
+    }
+    let mut document = read(path)?;
+    let ignores = ignores_mut(&mut document, path)?;
+    let before = ignores.len();
+    ignores.retain(|entry| {
+        let spec = entry
+            .get("crate")
+            .and_then(Item::as_str)
+            .unwrap_or_default();
+        spec != name
+            && spec
+                .split_once('@')
+                .is_none_or(|(crate_name, _)| crate_name != name)
+    });
+    let removed = before - ignores.len();
+    if removed == 0 {
+        return Err(anyhow!("{} has no waiver for {}", path.display(), name));
+    }
+    write(path, &document)?;
+    Ok(removed)
+}
+
+/// One line per waiver: the crate, its expiry and its reason.
+fn list_waivers(path: &Path) -> Result<String> {
+    let config = match path.exists() {
+        true => Config::load(Some(path))?,
+        false => Config::default(),
+    };
+    if config.ignore.is_empty() {
+        return Ok(format!("No waivers in {}\n", path.display()));
+    }
+    let today = Utc::now().date_naive();
+    let mut out = String::new();
+    for ignore in &config.ignore {
+        let expiry = match ignore.expires {
+            Some(expires) if ignore.is_expired(today) => format!(" (expired {})", expires),
+            Some(expires) => format!(" (until {})", expires),
+            None => String::new(),
+        };
+        out.push_str(&format!(
+            "{}{}: {}\n",
+            ignore.spec,
+            expiry,
+            ignore.reason.as_deref().unwrap_or("no reason given")
+        ));
+    }
+    Ok(out)
+}
+
+/// Warn when a waiver names a crate the dependency tree doesn't have, likely a typo.
+fn warn_if_not_in_tree(args: &Args, spec: &CrateSpec) {
+    let crates = match args.is_multi_project() {
+        true => deps::get_project_dependencies(&args.manifest_path, &args.cargo_options()),
+        false => deps::get_dependencies(&args.cargo_options()),
+    };
+    match crates {
+        Ok(crates)
+            if !crates
+                .iter()
+                .any(|c| spec.matches_crate(&c.name, &c.version)) =>
+        {
+            warn!("{} is not in the dependency tree; check the name", spec)
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Could not check that {} is a dependency: {}", spec, e),
+    }
+}
+
+fn read(path: &Path) -> Result<DocumentMut> {
+    if !path.exists() {
+        return Ok(DocumentMut::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    content
+        .parse()
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write(path: &Path, document: &DocumentMut) -> Result<()> {
+    std::fs::write(path, document.to_string())
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The `[[ignore]]` tables, created when the file has none yet.
+fn ignores_mut<'a>(document: &'a mut DocumentMut, path: &Path) -> Result<&'a mut ArrayOfTables> {
+    document
+        .entry("ignore")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| {
+            anyhow!(
+                "`ignore` in {} is not a list of [[ignore]] tables",
+                path.display()
+            )
+        })
+}
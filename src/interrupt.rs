@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tracing::debug;
+
+/// Exit code for a run stopped by Ctrl-C, the shell's own 128 + SIGINT.
+pub const EXIT_INTERRUPTED: u8 = 130;
+
+/// How long requests already in flight get to finish after the first Ctrl-C.
+const GRACE: Duration = Duration::from_secs(3);
+
+/// Set by the first Ctrl-C.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+/// Wakes the lookups waiting in [`abandoned`] when Ctrl-C is pressed.
+static PRESSED: Notify = Notify::const_new();
+
+/// Catch Ctrl-C: the first stops new lookups so the report can be rendered from what
+/// finished, the second exits at once.
+///
+/// The listener gets a thread of its own, so a second Ctrl-C still exits while the
+/// main thread is busy outside the runtime, e.g. waiting on cargo or rendering.
+pub fn install() {
+    let spawned = std::thread::Builder::new()
+        .name("ctrl-c".to_string())
+        .spawn(|| {
+            if let Err(e) = listen() {
+                debug!(error = %e, "stopped listening for Ctrl-C");
+            }
+        });
+    if let Err(e) = spawned {
+        debug!(error = %e, "could not listen for Ctrl-C");
+    }
+}
+
+fn listen() -> std::io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        loop {
+            tokio::signal::ctrl_c().await?;
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(EXIT_INTERRUPTED.into());
+            }
+            PRESSED.notify_waiters();
+        }
+    })
+}
+
+/// Whether Ctrl-C has been pressed.
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Resolves once in-flight work should be abandoned: [`GRACE`] after the first Ctrl-C.
+pub async fn abandoned() {
+    // Created before checking the flag, so a press in between still wakes it
+    let pressed = PRESSED.notified();
+    if !is_interrupted() {
+        pressed.await;
+    }
+    tokio::time::sleep(GRACE).await;
+}
@@ -1,163 +1,1086 @@
+mod airgap;
+mod audit;
+mod cache;
+mod cargo_deny;
+mod changed;
+mod checks;
+mod cli;
+mod compare;
+mod config;
+mod coverage;
+mod deps;
+mod diff;
+mod dump;
+mod duplicates;
+mod enrich;
+mod fetch;
+mod field;
+mod github;
+mod grade;
+mod history;
+mod http;
+mod ignore;
+mod interrupt;
+mod mismatch;
+mod notify;
+mod osv;
+mod outage;
+mod policy;
+mod progress;
+mod project;
+mod provenance;
+mod provider;
+mod render;
+mod repo;
+mod repo_list;
+mod repo_map;
+mod resolved;
+mod retry;
+mod score;
+mod scorecard;
+mod stats;
+mod tui;
+mod vendor;
+mod watch;
+
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use airgap::Requests;
 use anyhow::{Result, anyhow};
+use cache::Cache;
+use cli::{Args, Command, OutputFormat, PartialPolicy, SchemaKind, SortOrder};
+use config::Config;
+use deps::{CargoOptions, CrateInfo, Resolution};
+use dump::Dump;
+use fetch::Context;
+use github::GitHub;
+use grade::Grade;
+use http::Http;
+use outage::OutageDetector;
+use policy::Policy;
+use progress::Progress;
+use render::summary::Summary;
 use reqwest::Client;
+use score::{CrateScore, ScoreStatus, SelfScore};
+use stats::{Phases, RunStats};
+use tokio::runtime::{self, Runtime};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+use tracing_subscriber::EnvFilter;
+
+/// Exit code when the policy found violations.
+const EXIT_POLICY: u8 = 2;
 
-struct CrateInfo {
-    name: String,
-    version: String,
+/// Exit code when `--max-time` expired before every crate was fetched.
+const EXIT_PARTIAL: u8 = 3;
+
+/// Log to stderr, honoring `RUST_LOG` and falling back to `--verbose`.
+fn init_logging(verbose: bool) {
+    let default = if verbose {
+        "warn,cargo_scorecard=debug"
+    } else {
+        "warn"
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    if verbose {
+        subscriber.init();
+    } else {
+        subscriber.without_time().with_target(false).init();
+    }
 }
 
-struct CrateScore {
-    name: String,
-    version: String,
-    repository: Option<String>,
-    security_score: Option<f64>,
+/// Resolve the crates to score, narrowed by --only, --direct-only and --only-build-time.
+fn resolve_crates(args: &Args, ctx: &Context, runtime: &Runtime) -> Result<Vec<CrateInfo>> {
+    let repo_map = match &args.repo_map {
+        Some(path) => repo_map::load(path)?,
+        None => HashMap::new(),
+    };
+    // A repository list stands alone when there is no Cargo.toml to go with it
+    let lists_only = args.repos_file.is_some()
+        && args.manifest_path.is_empty()
+        && args.vendor_dir.is_none()
+        && !args.manifest().exists();
+    // cargo already refuses a stale lockfile under --locked; vendored crates never see cargo
+    let checks_lockfile = !args.no_lock_check
+        && !lists_only
+        && !matches!(args.command, Some(Command::Audit { .. }))
+        && (args.vendor_dir.is_some() || !(args.locked || args.frozen));
+    if checks_lockfile {
+        check_lockfiles(args);
+    }
+    let mut crates = match (&args.command, &args.vendor_dir) {
+        _ if lists_only => Vec::new(),
+        (Some(Command::Audit { spec }), _) => runtime.block_on(audit::resolve(ctx, spec))?,
+        (_, Some(vendor_dir)) => {
+            vendor::get_dependencies(vendor_dir, args.manifest(), &args.lockfile_path())?
+        }
+        (_, None) if args.is_multi_project() => {
+            let manifests: Vec<&Path> = args.manifest_path.iter().map(PathBuf::as_path).collect();
+            resolved::dependencies(&ctx.cache, &manifests, &args.cargo_options(), || {
+                deps::get_project_dependencies(&args.manifest_path, &args.cargo_options())
+            })?
+        }
+        (_, None) => resolved::dependencies(
+            &ctx.cache,
+            &[args.manifest()],
+            &args.cargo_options(),
+            || deps::get_dependencies(&args.cargo_options()),
+        )?,
+    };
+    deps::apply_pins(&mut crates, &args.pin);
+    repo_map::apply(&mut crates, &repo_map);
+    if !args.only.is_empty() {
+        let missing: Vec<String> = args
+            .only
+            .iter()
+            .filter(|spec| {
+                !crates
+                    .iter()
+                    .any(|c| spec.matches_crate(&c.name, &c.version))
+            })
+            .map(ToString::to_string)
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "--only names crates that are not in the dependency tree: {}",
+                missing.join(", ")
+            ));
+        }
+        crates.retain(|c| {
+            args.only
+                .iter()
+                .any(|spec| spec.matches_crate(&c.name, &c.version))
+        });
+    }
+    if let Some(git_ref) = &args.changed_since {
+        if args.is_multi_project() || matches!(args.command, Some(Command::Audit { .. })) {
+            return Err(anyhow!(
+                "--changed-since diffs the Cargo.lock of a single project"
+            ));
+        }
+        changed::retain_changed(&mut crates, &args.lockfile_path(), git_ref)?;
+    }
+    if let Some(path) = &args.repos_file {
+        crates.extend(repo_list::read(path)?);
+    }
+    if args.direct_only {
+        crates.retain(|c| c.direct);
+    }
+    if args.only_build_time {
+        crates.retain(|c| c.build_time.any());
+    }
+    if !args.only.is_empty() {
+        let found = args
+            .only
+            .iter()
+            .filter(|spec| {
+                crates
+                    .iter()
+                    .any(|c| spec.matches_crate(&c.name, &c.version))
+            })
+            .count();
+        if found < args.only.len() {
+            warn!(
+                "{} of the {} crates given to --only are left after --direct-only and \
+                 --only-build-time",
+                found,
+                args.only.len()
+            );
+        }
+    }
+    Ok(crates)
 }
 
-fn get_dependencies() -> Result<Vec<CrateInfo>> {
-    let output = std::process::Command::new("sh")
-        .args(["-c", "cargo tree --prefix none | sort -u"])
-        .output()
-        .map_err(|e| anyhow!("Failed to run cargo tree: {}", e))?;
+/// Warn about each manifest whose Cargo.lock is out of date, before cargo rewrites it.
+fn check_lockfiles(args: &Args) {
+    let manifests: Vec<&Path> = match args.manifest_path.as_slice() {
+        [] => vec![args.manifest()],
+        manifests => manifests.iter().map(PathBuf::as_path).collect(),
+    };
+    for manifest in manifests {
+        let options = CargoOptions {
+            manifest_path: Some(manifest),
+            ..args.cargo_options()
+        };
+        match deps::lockfile_is_stale(&options) {
+            Ok(true) => warn!(
+                "Cargo.lock is out of date with {}, so the versions scored may not be the \
+                 ones that build from the committed lockfile; run `cargo update --workspace` \
+                 (or `cargo generate-lockfile`) and commit it, or pass --no-lock-check",
+                manifest.display()
+            ),
+            Ok(false) => {}
+            Err(e) => debug!("Could not check whether Cargo.lock is up to date: {}", e),
+        }
+    }
+}
 
-    if !output.status.success() {
-        return Err(anyhow!("cargo tree with sort failed"));
+/// Resolve the dependency tree and fetch every crate's score.
+fn collect_scores(
+    args: &Args,
+    ctx: &Context,
+    runtime: &Runtime,
+    progress: bool,
+    phases: &mut Phases,
+) -> Result<Vec<CrateScore>> {
+    // Step 1: Get basic dependencies (fast, local operation)
+    if progress {
+        println!("Parsing dependencies...");
+    }
+    let crates = resolve_crates(args, ctx, runtime)?;
+    phases.lap("resolve");
+    if crates.len() > args.max_crates {
+        return Err(anyhow!(
+            "Found {} dependencies, more than --max-crates {}; narrow the scope with \
+             --manifest-path or raise the limit",
+            crates.len(),
+            args.max_crates
+        ));
     }
 
-    let dependencies = String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .map(|line| line.split_whitespace().collect::<Vec<&str>>())
-        .filter(|parts| parts.len() == 2)
-        .map(|parts| CrateInfo {
-            name: parts[0].to_string(),
-            version: parts[1].to_string(),
-        })
-        .collect();
+    if progress && !args.only.is_empty() {
+        println!(
+            "Found {} versions of the {} crates given to --only",
+            crates.len(),
+            args.only.len()
+        );
+    } else if progress && let Some(git_ref) = &args.changed_since {
+        println!(
+            "Found {} dependencies changed since {}",
+            crates.len(),
+            git_ref
+        );
+        println!("Fetching repository URLs and security scores...");
+    } else if progress {
+        println!("Found {} dependencies", crates.len());
+        println!("Fetching repository URLs and security scores...");
+    }
 
-    Ok(dependencies)
+    // Step 2: Fetch all crate scores concurrently using minimal Tokio runtime,
+    // abandoning whatever is still outstanding when the deadline passes
+    let deadline = args
+        .max_time
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+    let counter = Progress::new(crates.len(), !args.no_progress);
+    let mut scores = runtime.block_on(fetch::fetch_all(ctx, &crates, deadline, &counter));
+    phases.lap("fetch");
+    if let Some(warning) = ctx.scorecard_outage.warning() {
+        warn!("{}", warning);
+    }
+    // After Ctrl-C, report what was fetched rather than start more lookups
+    if !interrupt::is_interrupted() {
+        enrich_scores(args, ctx, runtime, &mut scores, phases);
+    }
+    Ok(scores)
 }
 
-async fn fetch_crate_repo_url(client: &Client, crate_name: &str) -> Result<Option<String>> {
-    let url = format!("https://crates.io/api/v1/crates/{}", crate_name);
+/// Add whatever the flags ask for beyond the scores themselves.
+fn enrich_scores(
+    args: &Args,
+    ctx: &Context,
+    runtime: &Runtime,
+    scores: &mut [CrateScore],
+    phases: &mut Phases,
+) {
+    // Step 3: Optionally add forge metadata such as archived status and stars
+    if args.github_enrich {
+        runtime.block_on(enrich::enrich(ctx, scores));
+        phases.lap("enrich");
+    }
 
-    let response = client
-        .get(&url)
-        .header("User-Agent", "cargo-scorecard/0.1.0")
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to fetch crate repo url for {}: {}", crate_name, e))?;
+    // Step 4: Optionally check that repositories hold the crates claiming them
+    if args.verifies_provenance() {
+        runtime.block_on(provenance::verify(ctx, scores));
+        phases.lap("provenance");
+    }
 
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "API request failed for {}: {}",
-            crate_name,
-            response.status()
-        ));
+    // Step 5: Optionally look up published advisories
+    if args.checks_advisories() {
+        runtime.block_on(osv::attach_advisories(ctx, scores));
+        phases.lap("advisories");
+    }
+}
+
+/// Reorder the rows for `--sort`; crates without a value keep their order at the end.
+fn sort_scores(scores: &mut [CrateScore], order: SortOrder) {
+    match order {
+        SortOrder::Score => scores.sort_by(|a, b| match (&a.status, &b.status) {
+            (ScoreStatus::Scored(a), ScoreStatus::Scored(b)) => a.total_cmp(b),
+            (ScoreStatus::Scored(_), _) => std::cmp::Ordering::Less,
+            (_, ScoreStatus::Scored(_)) => std::cmp::Ordering::Greater,
+            _ => std::cmp::Ordering::Equal,
+        }),
+        SortOrder::Risk => scores.sort_by(|a, b| match (a.risk, b.risk) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }),
+    }
+}
+
+/// Cargo options for tracing a crate through the first project that uses it.
+fn project_options<'a>(args: &'a Args, crate_score: &CrateScore) -> CargoOptions<'a> {
+    let manifest = args
+        .manifest_path
+        .iter()
+        .find(|manifest| crate_score.projects.first() == Some(&deps::project_label(manifest)));
+    CargoOptions {
+        manifest_path: manifest.map(PathBuf::as_path),
+        ..args.cargo_options()
     }
+}
 
-    let json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| anyhow!("Failed to parse JSON for {}: {}", crate_name, e))?;
+/// How `crate_score` entered the tree, root first; empty when that can't be traced.
+fn dependency_path(
+    args: &Args,
+    scores: &[CrateScore],
+    crate_score: &CrateScore,
+) -> Result<Vec<String>> {
+    match args.command {
+        Some(Command::Audit { .. }) => Ok(audit::introduction_path(scores, crate_score)),
+        // The tree the report came from may not be at hand
+        Some(Command::Retry { .. }) => Ok(Vec::new()),
+        _ => deps::dependency_path(
+            &project_options(args, crate_score),
+            &crate_score.name,
+            &crate_score.version,
+        ),
+    }
+}
 
-    let repository = json["crate"]["repository"].as_str().map(|s| s.to_string());
+/// Record which direct dependencies pull in each version of a crate resolved more than once.
+///
+/// Vendored trees are never traced, since cargo isn't run for them.
+fn trace_duplicates(args: &Args, scores: &mut [CrateScore]) -> Result<()> {
+    let names: HashSet<String> = duplicates::find(scores)
+        .iter()
+        .map(|duplicate| duplicate.name.to_string())
+        .collect();
+    for index in 0..scores.len() {
+        let crate_score = &scores[index];
+        if !names.contains(&crate_score.name) {
+            continue;
+        }
+        let required_by = match args.command {
+            // The second crate on the path from the audited crate is its direct dependency
+            Some(Command::Audit { .. }) => audit::introduction_path(scores, crate_score)
+                .into_iter()
+                .nth(1)
+                .into_iter()
+                .collect(),
+            _ if args.vendor_dir.is_some() => Vec::new(),
+            _ => {
+                let dependents = deps::dependents(
+                    &project_options(args, crate_score),
+                    &crate_score.name,
+                    &crate_score.version,
+                )?;
+                scores
+                    .iter()
+                    .filter(|c| {
+                        c.direct && dependents.contains(&(c.name.clone(), c.version.clone()))
+                    })
+                    .map(|c| format!("{} {}", c.name, c.version))
+                    .collect()
+            }
+        };
+        scores[index].required_by = required_by;
+    }
+    Ok(())
+}
+
+/// Write the URLs an `--offline` run still needs, for fetching outside the air gap.
+fn export_requests(args: &Args, ctx: &Context, runtime: &Runtime, path: &Path) -> Result<ExitCode> {
+    let crates = resolve_crates(args, ctx, runtime)?;
+    let repository = if args.no_self || args.is_multi_project() {
+        None
+    } else {
+        project::repository(args.manifest())
+    };
+    let requests = Requests::missing(&ctx.cache, &crates, repository);
+    requests.save(path)?;
 
-    Ok(repository)
+    println!(
+        "wrote {} requests for {} crates to {}",
+        requests.requests.len(),
+        crates.len(),
+        path.display()
+    );
+    if requests.crates_io() > 0 {
+        println!(
+            "{} crates.io responses may point at more repositories; run export-requests \
+             again after importing them",
+            requests.crates_io()
+        );
+    }
+    Ok(ExitCode::SUCCESS)
 }
 
-async fn fetch_security_score(client: &reqwest::Client, repo_url: &str) -> Result<Option<f64>> {
-    let url = format!(
-        "https://api.securityscorecards.dev/projects/{}",
-        repo_url
-            .trim_start_matches("http://")
-            .trim_start_matches("https://")
+/// Load responses fetched for `export-requests` into the cache.
+fn import_responses(
+    ctx: &Context,
+    dir: &Path,
+    requests: Option<&Path>,
+    checksums: Option<&Path>,
+) -> Result<ExitCode> {
+    if !ctx.cache.is_persistent() {
+        return Err(anyhow!("import-responses needs a cache directory"));
+    }
+    let requests_path = requests.map_or_else(|| dir.join("requests.json"), Path::to_path_buf);
+    let requests = Requests::load(&requests_path)?;
+    let checksums = checksums.map_or_else(|| dir.join(airgap::CHECKSUMS), Path::to_path_buf);
+    let imported = airgap::import(&ctx.cache, dir, &checksums, &requests)?;
+
+    for url in &imported.missing {
+        warn!("no response for {}", url);
+    }
+    for (url, reason) in &imported.rejected {
+        warn!("rejected the response for {}: {}", url, reason);
+    }
+    println!(
+        "imported {} responses, {} missing, {} rejected",
+        imported.imported,
+        imported.missing.len(),
+        imported.rejected.len()
     );
 
-    let response = client
-        .get(&url)
-        .header("accept", "application/json")
-        .header("User-Agent", "cargo-scorecard/0.1.0")
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to fetch security score for {}: {}", repo_url, e))?;
+    if !imported.missing.is_empty() || !imported.rejected.is_empty() {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Settings in Cargo.toml's `[workspace.metadata.scorecard]` and
+/// `[package.metadata.scorecard]`, below .cargo-scorecard.toml in precedence.
+fn manifest_layers(args: &Args) -> Result<Vec<(String, toml::Table)>> {
+    if !args.manifest().exists() {
+        return Ok(Vec::new());
+    }
+    match deps::scorecard_metadata(&args.cargo_options()) {
+        Ok((workspace, package)) => Config::manifest_layers(workspace, package),
+        Err(e) => {
+            debug!("Could not read scorecard settings from Cargo.toml: {}", e);
+            Ok(Vec::new())
+        }
+    }
+}
 
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "Security scorecard API request failed for {}: {}",
-            repo_url,
-            response.status()
+/// `--show-config`: one line per setting in effect, naming where it was set.
+fn show_config(args: &Args, config: &Config) -> String {
+    if config.settings.is_empty() {
+        return format!(
+            "No settings in {} or Cargo.toml metadata\n",
+            args.config_path().display()
+        );
+    }
+    let overrides = [
+        (
+            "fail_on_no_repository",
+            args.fail_on_no_repository.is_some(),
+            "--fail-on-no-repository",
+        ),
+        (
+            "fail_on_dead_repo",
+            args.fail_on_dead_repo,
+            "--fail-on-dead-repo",
+        ),
+        ("fail_under", args.fail_under.is_some(), "--fail-under"),
+        (
+            "fail_under_build_time",
+            args.fail_under_build_time.is_some(),
+            "--fail-under-build-time",
+        ),
+        (
+            "min_version_age",
+            args.min_version_age.is_some(),
+            "--min-version-age",
+        ),
+        (
+            "min_direct_version_age",
+            args.min_direct_version_age.is_some(),
+            "--min-direct-version-age",
+        ),
+        ("max_unknown", args.max_unknown.is_some(), "--max-unknown"),
+        (
+            "max_unscored",
+            args.max_unscored.is_some(),
+            "--max-unscored",
+        ),
+        (
+            "unsupported_forges",
+            args.unsupported_forges.is_some(),
+            "--unsupported-forges",
+        ),
+        (
+            "fail_on_duplicates",
+            !args.fail_on_duplicates.is_empty(),
+            "--fail-on-duplicates",
+        ),
+    ];
+    let mut out = String::new();
+    for setting in &config.settings {
+        let flag = overrides
+            .iter()
+            .find(|(key, set, _)| *key == setting.key && *set)
+            .map(|(_, _, flag)| flag);
+        let source = match flag {
+            Some(flag) => format!("{}, overridden by {}", setting.source, flag),
+            None => setting.source.clone(),
+        };
+        out.push_str(&format!(
+            "{} = {}  # {}\n",
+            setting.key, setting.value, source
         ));
     }
+    out
+}
 
-    let json: serde_json::Value = response.json().await.map_err(|e| {
-        anyhow!(
-            "Failed to parse security score JSON for {}: {}",
-            repo_url,
-            e
+/// Whether to print progress lines to stdout, which would corrupt machine-readable output.
+fn shows_progress(args: &Args) -> bool {
+    !args.no_progress
+        && args.template.is_none()
+        && matches!(
+            args.stdout_format(),
+            None | Some(OutputFormat::Table | OutputFormat::Markdown)
         )
-    })?;
+}
+
+/// Score the dependencies and print the report.
+fn run(args: &Args, config: &Config, ctx: &Context, runtime: &Runtime) -> Result<ExitCode> {
+    let mut phases = Phases::start();
+    let scores = collect_scores(args, ctx, runtime, shows_progress(args), &mut phases)?;
+    // An audited crate is not a project of ours
+    let project = match args.command {
+        Some(Command::Audit { .. }) => None,
+        _ if args.no_self || args.is_multi_project() => None,
+        _ => {
+            let repository = project::repository(args.manifest());
+            Some(runtime.block_on(fetch::fetch_self_score(ctx, repository)))
+        }
+    };
+    let collected = Collected {
+        scores,
+        project,
+        resolution: resolution(args),
+    };
+    report(args, config, ctx, runtime, collected, phases)
+}
+
+/// The build configuration the crates were resolved for, recorded in the JSON report
+/// and the summary.
+fn resolution(args: &Args) -> Resolution {
+    let mut resolution = Resolution {
+        source: args.resolution().to_string(),
+        ..Resolution::default()
+    };
+    // Audits resolve from crates.io, and vendored trees never see cargo
+    if args.vendor_dir.is_some()
+        || matches!(args.command, Some(Command::Audit { .. }))
+        || !args.manifest().exists()
+    {
+        return resolution;
+    }
+    let options: Vec<CargoOptions> = match args.is_multi_project() {
+        true => args
+            .manifest_path
+            .iter()
+            .map(|manifest| CargoOptions {
+                manifest_path: Some(manifest),
+                ..args.cargo_options()
+            })
+            .collect(),
+        false => vec![args.cargo_options()],
+    };
+    for options in &options {
+        if let Err(e) = resolution.add_features(options) {
+            debug!("Could not record the resolved features: {}", e);
+        }
+    }
+    resolution
+}
+
+/// `cargo scorecard retry`: fetch the rows of a JSON report that failed with an error
+/// again, then evaluate and render the merged rows like a fresh run.
+fn retry(
+    args: &Args,
+    config: &Config,
+    ctx: &Context,
+    runtime: &Runtime,
+    path: &Path,
+) -> Result<ExitCode> {
+    let mut phases = Phases::start();
+    let mut previous = retry::Report::load(path)?;
+    let failed = previous.failed();
+    let mut crates: Vec<CrateInfo> = failed
+        .iter()
+        .map(|&index| retry::crate_info(&previous.crates[index]))
+        .collect();
+    if let Some(path) = &args.repo_map {
+        repo_map::apply(&mut crates, &repo_map::load(path)?);
+    }
+    phases.lap("resolve");
+    if shows_progress(args) {
+        println!(
+            "Retrying {} of {} crates from {}",
+            crates.len(),
+            previous.crates.len(),
+            path.display()
+        );
+    }
+
+    let deadline = args
+        .max_time
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+    let counter = Progress::new(crates.len(), !args.no_progress);
+    let mut retried = runtime.block_on(fetch::fetch_all(ctx, &crates, deadline, &counter));
+    phases.lap("fetch");
+    if let Some(warning) = ctx.scorecard_outage.warning() {
+        warn!("{}", warning);
+    }
+    if !interrupt::is_interrupted() {
+        enrich_scores(args, ctx, runtime, &mut retried, &mut phases);
+    }
+    for (index, row) in failed.into_iter().zip(retried) {
+        retry::merge(&mut previous.crates[index], row);
+    }
 
-    Ok(json["score"].as_f64())
+    let resolution = previous.resolution().clone();
+    let mut project = previous.self_score;
+    if let Some(self_score) = &project
+        && matches!(self_score.status, ScoreStatus::Error(_))
+    {
+        let repository = self_score.repository.clone();
+        project = Some(runtime.block_on(fetch::fetch_self_score(ctx, repository)));
+    }
+    let collected = Collected {
+        scores: previous.crates,
+        project,
+        resolution,
+    };
+    report(args, config, ctx, runtime, collected, phases)
+}
+
+/// What a report is made from: fetched by this run, or read back by `retry`.
+struct Collected {
+    scores: Vec<CrateScore>,
+    project: Option<SelfScore>,
+    resolution: Resolution,
 }
 
-async fn fetch_crate_score(client: &Client, crate_info: &CrateInfo) -> Result<CrateScore> {
-    // First, get the repository URL
-    let repository = fetch_crate_repo_url(client, &crate_info.name).await?;
+/// Evaluate the rows against policy, render every requested output and pick the exit code.
+fn report(
+    args: &Args,
+    config: &Config,
+    ctx: &Context,
+    runtime: &Runtime,
+    collected: Collected,
+    mut phases: Phases,
+) -> Result<ExitCode> {
+    let Collected {
+        mut scores,
+        project,
+        resolution,
+    } = collected;
+    let project = project.as_ref();
+    let outputs = args.outputs();
+
+    if !args.check_weights.is_empty() {
+        for crate_score in &mut scores {
+            crate_score.custom_score = crate_score
+                .scorecard
+                .as_ref()
+                .and_then(|result| scorecard::weighted_score(result, &args.check_weights));
+        }
+    }
+    if let Some(order) = args.sort {
+        sort_scores(&mut scores, order);
+    }
+
+    if !args.no_trust {
+        for crate_score in &mut scores {
+            // A repository that doesn't hold the crate can't vouch for it
+            let verified = crate_score.repo_verified != Some(false);
+            crate_score.trusted = verified
+                && crate_score.repo.as_ref().is_some_and(|repo| {
+                    config
+                        .trusted_orgs
+                        .iter()
+                        .any(|prefix| repo.is_within(prefix))
+                });
+        }
+    }
+
+    if let Some(path) = &args.deny_toml {
+        let path = path.clone().unwrap_or_else(|| {
+            let dir = args.manifest().parent().unwrap_or(Path::new(""));
+            dir.join(cargo_deny::DENY_TOML)
+        });
+        match cargo_deny::waivers(&path) {
+            Ok(waivers) => {
+                for crate_score in &mut scores {
+                    if waivers.crates.iter().any(|spec| spec.matches(crate_score)) {
+                        crate_score.waiver = Some(cargo_deny::DENY_TOML.to_string());
+                    }
+                    for advisory in crate_score.advisories.iter_mut().flatten() {
+                        if waivers.advisories.iter().any(|id| advisory.is_known_as(id)) {
+                            advisory.waiver = Some(cargo_deny::DENY_TOML.to_string());
+                        }
+                    }
+                }
+            }
+            Err(e) => warn!("{}; continuing without deny.toml waivers", e),
+        }
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    for ignore in &config.ignore {
+        if ignore.is_expired(today) {
+            warn!(
+                "The waiver for {} expired on {}; renew or remove it with `cargo scorecard ignore`",
+                ignore.spec,
+                ignore.expires.expect("only waivers with a date expire")
+            );
+            continue;
+        }
+        let source = config.source("ignore").unwrap_or(config::CONFIG_FILE);
+        for crate_score in scores.iter_mut().filter(|c| ignore.spec.matches(c)) {
+            crate_score.waiver.get_or_insert_with(|| source.to_string());
+        }
+    }
+
+    mismatch::flag(&mut scores, &config.allow_repo_mismatch);
+
+    if let Some(path) = &args.baseline {
+        diff::apply_baseline(path, &mut scores, &resolution)?;
+    }
+    // A retried report keeps the dependents it was written with
+    if !matches!(args.command, Some(Command::Retry { .. })) {
+        trace_duplicates(args, &mut scores)?;
+    }
 
-    // If we have a repository URL, fetch the security score
-    let security_score = if let Some(ref repo_url) = repository {
-        fetch_security_score(client, repo_url).await.unwrap_or(None)
+    let policy = Policy::new(args, config);
+    let mut violations = policy.evaluate(&scores);
+    for denied in &mut violations.denied {
+        denied.path = dependency_path(args, &scores, denied.crate_score)?;
+    }
+
+    let grade = if args.grade {
+        Grade::for_scores(&scores, &config.grade)
     } else {
         None
     };
 
-    Ok(CrateScore {
-        name: crate_info.name.clone(),
-        version: crate_info.version.clone(),
-        repository,
-        security_score,
-    })
-}
+    phases.lap("evaluate");
+    let stats = args
+        .stats
+        .then(|| RunStats::collect(phases, &ctx.http, &ctx.cache));
 
-fn main() -> Result<()> {
-    // Step 1: Get basic dependencies (fast, local operation)
-    println!("Parsing dependencies...");
-    let crates = get_dependencies()?;
+    let count = |f: fn(&ScoreStatus) -> bool| scores.iter().filter(|c| f(&c.status)).count();
+    let skipped = count(|s| matches!(s, ScoreStatus::SkippedDeadline));
+    let partial_fails = skipped > 0 && args.on_partial == PartialPolicy::Fail;
+    let quiet = args.summary_only_on_failure
+        && violations.is_empty()
+        && !partial_fails
+        && !interrupt::is_interrupted();
 
-    println!("Found {} dependencies", crates.len());
+    // Step 6: Display results in the requested formats, all from the same results
+    let results = render::Results {
+        scores: &scores,
+        project,
+        violations: &violations,
+        grade: grade.as_ref(),
+        resolution: &resolution,
+        stats: stats.as_ref(),
+    };
+    if let Some(template) = &args.template {
+        if !quiet {
+            print!("{}", render::template::render(&scores, template, args)?);
+        }
+    } else {
+        for output in &outputs {
+            match &output.path {
+                Some(path) => std::fs::write(path, render::render(output.format, &results, args))
+                    .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?,
+                None if quiet || args.tui => {}
+                None => print!("{}", render::render(output.format, &results, args)),
+            }
+        }
+    }
+    if quiet {
+        println!("{}", success_line(&scores, args));
+    }
+    if args.tui {
+        tui::run(&scores, &violations, args, |crate_score| {
+            // `--repos-file` rows and vendored crates have no place in cargo's tree
+            if crate_score.listed || args.vendor_dir.is_some() {
+                return Ok(Vec::new());
+            }
+            dependency_path(args, &scores, crate_score)
+        })?;
+    }
 
-    // Step 2: Create HTTP client for API requests
-    let client = reqwest::Client::new();
+    if let Some(path) = &args.summary_file {
+        let summary = Summary::new(&scores, &violations, &config.grade, &resolution);
+        std::fs::write(path, summary.to_json() + "\n")
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+    }
 
-    println!("Fetching repository URLs and security scores...");
+    // A failed notification is worth a warning, never a failed run
+    if let Some(webhook) = &args.notify_webhook {
+        let message = notify::message(&scores, &violations, &config.grade, args);
+        if let Err(e) = runtime.block_on(notify::send(&ctx.http, webhook, message)) {
+            warn!("{}", e);
+        }
+    }
 
-    // Step 3: Fetch all crate scores concurrently using minimal Tokio runtime
-    let results = tokio::runtime::Runtime::new()?.block_on(futures::future::join_all(
-        crates
-            .iter()
-            .map(|crate_info| fetch_crate_score(&client, crate_info)),
-    ));
-
-    // Step 5: Display results in markdown table format
-    println!("\n## Cargo Scorecard Results\n");
-    println!("| Crate Name | Version | Repository URL | Security Score |");
-    println!("| --- | --- | --- | --- |");
-
-    for crate_score in results.into_iter().filter_map(Result::ok) {
-        let repo_url = match &crate_score.repository {
-            Some(repo) => repo.clone(),
-            None => "No repository information".to_string(),
-        };
-        let score = match crate_score.security_score {
-            Some(score) => format!("{:.1}", score),
-            None => "Not available".to_string(),
-        };
-        println!(
-            "| {} | {} | {} | {} |",
-            crate_score.name, crate_score.version, repo_url, score
+    let uncached = count(|s| matches!(s, ScoreStatus::Uncached));
+    if uncached > 0 {
+        warn!(
+            "{} of {} crates were not in the cache; run once online to fill it",
+            uncached,
+            scores.len()
         );
     }
 
-    Ok(())
+    if let Some(stats) = &stats {
+        stats.print();
+    }
+
+    // A run cut short would record a misleading score
+    if let Some(path) = &args.history_db
+        && !interrupt::is_interrupted()
+    {
+        let mut runs = history::load(path)?;
+        let run = history::Run::new(&scores, &violations);
+        history::record(path, &run)?;
+        runs.push(run);
+        if args.trend {
+            eprintln!(
+                "{}",
+                history::trend(&runs, args, render::table::supports_unicode())
+            );
+        }
+    }
+
+    if skipped > 0 {
+        warn!(
+            "--max-time expired; report is partial ({} of {} crates skipped)",
+            skipped,
+            scores.len()
+        );
+    }
+
+    let interrupted = count(|s| matches!(s, ScoreStatus::Interrupted));
+    if interrupt::is_interrupted() {
+        warn!(
+            "Interrupted; report is partial ({} of {} crates skipped)",
+            interrupted,
+            scores.len()
+        );
+    }
+
+    // The count is the whole contract of `--format count`, partial or not
+    if outputs.iter().any(|o| o.format == OutputFormat::Count) {
+        let failing = violations.failing_crates().min(u8::MAX as usize);
+        return Ok(ExitCode::from(failing as u8));
+    }
+
+    if interrupt::is_interrupted() {
+        return Ok(ExitCode::from(interrupt::EXIT_INTERRUPTED));
+    }
+
+    if partial_fails {
+        return Ok(ExitCode::from(EXIT_PARTIAL));
+    }
+
+    if !violations.is_empty() {
+        return Ok(ExitCode::from(EXIT_POLICY));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The `--summary-only-on-failure` line for a run that passed every gate, e.g.
+/// `all 245 dependencies pass (average score 6.2)`.
+fn success_line(scores: &[CrateScore], args: &Args) -> String {
+    match grade::aggregate(scores) {
+        Some(average) => format!(
+            "all {} dependencies pass (average score {})",
+            scores.len(),
+            args.format_score(average)
+        ),
+        None => format!("all {} dependencies pass", scores.len()),
+    }
+}
+
+/// Fill the cache for a later `--offline` run; only lookup failures affect the exit code.
+fn warm(args: &Args, ctx: &Context, runtime: &Runtime) -> Result<ExitCode> {
+    let mut phases = Phases::start();
+    let scores = collect_scores(args, ctx, runtime, false, &mut phases)?;
+
+    let mut failures = 0;
+    for crate_score in &scores {
+        match &crate_score.status {
+            ScoreStatus::Error(e) => warn!("{} {}: {}", crate_score.name, crate_score.version, e),
+            ScoreStatus::SkippedDeadline => {
+                warn!(
+                    "{} {}: skipped (deadline)",
+                    crate_score.name, crate_score.version
+                )
+            }
+            ScoreStatus::Uncached => {
+                warn!(
+                    "{} {}: not in the cache",
+                    crate_score.name, crate_score.version
+                )
+            }
+            ScoreStatus::Interrupted => {}
+            _ => continue,
+        }
+        failures += 1;
+    }
+    let repos: HashSet<_> = scores
+        .iter()
+        .filter(|c| matches!(c.status, ScoreStatus::Scored(_) | ScoreStatus::NotScanned))
+        .filter_map(|c| c.repo.as_ref())
+        .collect();
+    println!(
+        "cached {} crates, {} repos, {} failures",
+        scores.len() - failures,
+        repos.len(),
+        failures
+    );
+
+    if args.stats {
+        RunStats::collect(phases, &ctx.http, &ctx.cache).print();
+    }
+
+    if interrupt::is_interrupted() {
+        return Ok(ExitCode::from(interrupt::EXIT_INTERRUPTED));
+    }
+    if failures > 0 {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> Result<ExitCode> {
+    let mut args = Args::parse_from_env();
+    init_logging(args.verbose);
+    args.expand_manifests()?;
+    if let Some(template) = &args.template {
+        render::template::validate(template)?;
+    }
+    render::validate_columns(&args)?;
+    for weight in &args.check_weights {
+        if checks::hint(&weight.name).is_none() {
+            warn!(
+                "--check-weights names {}, which is not a known scorecard check",
+                weight.name
+            );
+        }
+    }
+    match args.command {
+        Some(Command::Schema {
+            kind: SchemaKind::Summary,
+        }) => {
+            println!("{}", render::summary::schema());
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Schema { .. }) => {
+            println!("{}", render::json::schema());
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Ignore { .. }) => return ignore::run(&args),
+        Some(Command::Completions { shell }) => {
+            cli::print_completions(shell);
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ if args.schema => {
+            println!("{}", render::json::schema());
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ => {}
+    }
+    let config = Config::load_layered(args.config.as_deref(), manifest_layers(&args)?)?;
+    if args.show_config {
+        print!("{}", show_config(&args, &config));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let ctx = Context {
+        http: match &args.debug_dump {
+            Some(dir) => Http::new(Client::new(), config.limits.clone())
+                .with_dump(Dump::new(dir.clone(), args.debug_dump_failures_only)),
+            None => Http::new(Client::new(), config.limits.clone()),
+        },
+        cache: Cache::new(
+            args.cache_dir(),
+            Duration::from_secs(args.cache_ttl * 3600),
+            Duration::from_secs(args.negative_cache_ttl * 3600),
+            args.offline,
+        ),
+        github: GitHub::new(args.github_token.clone())?,
+        verify_repos: !args.no_verify_repos
+            || args.fail_on_dead_repo
+            || config.fail_on_dead_repo
+            || args.strict,
+        raw_scorecard: args.raw_scorecard.clone(),
+        scorecard_outage: OutageDetector::new(args.api_down_after),
+        serial: args.serial,
+        provider: args.provider.build(args.provider_file.as_deref())?,
+    };
+    let runtime = match args.serial {
+        true => runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?,
+        false => Runtime::new()?,
+    };
+    // Under --watch, Ctrl-C keeps its usual job of ending the session
+    if !args.watch {
+        interrupt::install();
+    }
+
+    match args.command {
+        // Checked up front rather than after a scan nobody can see
+        _ if args.tui && !std::io::stdout().is_terminal() => {
+            Err(anyhow!("--tui needs a terminal on stdout"))
+        }
+        _ if args.watch && args.command.is_some() => Err(anyhow!(
+            "--watch only applies to scoring the workspace's own dependencies"
+        )),
+        None if args.watch => {
+            let manifests: Vec<&Path> = match args.manifest_path.as_slice() {
+                [] => vec![args.manifest()],
+                manifests => manifests.iter().map(PathBuf::as_path).collect(),
+            };
+            watch::watch(&manifests, !args.no_clear, || {
+                run(&args, &config, &ctx, &runtime)
+            })
+        }
+        Some(Command::Warm) => warm(&args, &ctx, &runtime),
+        Some(Command::ExportRequests { ref path }) => export_requests(&args, &ctx, &runtime, path),
+        Some(Command::ImportResponses {
+            ref dir,
+            ref requests,
+            ref checksums,
+        }) => import_responses(&ctx, dir, requests.as_deref(), checksums.as_deref()),
+        Some(Command::Schema { .. } | Command::Ignore { .. } | Command::Completions { .. }) => {
+            unreachable!("handled before loading the config")
+        }
+        Some(Command::Compare {
+            ref first,
+            ref second,
+        }) => compare::run(&args, &ctx, &runtime, first, second),
+        Some(Command::Retry { ref report }) => retry(&args, &config, &ctx, &runtime, report),
+        Some(Command::Audit { .. }) | None => run(&args, &config, &ctx, &runtime),
+    }
 }
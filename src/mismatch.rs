@@ -0,0 +1,68 @@
+use crate::repo::Repository;
+use crate::score::CrateScore;
+
+/// Parts of crate names too generic to tie a crate to a repository.
+const GENERIC_PARTS: &[&str] = &[
+    "sys", "core", "derive", "macro", "macros", "impl", "internal", "shared", "util", "utils",
+    "common",
+];
+
+/// Flag crates whose repository seems to belong to other crates: nothing in the crate's
+/// name shows up in the repository path, while another crate in the tree pointing at
+/// the same repository does match it. A copy-pasted `repository` field looks like this,
+/// but so do some monorepos, so it is a prompt to check by hand, not a failure.
+///
+/// Crates in `allowed` (`allow_repo_mismatch`) and crates whose repository was shown to
+/// hold them by `--verify-provenance` are never flagged.
+pub fn flag(scores: &mut [CrateScore], allowed: &[String]) {
+    let flagged: Vec<bool> = scores
+        .iter()
+        .map(|crate_score| {
+            let Some(repo) = &crate_score.repo else {
+                return false;
+            };
+            !crate_score.listed
+                && crate_score.repo_verified != Some(true)
+                && !allowed.contains(&crate_score.name)
+                && !name_in_path(&crate_score.name, repo)
+                && !owners(scores, crate_score).is_empty()
+        })
+        .collect();
+    for (crate_score, flagged) in scores.iter_mut().zip(flagged) {
+        crate_score.repo_mismatch = flagged;
+    }
+}
+
+/// Other crates in the tree with the same repository whose names match its path.
+pub fn owners<'a>(scores: &'a [CrateScore], crate_score: &CrateScore) -> Vec<&'a str> {
+    let mut owners: Vec<&str> = scores
+        .iter()
+        .filter(|other| other.name != crate_score.name && other.repo == crate_score.repo)
+        .filter(|other| {
+            other
+                .repo
+                .as_ref()
+                .is_some_and(|repo| name_in_path(&other.name, repo))
+        })
+        .map(|other| other.name.as_str())
+        .collect();
+    owners.sort_unstable();
+    owners.dedup();
+    owners
+}
+
+/// Whether a distinctive part of the crate's name appears in the repository path.
+fn name_in_path(name: &str, repo: &Repository) -> bool {
+    let name = name.to_lowercase();
+    let path = repo.path.to_lowercase();
+    let mut parts = name
+        .split(['-', '_'])
+        .filter(|part| part.len() >= 3 && !GENERIC_PARTS.contains(part))
+        .peekable();
+    if parts.peek().is_none() {
+        return path
+            .replace(['-', '_'], "")
+            .contains(&name.replace(['-', '_'], ""));
+    }
+    parts.any(|part| path.contains(part))
+}
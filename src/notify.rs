@@ -0,0 +1,127 @@
+use anyhow::{Result, anyhow};
+use serde_json::json;
+
+use crate::cli::Args;
+use crate::grade::{self, GradeCutoffs};
+use crate::http::Http;
+use crate::policy::Violations;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Placeholders a `--notify-template` can use, in the order `message` fills them.
+const PLACEHOLDERS: &[&str] = &[
+    "crate_count",
+    "fail_count",
+    "aggregate",
+    "summary",
+    "worst_crates",
+    "report_url",
+];
+
+/// Build the notification body: Slack-compatible JSON, or the filled-in template.
+pub fn message(
+    scores: &[CrateScore],
+    violations: &Violations,
+    cutoffs: &GradeCutoffs,
+    args: &Args,
+) -> String {
+    let mut scored: Vec<(&CrateScore, f64)> = scores
+        .iter()
+        .filter_map(|c| match c.status {
+            ScoreStatus::Scored(score) => Some((c, score)),
+            _ => None,
+        })
+        .collect();
+    // Weighted by reach when the tree's shape is known, so a weak crate many others
+    // depend on comes before a weak leaf
+    let by_risk = scored.iter().any(|(c, _)| c.risk.is_some());
+    if by_risk {
+        let risk = |c: &CrateScore| c.risk.unwrap_or(f64::NEG_INFINITY);
+        scored.sort_by(|a, b| risk(b.0).total_cmp(&risk(a.0)));
+    } else {
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+    }
+    let worst_crates = scored
+        .iter()
+        .take(args.notify_worst)
+        .map(|(c, score)| format!("{} {} ({})", c.name, c.version, args.format_score(*score)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // Crates per grade band, then those the bands leave out
+    let mut summary: Vec<String> = grade::bands(scores, cutoffs)
+        .into_iter()
+        .map(|(letter, count)| format!("{}: {}", letter, count))
+        .collect();
+    let unscored = scores.len() - scored.len();
+    if unscored > 0 {
+        summary.push(format!("no score: {}", unscored));
+    }
+    let summary = summary.join(" · ");
+
+    let values = [
+        scores.len().to_string(),
+        violations.count().to_string(),
+        grade::aggregate(scores).map_or("n/a".to_string(), |score| args.format_score(score)),
+        summary,
+        worst_crates,
+        args.report_url.clone().unwrap_or_default(),
+    ];
+
+    if let Some(template) = &args.notify_template {
+        return PLACEHOLDERS
+            .iter()
+            .zip(&values)
+            .fold(template.clone(), |body, (name, value)| {
+                body.replace(&format!("{{{}}}", name), value)
+            });
+    }
+
+    let [
+        crate_count,
+        fail_count,
+        aggregate,
+        summary,
+        worst_crates,
+        report_url,
+    ] = values;
+    let mut text = format!(
+        "*Cargo Scorecard*: {} crates, {} policy failures, mean score {}\n{}",
+        crate_count, fail_count, aggregate, summary
+    );
+    if !worst_crates.is_empty() {
+        let heading = if by_risk {
+            "Highest risk"
+        } else {
+            "Lowest scores"
+        };
+        text.push_str(&format!("\n{}: {}", heading, worst_crates));
+    }
+    if !report_url.is_empty() {
+        text.push_str(&format!("\n<{}|Full report>", report_url));
+    }
+    json!({ "text": text }).to_string()
+}
+
+/// POST the message to the webhook.
+///
+/// Errors never include the URL, which usually embeds a secret token.
+pub async fn send(http: &Http, webhook: &str, body: String) -> Result<()> {
+    let response = http
+        .send(
+            http.client()
+                .post(webhook)
+                .header("content-type", "application/json")
+                .header("User-Agent", "cargo-scorecard/0.1.0")
+                .body(body),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to post the notification: {}", e.without_url()))?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Notification webhook answered {}",
+            response.status()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::{debug, warn};
+
+use crate::cache::CacheKind;
+use crate::fetch::Context;
+use crate::http::Http;
+use crate::score::CrateScore;
+
+/// A published advisory affecting the version in use, from OSV.dev.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Advisory {
+    /// e.g. `RUSTSEC-2023-0001` or `GHSA-...`.
+    pub id: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub published: Option<DateTime<Utc>>,
+    /// The same advisory in other databases, e.g. a `GHSA-...` for a `RUSTSEC-...`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Where the advisory was waived, e.g. `deny.toml`; waived advisories are listed
+    /// but not reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiver: Option<String>,
+}
+
+impl Advisory {
+    /// Whether `id` names this advisory, under its own ID or an alias.
+    pub fn is_known_as(&self, id: &str) -> bool {
+        self.id.eq_ignore_ascii_case(id) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(id))
+    }
+
+    /// Whether the advisory is reported under `--advisories-since`.
+    ///
+    /// The advisory's `published` timestamp is converted to a UTC date and kept when it
+    /// falls on or after the cutoff. Advisories without a publication date are always
+    /// kept, since they can't be shown to be old.
+    pub fn is_since(&self, since: Option<NaiveDate>) -> bool {
+        match (since, self.published) {
+            (Some(since), Some(published)) => published.date_naive() >= since,
+            _ => true,
+        }
+    }
+}
+
+/// Look up OSV advisories for every crate but local ones and `--repos-file` entries.
+///
+/// Crates whose lookup fails keep `advisories: None`, so the report can tell "none
+/// known" apart from "not checked".
+pub async fn attach_advisories(ctx: &Context, scores: &mut [CrateScore]) {
+    let lookups = futures::future::join_all(scores.iter().map(|crate_score| {
+        let version = crate_score.version.trim_start_matches('v').to_string();
+        let key = format!("{}@{}", crate_score.name, version);
+        async move {
+            // A `--repos-file` entry is no crate for OSV to know about, and a local crate
+            // only shares its name with whatever crates.io has under it
+            if crate_score.listed || crate_score.is_local() {
+                return Ok(None);
+            }
+            let json = ctx
+                .cache
+                .get_or_fetch(CacheKind::Advisories, &key, || {
+                    fetch_advisories(&ctx.http, &crate_score.name, &version)
+                })
+                .await?;
+            // OSV answers `{}` when nothing is known
+            match &json["vulns"] {
+                Value::Null => Ok(Some(Vec::new())),
+                vulns => serde_json::from_value::<Vec<Advisory>>(vulns.clone())
+                    .map(Some)
+                    .map_err(|e| anyhow!("Unexpected OSV JSON for {}: {}", key, e)),
+            }
+        }
+    }))
+    .await;
+
+    let mut failures = 0;
+    for (crate_score, lookup) in scores.iter_mut().zip(lookups) {
+        match lookup {
+            Ok(advisories) => crate_score.advisories = advisories,
+            Err(e) => {
+                debug!(crate = %crate_score.name, error = %e, "advisory lookup failed");
+                failures += 1;
+            }
+        }
+    }
+    if failures > 0 {
+        warn!("could not check advisories for {} crates", failures);
+    }
+}
+
+/// Query OSV for advisories affecting one crate version.
+async fn fetch_advisories(http: &Http, crate_name: &str, version: &str) -> Result<Value> {
+    let body = json!({
+        "version": version,
+        "package": { "name": crate_name, "ecosystem": "crates.io" },
+    });
+    let response = http
+        .send(
+            http.client()
+                .post("https://api.osv.dev/v1/query")
+                .header("User-Agent", "cargo-scorecard/0.1.0")
+                .json(&body),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to query OSV for {} {}: {}", crate_name, version, e))?;
+    debug!(crate = %crate_name, status = %response.status(), "OSV response");
+
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "OSV query failed for {} {}: {}",
+            crate_name,
+            version,
+            response.status()
+        ));
+    }
+    response.json().await.map_err(|e| {
+        anyhow!(
+            "Failed to parse OSV JSON for {} {}: {}",
+            crate_name,
+            version,
+            e
+        )
+    })
+}
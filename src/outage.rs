@@ -0,0 +1,69 @@
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+
+/// Watches the first scorecard API requests of a run for a total outage.
+///
+/// When the first `threshold` requests all fail, the API is taken to be down and
+/// the remaining lookups are skipped instead of each waiting out its own failure.
+/// One answer from the API, a 404 included, rules an outage out for the run.
+pub struct OutageDetector {
+    /// Failures in a row it takes; 0 never gives up on the API.
+    threshold: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    failures: usize,
+    answered: bool,
+    /// The last failure, quoted when the API is declared down.
+    last_error: Option<String>,
+}
+
+impl OutageDetector {
+    pub fn new(threshold: usize) -> Self {
+        OutageDetector {
+            threshold,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Record a request that got an answer from the API.
+    pub fn answered(&self) {
+        self.state.lock().unwrap().answered = true;
+    }
+
+    /// Record a request that failed outright: no response, or an error status.
+    pub fn failed(&self, error: &anyhow::Error) {
+        let mut state = self.state.lock().unwrap();
+        state.failures += 1;
+        state.last_error = Some(error.to_string());
+    }
+
+    /// Whether every request so far has failed, and at least `threshold` of them.
+    pub fn is_down(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        self.threshold > 0 && !state.answered && state.failures >= self.threshold
+    }
+
+    /// The error for a lookup skipped because the API is down.
+    pub fn skipped(&self) -> anyhow::Error {
+        anyhow!("skipped: the scorecard API appears to be down")
+    }
+
+    /// The warning to print once the run is over, when the API was down.
+    pub fn warning(&self) -> Option<String> {
+        if !self.is_down() {
+            return None;
+        }
+        let state = self.state.lock().unwrap();
+        Some(format!(
+            "The scorecard API appears to be down: the first {} requests all failed (last: {}), \
+             so the remaining lookups were skipped and scores are missing. Unless this machine \
+             has no network, the problem is upstream; try again later, or raise --api-down-after",
+            state.failures,
+            state.last_error.as_deref().unwrap_or("unknown error")
+        ))
+    }
+}
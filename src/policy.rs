@@ -0,0 +1,643 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Error, anyhow};
+use chrono::{NaiveDate, Utc};
+use clap::ValueEnum;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+use crate::checks;
+use crate::cli::{Args, PartialPolicy};
+use crate::config::Config;
+use crate::coverage::{self, Coverage, UnsupportedForges};
+use crate::deps::DepKind;
+use crate::diff::Change;
+use crate::duplicates::{self, Duplicate};
+use crate::score::{CrateScore, ScoreStatus};
+use crate::scorecard::CheckRequirement;
+
+/// The `max_scan_age` under `--strict`: past it, a scan only rates low confidence.
+pub const STRICT_SCAN_AGE: u32 = 180;
+
+/// Which dependencies a policy rule applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Only dependencies of workspace members.
+    Direct,
+    /// Every crate in the dependency tree.
+    All,
+}
+
+impl Scope {
+    fn includes(self, crate_score: &CrateScore) -> bool {
+        match self {
+            Scope::Direct => crate_score.direct,
+            Scope::All => true,
+        }
+    }
+}
+
+/// A crate name with an optional version requirement, written `name` or `name@<req>`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct CrateSpec {
+    pub name: String,
+    /// Matching versions, in Cargo's requirement syntax; all versions if unset.
+    pub req: Option<VersionReq>,
+}
+
+impl CrateSpec {
+    pub fn matches(&self, crate_score: &CrateScore) -> bool {
+        self.matches_crate(&crate_score.name, &crate_score.version)
+    }
+
+    pub fn matches_crate(&self, name: &str, version: &str) -> bool {
+        if name != self.name {
+            return false;
+        }
+        let Some(req) = &self.req else {
+            return true;
+        };
+        let version = version.trim_start_matches('v');
+        Version::parse(version).is_ok_and(|version| req.matches(&version))
+    }
+}
+
+impl FromStr for CrateSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, req) = match s.split_once('@') {
+            Some((name, req)) => {
+                let req = VersionReq::parse(req)
+                    .map_err(|e| anyhow!("invalid version requirement in {}: {}", s, e))?;
+                (name, Some(req))
+            }
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(anyhow!("missing crate name in {}", s));
+        }
+        Ok(CrateSpec {
+            name: name.to_string(),
+            req,
+        })
+    }
+}
+
+impl TryFrom<String> for CrateSpec {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for CrateSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.req {
+            Some(req) => write!(f, "{}@{}", self.name, req),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Rules that turn report findings into a failing exit code.
+pub struct Policy {
+    pub fail_on_no_repository: Option<Scope>,
+    pub fail_on_dead_repo: bool,
+    pub fail_on_unverified: bool,
+    pub fail_under: Option<f64>,
+    /// Like `fail_under`, for crates running code at build time.
+    pub fail_under_build_time: Option<f64>,
+    /// Per-check minimums for scored crates in `required_checks_scope`.
+    pub required_checks: Vec<CheckRequirement>,
+    pub required_checks_scope: Scope,
+    /// Whether a required check without data fails.
+    pub missing_check: PartialPolicy,
+    pub min_version_age: Option<u32>,
+    pub min_direct_version_age: Option<u32>,
+    pub deny: Vec<CrateSpec>,
+    /// Crate names that must resolve to a single version; `all` for every crate.
+    pub fail_on_duplicates: Vec<String>,
+    /// Most crates, as a percentage, allowed to have no score data.
+    pub max_unknown: Option<f64>,
+    /// Most crates allowed to end up without a score, whatever the reason; local crates
+    /// aside, every crate counts, trusted and waived ones included.
+    pub max_unscored: Option<usize>,
+    pub unsupported_forges: UnsupportedForges,
+    /// Oldest scorecard scan, in days, a scored crate may have.
+    pub max_scan_age: Option<u32>,
+    pub advisories_since: Option<NaiveDate>,
+    pub fail_on_advisory: bool,
+    pub fail_on_regression: bool,
+    /// Dependency kinds `fail_on_regression` applies to; empty for all.
+    pub regression_kinds: Vec<DepKind>,
+}
+
+/// A crate matched by a deny rule.
+pub struct Denied<'a> {
+    pub crate_score: &'a CrateScore,
+    pub rule: &'a CrateSpec,
+    /// How a workspace member pulls the crate in, outermost first; filled in by the caller.
+    pub path: Vec<String>,
+}
+
+/// A crate falling short of a required check.
+pub struct CheckFailure<'a> {
+    pub crate_score: &'a CrateScore,
+    pub requirement: &'a CheckRequirement,
+    /// The check's score; `None` when the check has no data.
+    pub score: Option<f64>,
+    /// Where the check is documented.
+    pub documentation: String,
+}
+
+/// Crates breaking the policy, grouped by rule.
+pub struct Violations<'a> {
+    /// The policy that was evaluated.
+    pub policy: &'a Policy,
+    pub no_repository: Vec<&'a CrateScore>,
+    pub dead_repo: Vec<&'a CrateScore>,
+    /// Repositories without a Cargo.toml for the crate claiming them.
+    pub unverified: Vec<&'a CrateScore>,
+    pub below_threshold: Vec<&'a CrateScore>,
+    /// One entry per crate and required check it falls short of.
+    pub check_failures: Vec<CheckFailure<'a>>,
+    /// Versions published more recently than the applicable minimum age.
+    pub too_new: Vec<&'a CrateScore>,
+    pub denied: Vec<Denied<'a>>,
+    pub regressions: Vec<&'a CrateScore>,
+    /// Crates with advisories published since `advisories_since`.
+    pub advisories: Vec<&'a CrateScore>,
+    /// Crates covered by `fail_on_duplicates` resolved at more than one version.
+    pub duplicates: Vec<Duplicate<'a>>,
+    /// Score coverage of the tree, computed whether or not `max_unknown` is set.
+    pub coverage: Coverage,
+    /// The crates without a score when they exceed `max_unknown`, empty otherwise.
+    pub unknown: Vec<&'a CrateScore>,
+    /// The crates without a score when there are more than `max_unscored`, empty otherwise.
+    pub unscored: Vec<&'a CrateScore>,
+    /// Crates whose scan is older than `max_scan_age`, or undated.
+    pub stale: Vec<&'a CrateScore>,
+}
+
+impl Policy {
+    /// Combine the command line with the config file, the command line taking precedence;
+    /// `--strict` fills in whatever neither sets.
+    pub fn new(args: &Args, config: &Config) -> Self {
+        let strict = args.strict;
+        Policy {
+            fail_on_no_repository: args
+                .fail_on_no_repository
+                .or(config.fail_on_no_repository)
+                .or(strict.then_some(Scope::All)),
+            fail_on_dead_repo: args.fail_on_dead_repo || config.fail_on_dead_repo || strict,
+            fail_on_unverified: args.fail_on_unverified,
+            fail_under: args.fail_under.or(config.fail_under),
+            fail_under_build_time: args.fail_under_build_time.or(config.fail_under_build_time),
+            required_checks: args.require_check.clone(),
+            required_checks_scope: args.require_check_scope,
+            missing_check: args.on_missing_check,
+            min_version_age: args.min_version_age.or(config.min_version_age),
+            min_direct_version_age: args
+                .min_direct_version_age
+                .or(config.min_direct_version_age),
+            deny: config.deny.iter().chain(&args.deny).cloned().collect(),
+            fail_on_duplicates: if !args.fail_on_duplicates.is_empty() {
+                args.fail_on_duplicates.clone()
+            } else if !config.fail_on_duplicates.is_empty() || !strict {
+                config.fail_on_duplicates.clone()
+            } else {
+                vec!["all".to_string()]
+            },
+            max_unknown: args
+                .max_unknown
+                .or(config.max_unknown)
+                .or(strict.then_some(0.0)),
+            max_unscored: args.max_unscored.or(config.max_unscored),
+            unsupported_forges: args
+                .unsupported_forges
+                .or(config.unsupported_forges)
+                .unwrap_or_default(),
+            max_scan_age: args.max_scan_age.or(strict.then_some(STRICT_SCAN_AGE)),
+            advisories_since: args.advisories_since,
+            fail_on_advisory: args.fail_on_advisory,
+            fail_on_regression: args.fail_on_regression,
+            regression_kinds: args.kind.clone(),
+        }
+    }
+
+    /// Check every crate against the rules. Trusted and waived crates are exempt from
+    /// all but `deny` and `max_unscored`.
+    pub fn evaluate<'a>(&'a self, scores: &'a [CrateScore]) -> Violations<'a> {
+        let untrusted = || scores.iter().filter(|c| !c.trusted && c.waiver.is_none());
+
+        // Local crates aren't published, so they have no registry metadata to lack
+        let no_repository = match self.fail_on_no_repository {
+            Some(scope) => untrusted()
+                .filter(|c| matches!(c.status, ScoreStatus::NoRepository) && !c.is_local())
+                .filter(|c| scope.includes(c))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let dead_repo = if self.fail_on_dead_repo {
+            untrusted()
+                .filter(|c| matches!(c.status, ScoreStatus::Unreachable(_)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Trust comes from the repository, so it doesn't exempt a repository that was claimed
+        let unverified = if self.fail_on_unverified {
+            scores
+                .iter()
+                .filter(|c| c.waiver.is_none() && c.repo_verified == Some(false))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let below_threshold = untrusted()
+            .filter(|c| {
+                let threshold = self.fail_under(c);
+                matches!((&c.status, threshold), (ScoreStatus::Scored(score), Some(threshold)) if *score < threshold)
+            })
+            .collect();
+
+        // Crates without a scorecard are left to `max_unknown`
+        let check_failures = untrusted()
+            .filter(|c| self.required_checks_scope.includes(c))
+            .filter_map(|c| Some((c, c.scorecard.as_ref()?)))
+            .flat_map(|(crate_score, result)| {
+                self.required_checks.iter().filter_map(move |requirement| {
+                    let check = result
+                        .checks
+                        .iter()
+                        .find(|check| check.name.eq_ignore_ascii_case(&requirement.name));
+                    let score = check
+                        .and_then(|check| check.score)
+                        .filter(|score| *score >= 0.0);
+                    let failed = match score {
+                        Some(score) => score < requirement.min,
+                        None => self.missing_check == PartialPolicy::Fail,
+                    };
+                    failed.then(|| CheckFailure {
+                        crate_score,
+                        requirement,
+                        score,
+                        documentation: check
+                            .and_then(|check| check.documentation.as_ref()?.url.clone())
+                            .unwrap_or_else(|| checks::documentation_url(&requirement.name)),
+                    })
+                })
+            })
+            .collect();
+
+        let now = Utc::now();
+        let too_new = untrusted()
+            .filter(|c| {
+                let min_age = self.min_version_age(c);
+                c.version_age_days(now)
+                    .zip(min_age)
+                    .is_some_and(|(age, min_age)| age < i64::from(min_age))
+            })
+            .collect();
+
+        // Crates of unknown kind (vendored or audited trees) are never assumed test-only
+        let regressions = if self.fail_on_regression {
+            untrusted()
+                .filter(|c| Change::of(c).is_regression())
+                .filter(|c| match c.kind {
+                    Some(kind) if !self.regression_kinds.is_empty() => {
+                        self.regression_kinds.contains(&kind)
+                    }
+                    _ => true,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let advisories = if self.fail_on_advisory {
+            untrusted()
+                .filter(|c| !c.reported_advisories(self.advisories_since).is_empty())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let duplicates = duplicates::find(scores)
+            .into_iter()
+            .filter(|duplicate| self.requires_single_version(duplicate.name))
+            .collect();
+
+        let coverage = Coverage::of(scores, self.unsupported_forges);
+        let unknown = match self.max_unknown {
+            Some(max) if coverage.unknown_percent() > max => {
+                coverage::considered(scores, self.unsupported_forges)
+                    .filter(|c| coverage::is_unknown(&c.status))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        // Unlike the other rules this counts trusted and waived crates too: it guards the
+        // audit's own coverage, not the dependencies
+        let unscored: Vec<&CrateScore> = scores
+            .iter()
+            .filter(|c| !c.is_local() && !matches!(c.status, ScoreStatus::Scored(_)))
+            .collect();
+        let unscored = match self.max_unscored {
+            Some(max) if unscored.len() > max => unscored,
+            _ => Vec::new(),
+        };
+
+        let today = now.date_naive();
+        let stale = match self.max_scan_age {
+            Some(max_age) => untrusted()
+                .filter(|c| {
+                    c.scorecard.as_ref().is_some_and(|result| {
+                        result
+                            .date()
+                            .is_none_or(|date| (today - date).num_days() > i64::from(max_age))
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let denied = scores
+            .iter()
+            .filter_map(|crate_score| {
+                let rule = self.deny.iter().find(|rule| rule.matches(crate_score))?;
+                Some(Denied {
+                    crate_score,
+                    rule,
+                    path: Vec::new(),
+                })
+            })
+            .collect();
+
+        Violations {
+            policy: self,
+            no_repository,
+            dead_repo,
+            unverified,
+            below_threshold,
+            check_failures,
+            too_new,
+            denied,
+            regressions,
+            advisories,
+            duplicates,
+            coverage,
+            unknown,
+            unscored,
+            stale,
+        }
+    }
+}
+
+impl Policy {
+    /// The score a crate must reach, the stricter one for crates running build-time code.
+    pub fn fail_under(&self, crate_score: &CrateScore) -> Option<f64> {
+        let build_time = self
+            .fail_under_build_time
+            .filter(|_| crate_score.build_time.any());
+        self.fail_under
+            .into_iter()
+            .chain(build_time)
+            .reduce(f64::max)
+    }
+
+    /// The minimum version age for a crate, the stricter one for direct dependencies.
+    pub fn min_version_age(&self, crate_score: &CrateScore) -> Option<u32> {
+        let direct = self.min_direct_version_age.filter(|_| crate_score.direct);
+        self.min_version_age.into_iter().chain(direct).max()
+    }
+
+    /// Whether `fail_on_duplicates` covers the crate `name`.
+    fn requires_single_version(&self, name: &str) -> bool {
+        self.fail_on_duplicates
+            .iter()
+            .any(|entry| entry == "all" || entry == name)
+    }
+
+    /// Whether a minimum version age is in force for any crate.
+    pub fn checks_version_age(&self) -> bool {
+        self.min_version_age.is_some() || self.min_direct_version_age.is_some()
+    }
+}
+
+impl Violations<'_> {
+    /// Total number of violations across every rule.
+    pub fn count(&self) -> usize {
+        self.no_repository.len()
+            + self.dead_repo.len()
+            + self.unverified.len()
+            + self.below_threshold.len()
+            + self.check_failures.len()
+            + self.too_new.len()
+            + self.denied.len()
+            + self.regressions.len()
+            + self.advisories.len()
+            + self.duplicates.len()
+            + self.unknown.len()
+            + self.unscored.len()
+            + self.stale.len()
+    }
+
+    /// Number of distinct crates breaking at least one rule.
+    pub fn failing_crates(&self) -> usize {
+        self.failing().len()
+    }
+
+    /// The distinct crates breaking at least one rule, by name and version.
+    pub fn failing(&self) -> Vec<&CrateScore> {
+        let mut crates: Vec<&CrateScore> = self
+            .no_repository
+            .iter()
+            .chain(&self.dead_repo)
+            .chain(&self.unverified)
+            .chain(&self.below_threshold)
+            .chain(&self.too_new)
+            .chain(&self.regressions)
+            .chain(&self.advisories)
+            .chain(&self.unknown)
+            .chain(&self.unscored)
+            .chain(&self.stale)
+            .copied()
+            .chain(self.denied.iter().map(|denied| denied.crate_score))
+            .chain(
+                self.check_failures
+                    .iter()
+                    .map(|failure| failure.crate_score),
+            )
+            .chain(
+                self.duplicates
+                    .iter()
+                    .flat_map(|duplicate| duplicate.versions.iter().map(|v| v.crate_score)),
+            )
+            .collect();
+        crates.sort_by_key(|c| (&c.name, &c.version));
+        crates.dedup_by_key(|c| (&c.name, &c.version));
+        crates
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.no_repository.is_empty()
+            && self.dead_repo.is_empty()
+            && self.unverified.is_empty()
+            && self.below_threshold.is_empty()
+            && self.check_failures.is_empty()
+            && self.too_new.is_empty()
+            && self.denied.is_empty()
+            && self.regressions.is_empty()
+            && self.advisories.is_empty()
+            && self.duplicates.is_empty()
+            && self.unknown.is_empty()
+            && self.unscored.is_empty()
+            && self.stale.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::deps::CrateSource;
+    use crate::score::tests::crate_score;
+
+    fn policy(flags: &[&str]) -> Policy {
+        let args =
+            Args::parse_from(std::iter::once("cargo-scorecard").chain(flags.iter().copied()));
+        Policy::new(&args, &Config::default())
+    }
+
+    fn names(crates: &[&CrateScore]) -> Vec<String> {
+        crates.iter().map(|c| c.name.clone()).collect()
+    }
+
+    #[test]
+    fn no_repository_exempts_path_and_workspace_crates() {
+        let mut workspace_member = crate_score("member", ScoreStatus::NoRepository);
+        workspace_member.source = Some(CrateSource::Path);
+        let scores = [
+            crate_score("published", ScoreStatus::NoRepository),
+            workspace_member,
+            crate_score("path-dep", ScoreStatus::Local),
+        ];
+
+        let policy = policy(&["--fail-on-no-repository"]);
+        let violations = policy.evaluate(&scores);
+        assert_eq!(names(&violations.no_repository), ["published"]);
+        assert_eq!(violations.failing_crates(), 1);
+    }
+
+    #[test]
+    fn no_repository_direct_scope_skips_transitive_crates() {
+        let mut direct = crate_score("direct", ScoreStatus::NoRepository);
+        direct.direct = true;
+        let scores = [direct, crate_score("transitive", ScoreStatus::NoRepository)];
+
+        let direct_only = policy(&["--fail-on-no-repository=direct"]);
+        assert_eq!(
+            names(&direct_only.evaluate(&scores).no_repository),
+            ["direct"]
+        );
+
+        let unset = policy(&[]);
+        assert!(unset.evaluate(&scores).is_empty());
+    }
+
+    #[test]
+    fn fail_under_exempts_trusted_crates_but_deny_does_not() {
+        let mut trusted = crate_score("trusted", ScoreStatus::Scored(2.0));
+        trusted.trusted = true;
+        let scores = [
+            crate_score("low", ScoreStatus::Scored(3.0)),
+            crate_score("high", ScoreStatus::Scored(8.0)),
+            trusted,
+        ];
+
+        let policy = policy(&["--fail-under", "5", "--deny", "trusted"]);
+        let violations = policy.evaluate(&scores);
+        assert_eq!(names(&violations.below_threshold), ["low"]);
+        assert_eq!(violations.denied.len(), 1);
+        assert_eq!(violations.denied[0].crate_score.name, "trusted");
+        assert_eq!(violations.count(), 2);
+    }
+
+    #[test]
+    fn deny_matches_version_requirements() {
+        let spec: CrateSpec = "openssl@<0.10".parse().unwrap();
+        assert!(spec.matches_crate("openssl", "v0.9.24"));
+        assert!(!spec.matches_crate("openssl", "v0.10.1"));
+        assert!(!spec.matches_crate("openssl-sys", "v0.9.24"));
+        assert!("@1".parse::<CrateSpec>().is_err());
+    }
+
+    #[test]
+    fn max_unscored_counts_every_published_crate_without_a_score() {
+        let mut trusted = crate_score("trusted", ScoreStatus::NotScanned);
+        trusted.trusted = true;
+        let scores = [
+            crate_score("scored", ScoreStatus::Scored(7.0)),
+            crate_score("unscanned", ScoreStatus::NotScanned),
+            crate_score(
+                "gitlab",
+                ScoreStatus::UnsupportedForge("gitlab.example".into()),
+            ),
+            crate_score("path-dep", ScoreStatus::Local),
+            trusted,
+        ];
+
+        let within = policy(&["--max-unscored", "3"]);
+        assert!(within.evaluate(&scores).unscored.is_empty());
+
+        let over = policy(&["--max-unscored", "2", "--unsupported-forges", "exclude"]);
+        let violations = over.evaluate(&scores);
+        assert_eq!(
+            names(&violations.unscored),
+            ["unscanned", "gitlab", "trusted"]
+        );
+        assert_eq!(
+            coverage::list_categories(&coverage::by_category(violations.unscored.iter().copied())),
+            "Not scanned 2, Unsupported forge 1"
+        );
+    }
+
+    #[test]
+    fn waived_advisories_do_not_fail_the_run() {
+        let advisory = |id: &str, alias: &str| crate::osv::Advisory {
+            id: id.to_string(),
+            summary: None,
+            published: None,
+            aliases: vec![alias.to_string()],
+            waiver: None,
+        };
+        let mut triaged = crate_score("triaged", ScoreStatus::Scored(7.0));
+        triaged.advisories = Some(vec![advisory("RUSTSEC-2024-0001", "GHSA-aaaa-bbbb-cccc")]);
+        let mut vulnerable = crate_score("vulnerable", ScoreStatus::Scored(7.0));
+        vulnerable.advisories = Some(vec![advisory("RUSTSEC-2024-0002", "CVE-2024-0002")]);
+        let mut scores = [triaged, vulnerable];
+
+        for advisory in scores
+            .iter_mut()
+            .flat_map(|c| c.advisories.iter_mut().flatten())
+        {
+            if advisory.is_known_as("ghsa-aaaa-bbbb-cccc") {
+                advisory.waiver = Some("deny.toml".to_string());
+            }
+        }
+
+        let policy = policy(&["--fail-on-advisory"]);
+        assert_eq!(names(&policy.evaluate(&scores).advisories), ["vulnerable"]);
+    }
+}
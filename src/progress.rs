@@ -0,0 +1,72 @@
+use std::cell::Cell;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// How often a captured log gets a progress line.
+const LINE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How progress is shown on stderr.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Off,
+    /// A counter redrawn in place, for a terminal.
+    Redraw,
+    /// A plain line every [`LINE_INTERVAL`], for CI logs and other captured output.
+    Lines,
+}
+
+/// Counts crates as their lookups finish, e.g. `fetched 10/200`.
+pub struct Progress {
+    mode: Mode,
+    total: usize,
+    done: Cell<usize>,
+    last_line: Cell<Instant>,
+}
+
+impl Progress {
+    /// Redraw a counter when stderr is a terminal, and print plain lines otherwise;
+    /// nothing at all when `enabled` is false (`--no-progress`).
+    pub fn new(total: usize, enabled: bool) -> Self {
+        let mode = match enabled {
+            false => Mode::Off,
+            true if std::io::stderr().is_terminal() => Mode::Redraw,
+            true => Mode::Lines,
+        };
+        Progress {
+            mode,
+            total,
+            done: Cell::new(0),
+            last_line: Cell::new(Instant::now()),
+        }
+    }
+
+    /// Record one finished crate.
+    pub fn tick(&self) {
+        let done = self.done.get() + 1;
+        self.done.set(done);
+        let mut stderr = std::io::stderr().lock();
+        match self.mode {
+            Mode::Off => {}
+            Mode::Redraw => {
+                let _ = write!(stderr, "\r\x1b[Kfetched {}/{}", done, self.total);
+                let _ = stderr.flush();
+            }
+            Mode::Lines => {
+                if self.last_line.get().elapsed() >= LINE_INTERVAL {
+                    self.last_line.set(Instant::now());
+                    let _ = writeln!(stderr, "fetched {}/{}", done, self.total);
+                    let _ = stderr.flush();
+                }
+            }
+        }
+    }
+
+    /// Clear a redrawn counter so the report starts on a clean line.
+    pub fn finish(&self) {
+        if self.mode == Mode::Redraw {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r\x1b[K");
+            let _ = stderr.flush();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+use tracing::debug;
+
+/// The parts of the project's own Cargo.toml that name its repository.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Manifest {
+    package: Option<Package>,
+    workspace: Option<Workspace>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Workspace {
+    package: Option<Package>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Package {
+    /// A URL, or `{ workspace = true }` when inherited.
+    repository: Option<toml::Value>,
+}
+
+/// The repository of the project being scored.
+///
+/// Read from the root manifest's `repository` (or `[workspace.package]`), falling back
+/// to the URL of the `origin` git remote next to it.
+pub fn repository(manifest: &Path) -> Option<String> {
+    let from_manifest = std::fs::read_to_string(manifest)
+        .ok()
+        .and_then(|content| toml::from_str::<Manifest>(&content).ok())
+        .and_then(|manifest| {
+            let workspace = manifest.workspace.and_then(|w| w.package);
+            [manifest.package, workspace]
+                .into_iter()
+                .flatten()
+                .find_map(|package| Some(package.repository?.as_str()?.to_string()))
+        });
+    from_manifest.or_else(|| origin_url(manifest.parent().unwrap_or(Path::new(""))))
+}
+
+/// The `origin` remote of the git checkout containing `dir`, as an https URL.
+fn origin_url(dir: &Path) -> Option<String> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        debug!(dir = %dir.display(), "no origin remote");
+        return None;
+    }
+    let url = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    // scp-like remotes such as git@github.com:owner/repo.git
+    match url
+        .split_once('@')
+        .and_then(|(_, rest)| rest.split_once(':'))
+    {
+        Some((host, path)) if !url.contains("://") => Some(format!("https://{}/{}", host, path)),
+        _ => Some(url),
+    }
+}
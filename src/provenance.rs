@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use anyhow::{Result, anyhow};
+use reqwest::StatusCode;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::cache::CacheKind;
+use crate::fetch::Context;
+use crate::repo::Repository;
+use crate::score::CrateScore;
+
+/// Check that each crate's repository has a Cargo.toml for a package of that name.
+///
+/// Only github.com and gitlab.com repositories are checked, reading the default branch
+/// through the forge's raw file endpoint. The root manifest is read once per repository;
+/// workspace members it lists are read as needed, with `dir/*` members tried as
+/// `dir/<crate name>`. A crate is left unchecked when any read fails.
+pub async fn verify(ctx: &Context, scores: &mut [CrateScore]) {
+    let mut by_repo: BTreeMap<&Repository, BTreeSet<&str>> = BTreeMap::new();
+    for crate_score in scores.iter().filter(|c| !c.listed) {
+        if let Some(repo) = crate_score
+            .repo
+            .as_ref()
+            .filter(|repo| raw_url(repo, "").is_some())
+        {
+            by_repo.entry(repo).or_default().insert(&crate_score.name);
+        }
+    }
+
+    let results = futures::future::join_all(by_repo.into_iter().map(|(repo, names)| async move {
+        let found = packages_in(ctx, repo, &names).await;
+        (repo.clone(), names, found)
+    }))
+    .await;
+
+    let mut verified: BTreeMap<(Repository, String), Option<bool>> = BTreeMap::new();
+    for (repo, names, found) in results {
+        for name in names {
+            let result = match &found {
+                Ok(packages) => Some(packages.contains(name)),
+                Err(e) => {
+                    debug!(repo = %repo, error = %e, "could not check the repository's manifests");
+                    None
+                }
+            };
+            verified.insert((repo.clone(), name.to_string()), result);
+        }
+    }
+    for crate_score in scores {
+        crate_score.repo_verified = crate_score.repo.as_ref().and_then(|repo| {
+            verified
+                .get(&(repo.clone(), crate_score.name.clone()))
+                .copied()
+                .flatten()
+        });
+    }
+}
+
+/// The package names among the manifests that could hold `names` in `repo`.
+async fn packages_in(
+    ctx: &Context,
+    repo: &Repository,
+    names: &BTreeSet<&str>,
+) -> Result<HashSet<String>> {
+    let mut packages = HashSet::new();
+    let root = manifest(ctx, repo, "").await?;
+    let mut dirs = Vec::new();
+    if let Some(root) = &root {
+        packages.extend(package_name(root));
+        let members = root
+            .get("workspace")
+            .and_then(|workspace| workspace.get("members"))
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(toml::Value::as_str);
+        for member in members {
+            match member.strip_suffix("/*") {
+                Some(parent) => {
+                    dirs.extend(names.iter().map(|name| format!("{}/{}", parent, name)))
+                }
+                None if !member.contains('*') => dirs.push(member.to_string()),
+                None => debug!(repo = %repo, member, "skipping workspace member pattern"),
+            }
+        }
+    }
+    // Crates often live in a directory named after them, listed or not
+    dirs.extend(names.iter().map(|name| name.to_string()));
+
+    dirs.sort();
+    dirs.dedup();
+    for dir in dirs {
+        if names.iter().all(|name| packages.contains(*name)) {
+            break;
+        }
+        if let Some(member) = manifest(ctx, repo, &dir).await? {
+            packages.extend(package_name(&member));
+        }
+    }
+    Ok(packages)
+}
+
+/// `package.name` of a parsed manifest.
+fn package_name(manifest: &toml::Value) -> Option<String> {
+    let name = manifest.get("package")?.get("name")?.as_str()?;
+    Some(name.to_string())
+}
+
+/// The Cargo.toml in `dir` of the repository's default branch, `None` when there is none.
+async fn manifest(ctx: &Context, repo: &Repository, dir: &str) -> Result<Option<toml::Value>> {
+    let path = if dir.is_empty() {
+        "Cargo.toml".to_string()
+    } else {
+        format!("{}/Cargo.toml", dir.trim_end_matches('/'))
+    };
+    let key = format!("{}/{}", repo, path);
+    let content = ctx
+        .cache
+        .get_or_fetch(CacheKind::RepoManifest, &key, || {
+            fetch_file(ctx, repo, &path)
+        })
+        .await?;
+    let Some(content) = content.as_str() else {
+        return Ok(None);
+    };
+    // An unparsable manifest names no package, the same as a missing one
+    Ok(toml::from_str(content).ok())
+}
+
+/// Fetch a file from the default branch as a JSON string, or `null` when it's missing.
+async fn fetch_file(ctx: &Context, repo: &Repository, path: &str) -> Result<Value> {
+    let url = raw_url(repo, path).ok_or_else(|| anyhow!("No raw file endpoint for {}", repo))?;
+    let response = ctx
+        .http
+        .send(
+            ctx.http
+                .client()
+                .get(&url)
+                .header("User-Agent", "cargo-scorecard/0.1.0"),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to fetch {} from {}: {}", path, repo, e))?;
+    debug!(url = %url, status = %response.status(), "repository file");
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(Value::Null);
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Fetching {} from {} failed: {}",
+            path,
+            repo,
+            response.status()
+        ));
+    }
+    let content = response
+        .text()
+        .await
+        .map_err(|e| anyhow!("Failed to read {} from {}: {}", path, repo, e))?;
+    Ok(Value::String(content))
+}
+
+/// The raw file URL for `path` on the default branch, for the forges that have one.
+fn raw_url(repo: &Repository, path: &str) -> Option<String> {
+    match repo.host.as_str() {
+        "github.com" => Some(format!(
+            "https://raw.githubusercontent.com/{}/HEAD/{}",
+            repo.path, path
+        )),
+        "gitlab.com" => Some(format!(
+            "https://gitlab.com/{}/-/raw/HEAD/{}",
+            repo.path, path
+        )),
+        _ => None,
+    }
+}
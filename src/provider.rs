@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use serde::Deserialize;
+
+use crate::fetch::{self, Context};
+use crate::repo::Repository;
+use crate::scorecard::ScorecardResult;
+
+/// A source of repository scores.
+///
+/// The fetch pipeline resolves each crate to a repository and asks the provider once per
+/// distinct repository. The answer becomes the row's status: a result with a score is
+/// Scored, one without a score Pending, and `None` Not scanned (then checked for existence
+/// unless `--no-verify-repos`). Repositories the provider doesn't [`support`] are reported
+/// as on an unsupported forge without asking.
+///
+/// To add a backend, implement this trait, add a [`Provider`] variant for `--provider`
+/// and build it in [`Provider::build`].
+///
+/// [`support`]: ScoreProvider::supports
+pub trait ScoreProvider {
+    /// The result for `repo`, or `None` when the provider has never scored it.
+    fn score<'a>(
+        &'a self,
+        ctx: &'a Context,
+        repo: &'a Repository,
+    ) -> LocalBoxFuture<'a, Result<Option<ScorecardResult>>>;
+
+    /// Whether the provider can score repositories like `repo` at all.
+    fn supports(&self, repo: &Repository) -> bool;
+}
+
+/// The `--provider` choices.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Provider {
+    /// The OpenSSF Scorecard API
+    #[default]
+    Openssf,
+    /// Scores read from --provider-file
+    File,
+}
+
+impl Provider {
+    /// The provider to score with, reading `--provider-file` for `file`.
+    pub fn build(self, file: Option<&Path>) -> Result<Box<dyn ScoreProvider>> {
+        match (self, file) {
+            (Provider::Openssf, None) => Ok(Box::new(OpenSsf)),
+            (Provider::Openssf, Some(_)) => Err(anyhow!("--provider-file needs --provider file")),
+            (Provider::File, Some(path)) => Ok(Box::new(StaticFile::load(path)?)),
+            (Provider::File, None) => Err(anyhow!("--provider file needs --provider-file")),
+        }
+    }
+}
+
+/// The OpenSSF Scorecard API, with caching, pending-scan retries and renamed GitHub
+/// repositories handled.
+pub struct OpenSsf;
+
+impl ScoreProvider for OpenSsf {
+    fn score<'a>(
+        &'a self,
+        ctx: &'a Context,
+        repo: &'a Repository,
+    ) -> LocalBoxFuture<'a, Result<Option<ScorecardResult>>> {
+        fetch::fetch_repo_score(ctx, repo).boxed_local()
+    }
+
+    fn supports(&self, repo: &Repository) -> bool {
+        repo.is_scorecard_supported()
+    }
+}
+
+/// One entry of a `--provider-file`: a bare score, or a scorecard-shaped result.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FileEntry {
+    Score(f64),
+    Result(ScorecardResult),
+}
+
+/// Scores from a JSON file mapping `host/owner/repo` to a 0-10 score or to a result
+/// shaped like the scorecard API's (`score`, `date`, `checks`), e.g. exported from an
+/// internal risk service. Repositories it doesn't list are not scanned.
+pub struct StaticFile {
+    scores: HashMap<String, ScorecardResult>,
+}
+
+impl StaticFile {
+    fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let entries: HashMap<String, FileEntry> = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        let mut scores = HashMap::with_capacity(entries.len());
+        for (key, entry) in entries {
+            let repo = Repository::parse(&format!("https://{}", key)).ok_or_else(|| {
+                anyhow!(
+                    "{}: {} is not a repository such as github.com/owner/repo",
+                    path.display(),
+                    key
+                )
+            })?;
+            let result = match entry {
+                FileEntry::Score(score) => ScorecardResult {
+                    score: Some(score),
+                    ..ScorecardResult::default()
+                },
+                FileEntry::Result(result) => result,
+            };
+            scores.insert(repo.to_string(), result);
+        }
+        Ok(StaticFile { scores })
+    }
+}
+
+impl ScoreProvider for StaticFile {
+    fn score<'a>(
+        &'a self,
+        _ctx: &'a Context,
+        repo: &'a Repository,
+    ) -> LocalBoxFuture<'a, Result<Option<ScorecardResult>>> {
+        let result = self.scores.get(&repo.to_string()).cloned();
+        async move { Ok(result) }.boxed_local()
+    }
+
+    fn supports(&self, _repo: &Repository) -> bool {
+        true
+    }
+}
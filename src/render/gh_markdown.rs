@@ -0,0 +1,130 @@
+use std::fmt::{self, Write};
+
+use chrono::NaiveDate;
+
+use super::markdown::{weak_checks, write_checks, write_hints, write_sections, write_table};
+use super::score_cell;
+use crate::checks;
+use crate::cli::Args;
+use crate::grade::Grade;
+use crate::policy::Violations;
+use crate::score::{CrateScore, ScoreStatus, SelfScore};
+
+/// GitHub rejects issue and pull request comments longer than this many characters.
+const COMMENT_LIMIT: usize = 65_536;
+/// Room kept free for a truncation notice.
+const NOTICE_ROOM: usize = 256;
+/// How many of the weakest crates start expanded.
+const EXPANDED: usize = 3;
+
+/// Render the markdown report for a pull request comment.
+///
+/// Crates below `--fail-under` (or below [`checks::EXPLAIN_BELOW`] without it) get a
+/// collapsible section holding their check breakdown and remediation hints, weakest
+/// first. Sections that would push the comment past GitHub's size limit are dropped
+/// with a notice.
+pub fn render(
+    scores: &[CrateScore],
+    project: Option<&SelfScore>,
+    violations: &Violations,
+    grade: Option<&Grade>,
+    args: &Args,
+) -> String {
+    let mut out = String::new();
+    write_report(&mut out, scores, project, violations, grade, args)
+        .expect("writing to a String cannot fail");
+
+    // A huge table alone can still exceed the limit
+    if out.len() > COMMENT_LIMIT {
+        let mut end = COMMENT_LIMIT - NOTICE_ROOM;
+        while !out.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.truncate(end);
+        out.push_str("\n\n_Report truncated to fit GitHub's comment size limit._\n");
+    }
+    out
+}
+
+fn write_report(
+    out: &mut String,
+    scores: &[CrateScore],
+    project: Option<&SelfScore>,
+    violations: &Violations,
+    grade: Option<&Grade>,
+    args: &Args,
+) -> fmt::Result {
+    let mut tail = String::new();
+    write_table(out, scores, project, violations, args)?;
+    write_sections(&mut tail, scores, violations, grade, true, args)?;
+
+    let policy = violations.policy;
+    let threshold = |c: &CrateScore| policy.fail_under(c).unwrap_or(checks::EXPLAIN_BELOW);
+    let mut weak: Vec<(&CrateScore, f64)> = scores
+        .iter()
+        .filter_map(|c| match c.status {
+            ScoreStatus::Scored(score) if score < threshold(c) && c.scorecard.is_some() => {
+                Some((c, score))
+            }
+            _ => None,
+        })
+        .collect();
+    weak.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    if !weak.is_empty() {
+        match policy.fail_under_build_time {
+            Some(_) => writeln!(out, "\n### Crates below their minimum score\n")?,
+            None => writeln!(
+                out,
+                "\n### Crates below {}\n",
+                args.format_score(policy.fail_under.unwrap_or(checks::EXPLAIN_BELOW))
+            )?,
+        }
+    }
+    let today = chrono::Utc::now().date_naive();
+    for (index, (crate_score, _)) in weak.iter().enumerate() {
+        let mut section = String::new();
+        write_crate_section(&mut section, crate_score, index < EXPANDED, today, args)?;
+        if out.len() + section.len() + tail.len() + NOTICE_ROOM > COMMENT_LIMIT {
+            writeln!(
+                out,
+                "_{} more crates left out to stay under GitHub's comment size limit; \
+                 see `--format markdown --detailed` for everything._",
+                weak.len() - index
+            )?;
+            break;
+        }
+        out.push_str(&section);
+    }
+
+    out.push_str(&tail);
+    Ok(())
+}
+
+/// A `<details>` block with one crate's checks and remediation hints.
+fn write_crate_section(
+    out: &mut String,
+    crate_score: &CrateScore,
+    open: bool,
+    today: NaiveDate,
+    args: &Args,
+) -> fmt::Result {
+    let Some(result) = &crate_score.scorecard else {
+        return Ok(());
+    };
+    writeln!(
+        out,
+        "<details{}>\n<summary>{} {} — score {}</summary>\n",
+        if open { " open" } else { "" },
+        crate_score.name,
+        crate_score.version,
+        score_cell(&crate_score.status, args)
+    )?;
+    write_checks(out, result, today, args)?;
+    let weak = weak_checks(result);
+    if !weak.is_empty() {
+        writeln!(out)?;
+        write_hints(out, &weak, args)?;
+    }
+    writeln!(out, "\n</details>\n")
+}
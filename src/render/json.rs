@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::coverage::Coverage;
+use crate::deps::Resolution;
+use crate::duplicates::{self, Duplicate};
+use crate::score::{CrateScore, SelfScore};
+use crate::stats::RunStats;
+
+/// Version of the JSON report layout. Bump it whenever a field is removed, renamed or
+/// changes type, or an enum gains a variant; adding an optional field is not a
+/// breaking change.
+///
+/// Version 2 added the `interrupted` and `local` statuses.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The JSON report.
+#[derive(Serialize, JsonSchema)]
+struct Report<'a> {
+    /// [`SCHEMA_VERSION`] of the tool that wrote the report.
+    schema_version: u32,
+    metadata: Metadata<'a>,
+    /// The project's own repository; absent with `--no-self` and in audit mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    self_score: Option<&'a SelfScore>,
+    crates: &'a [CrateScore],
+    /// Crates resolved at more than one version.
+    duplicates: Vec<Duplicate<'a>>,
+    /// How much of the tree has score data.
+    coverage: &'a Coverage,
+    /// Present with `--stats`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a RunStats>,
+}
+
+/// What the report reflects and when it was made.
+#[derive(Serialize, JsonSchema)]
+struct Metadata<'a> {
+    tool_version: &'static str,
+    generated_at: DateTime<Utc>,
+    resolution: &'a Resolution,
+}
+
+/// Render the report as a JSON object holding the crate rows.
+pub fn render(
+    scores: &[CrateScore],
+    project: Option<&SelfScore>,
+    coverage: &Coverage,
+    resolution: &Resolution,
+    stats: Option<&RunStats>,
+) -> String {
+    let report = Report {
+        schema_version: SCHEMA_VERSION,
+        metadata: Metadata {
+            tool_version: env!("CARGO_PKG_VERSION"),
+            generated_at: Utc::now(),
+            resolution,
+        },
+        self_score: project,
+        crates: scores,
+        duplicates: duplicates::find(scores),
+        coverage,
+        stats,
+    };
+    serde_json::to_string_pretty(&report).expect("crate rows always serialize")
+}
+
+/// JSON Schema for the output of [`render`], derived from the same structs.
+pub fn schema() -> String {
+    let mut schema = schemars::schema_for!(Report<'static>);
+    schema.insert(
+        "$id".to_string(),
+        format!("urn:cargo-scorecard:report:v{}", SCHEMA_VERSION).into(),
+    );
+    serde_json::to_string_pretty(&schema).expect("a JSON Schema always serializes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A change here changes the report layout: bump [`SCHEMA_VERSION`] unless it only
+    /// adds optional fields, then regenerate the snapshot with
+    /// `cargo run -- schema > tests/fixtures/report-schema.json`.
+    #[test]
+    fn schema_matches_snapshot() {
+        let snapshot = include_str!("../../tests/fixtures/report-schema.json");
+        assert_eq!(schema().trim_end(), snapshot.trim_end());
+    }
+}
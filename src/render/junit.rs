@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::{check_value, repository_cell, scan_date, score_cell};
+use crate::cli::Args;
+use crate::policy::Violations;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Render the report as JUnit XML, one test case per crate.
+///
+/// A crate fails when it breaks the policy (a score below `--fail-under`, a denied
+/// crate, ...) or has a reported advisory, with one `<failure>` per finding. A failed
+/// lookup is an `<error>`, and any other crate without a score is skipped.
+pub fn render(scores: &[CrateScore], violations: &Violations, args: &Args) -> String {
+    let findings = findings(scores, violations, args);
+    let key = |c: &CrateScore| (c.name.clone(), c.version.clone());
+
+    let mut failures = 0;
+    let mut errors = 0;
+    let mut skipped = 0;
+    let mut cases = String::new();
+    for crate_score in scores {
+        let name = format!("{} {}", crate_score.name, crate_score.version);
+        let _ = write!(
+            cases,
+            "    <testcase classname=\"{}\" name=\"{}\"",
+            env!("CARGO_PKG_NAME"),
+            escape(&name)
+        );
+        let found = findings.get(&key(crate_score));
+        match (&crate_score.status, found) {
+            (_, Some(found)) => {
+                failures += 1;
+                cases.push_str(">\n");
+                for (kind, message) in found {
+                    let _ = writeln!(
+                        cases,
+                        "      <failure type=\"{}\" message=\"{}\"/>",
+                        kind,
+                        escape(message)
+                    );
+                }
+                cases.push_str("    </testcase>\n");
+            }
+            (ScoreStatus::Error(e), None) => {
+                errors += 1;
+                let _ = writeln!(
+                    cases,
+                    ">\n      <error message=\"{}\"/>\n    </testcase>",
+                    escape(e)
+                );
+            }
+            (ScoreStatus::Scored(_), None) => cases.push_str("/>\n"),
+            (status, None) => {
+                skipped += 1;
+                let _ = writeln!(
+                    cases,
+                    ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                    escape(&score_cell(status, args))
+                );
+            }
+        }
+    }
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites>\n  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" \
+         skipped=\"{}\" timestamp=\"{}\">",
+        env!("CARGO_PKG_NAME"),
+        scores.len(),
+        failures,
+        errors,
+        skipped,
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    let _ = writeln!(
+        out,
+        "    <properties>\n      <property name=\"resolution\" value=\"{}\"/>\n    </properties>",
+        escape(args.resolution())
+    );
+    out.push_str(&cases);
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+/// Failure type and message for every finding, by crate name and version.
+fn findings(
+    scores: &[CrateScore],
+    violations: &Violations,
+    args: &Args,
+) -> HashMap<(String, String), Vec<(&'static str, String)>> {
+    let mut findings: HashMap<(String, String), Vec<(&'static str, String)>> = HashMap::new();
+    let mut add = |c: &CrateScore, kind: &'static str, message: String| {
+        findings
+            .entry((c.name.clone(), c.version.clone()))
+            .or_default()
+            .push((kind, message));
+    };
+
+    for c in &violations.below_threshold {
+        let threshold = violations.policy.fail_under(c).unwrap_or(0.0);
+        add(
+            c,
+            "low-score",
+            format!(
+                "scores {}, below the minimum of {} ({})",
+                score_cell(&c.status, args),
+                args.format_score(threshold),
+                repository_cell(c)
+            ),
+        );
+    }
+    // Advisories are findings whether or not --fail-on-advisory makes them a policy failure
+    for c in scores {
+        for advisory in c.reported_advisories(violations.policy.advisories_since) {
+            add(
+                c,
+                "advisory",
+                format!(
+                    "affected by {}: {}",
+                    advisory.id,
+                    advisory.summary.as_deref().unwrap_or("no summary")
+                ),
+            );
+        }
+    }
+    for denied in &violations.denied {
+        add(
+            denied.crate_score,
+            "denied-crate",
+            format!(
+                "denied by `{}`; pulled in by {}",
+                denied.rule,
+                denied.path.join(" -> ")
+            ),
+        );
+    }
+    let now = chrono::Utc::now();
+    for c in &violations.too_new {
+        add(
+            c,
+            "recent-version",
+            format!(
+                "published {} days ago, less than the minimum of {}",
+                c.version_age_days(now).unwrap_or(0),
+                violations.policy.min_version_age(c).unwrap_or(0)
+            ),
+        );
+    }
+    for c in &violations.regressions {
+        let previous = c.baseline.as_ref().and_then(|b| b.score);
+        add(
+            c,
+            "score-regression",
+            format!(
+                "scores {}, down from {} in the baseline",
+                score_cell(&c.status, args),
+                previous.map_or_else(|| "-".to_string(), |score| args.format_score(score))
+            ),
+        );
+    }
+    for duplicate in &violations.duplicates {
+        let versions: Vec<&str> = duplicate.versions.iter().map(|v| v.version).collect();
+        for version in &duplicate.versions {
+            add(
+                version.crate_score,
+                "duplicate-crate",
+                format!("resolved at several versions: {}", versions.join(", ")),
+            );
+        }
+    }
+    for c in &violations.dead_repo {
+        add(
+            c,
+            "dead-repository",
+            format!("repository is unreachable: {}", repository_cell(c)),
+        );
+    }
+    for c in &violations.unverified {
+        add(
+            c,
+            "unverified-repository",
+            format!("{} has no Cargo.toml for it", repository_cell(c)),
+        );
+    }
+    for failure in &violations.check_failures {
+        add(
+            failure.crate_score,
+            "check-requirement",
+            format!(
+                "{} (needs {}): {}",
+                check_value(failure),
+                failure.requirement,
+                failure.documentation
+            ),
+        );
+    }
+    for c in &violations.stale {
+        add(
+            c,
+            "stale-scan",
+            format!(
+                "last scanned {}; the maximum age is {} days",
+                scan_date(c),
+                violations.policy.max_scan_age.unwrap_or(0)
+            ),
+        );
+    }
+    for c in &violations.no_repository {
+        add(c, "no-repository", "no repository metadata".to_string());
+    }
+    for c in &violations.unscored {
+        add(
+            c,
+            "unscored",
+            format!(
+                "no score ({}); {} in the tree have none, more than --max-unscored {}",
+                score_cell(&c.status, args),
+                violations.unscored.len(),
+                violations.policy.max_unscored.unwrap_or(0)
+            ),
+        );
+    }
+    for c in &violations.unknown {
+        add(
+            c,
+            "unknown-score",
+            format!(
+                "no score data ({}); {:.1}% of the tree has none, more than --max-unknown",
+                c.status.category(),
+                violations.coverage.unknown_percent()
+            ),
+        );
+    }
+    findings
+}
+
+/// Escape text for an XML attribute value.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,711 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+use chrono::NaiveDate;
+
+use super::{
+    Columns, check_value, interrupted_line, repository_cell, scan_date, score_cell, self_line,
+};
+use crate::checks;
+use crate::cli::Args;
+use crate::config;
+use crate::coverage;
+use crate::deps::DepKind;
+use crate::diff::Change;
+use crate::duplicates;
+use crate::grade::Grade;
+use crate::mismatch;
+use crate::osv::Advisory;
+use crate::policy::Violations;
+use crate::score::{CrateScore, ScoreStatus, SelfScore};
+use crate::scorecard::{self, Check, ScorecardResult};
+
+/// Render the report as GitHub-flavored markdown.
+pub fn render(
+    scores: &[CrateScore],
+    project: Option<&SelfScore>,
+    violations: &Violations,
+    grade: Option<&Grade>,
+    args: &Args,
+) -> String {
+    let mut out = String::new();
+    write_report(&mut out, scores, project, violations, grade, args)
+        .expect("writing to a String cannot fail");
+    out
+}
+
+fn write_report(
+    out: &mut String,
+    scores: &[CrateScore],
+    project: Option<&SelfScore>,
+    violations: &Violations,
+    grade: Option<&Grade>,
+    args: &Args,
+) -> fmt::Result {
+    write_table(out, scores, project, violations, args)?;
+    write_sections(out, scores, violations, grade, false, args)
+}
+
+/// The heading, the project line and the results table.
+pub(super) fn write_table(
+    out: &mut String,
+    scores: &[CrateScore],
+    project: Option<&SelfScore>,
+    violations: &Violations,
+    args: &Args,
+) -> fmt::Result {
+    writeln!(out, "\n## Cargo Scorecard Results\n")?;
+    if let Some(line) = interrupted_line(scores) {
+        writeln!(out, "> **{}**\n", line)?;
+    }
+    if let Some(project) = project {
+        writeln!(out, "> **{}**\n", self_line(project, args))?;
+    }
+    writeln!(out, "Dependency resolution: {}\n", args.resolution())?;
+    let columns = Columns::new(scores, violations.policy, args);
+    let header = columns.header();
+    writeln!(out, "| {} |", header.join(" | "))?;
+    writeln!(out, "|{}", " --- |".repeat(header.len()))?;
+    for crate_score in scores {
+        writeln!(out, "| {} |", columns.row(crate_score, args).join(" | "))?;
+    }
+    Ok(())
+}
+
+/// Everything after the results table: details, summary and policy failures.
+///
+/// `collapsible` leaves out the per-crate breakdown, which gh-markdown folds into its
+/// own collapsible sections.
+pub(super) fn write_sections(
+    out: &mut String,
+    scores: &[CrateScore],
+    violations: &Violations,
+    grade: Option<&Grade>,
+    collapsible: bool,
+    args: &Args,
+) -> fmt::Result {
+    if args.detailed {
+        if !collapsible {
+            write_details(out, scores, args)?;
+        }
+        write_yanked(out, scores)?;
+        write_mismatches(out, scores)?;
+    }
+    if args.explain && !collapsible {
+        write_explanations(out, scores, args)?;
+    }
+
+    writeln!(out, "\n### Summary\n")?;
+    if let Some(grade) = grade {
+        writeln!(
+            out,
+            "**Overall grade: {}** (mean score {})\n",
+            grade,
+            args.format_score(grade.score)
+        )?;
+    }
+    let mut projects: Vec<&str> = scores
+        .iter()
+        .flat_map(|c| c.projects.iter().map(String::as_str))
+        .collect();
+    projects.sort_unstable();
+    projects.dedup();
+    if projects.len() > 1 {
+        writeln!(
+            out,
+            "Across {} projects: {}\n",
+            projects.len(),
+            projects.join(", ")
+        )?;
+    }
+    for (label, count) in ScoreStatus::count_by_category(scores) {
+        writeln!(out, "- {}: {}", label, count)?;
+    }
+    writeln!(out, "\nCoverage: {}", violations.coverage.line())?;
+    let build_time: Vec<&CrateScore> = scores.iter().filter(|c| c.build_time.any()).collect();
+    if !build_time.is_empty() {
+        let unscored = build_time
+            .iter()
+            .filter(|c| !matches!(c.status, ScoreStatus::Scored(_)))
+            .count();
+        writeln!(
+            out,
+            "\nRun code at build time (build scripts, proc-macros): {} ({} without a score)",
+            build_time.len(),
+            unscored
+        )?;
+    }
+    let unverified = scores
+        .iter()
+        .filter(|c| c.repo_verified == Some(false))
+        .count();
+    if unverified > 0 {
+        writeln!(
+            out,
+            "\nUnverified repositories (no Cargo.toml for the crate, score suspect): {}",
+            unverified
+        )?;
+    }
+    let duplicates = duplicates::find(scores);
+    if !duplicates.is_empty() {
+        writeln!(out, "\nResolved at more than one version:\n")?;
+        for duplicate in &duplicates {
+            let versions: Vec<String> = duplicate
+                .versions
+                .iter()
+                .map(|v| match v.required_by {
+                    [] => v.version.to_string(),
+                    required_by => format!("{} (via {})", v.version, required_by.join(", ")),
+                })
+                .collect();
+            writeln!(out, "- {}: {}", duplicate.name, versions.join("; "))?;
+        }
+    }
+
+    if !violations.too_new.is_empty() {
+        writeln!(out, "\n### Policy failures: recently published versions\n")?;
+        let now = chrono::Utc::now();
+        for crate_score in &violations.too_new {
+            let min_age = violations.policy.min_version_age(crate_score).unwrap_or(0);
+            let new_crate = if crate_score.is_new_crate(now, i64::from(min_age)) {
+                "**new crate**, "
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "- {} {}: {}published {} days ago (minimum {})",
+                crate_score.name,
+                crate_score.version,
+                new_crate,
+                crate_score.version_age_days(now).unwrap_or(0),
+                min_age
+            )?;
+        }
+    }
+
+    if args.checks_advisories() {
+        write_advisories(out, scores, violations.policy.advisories_since, args)?;
+    }
+
+    if args.baseline.is_some() {
+        write_changes(out, scores, args)?;
+    }
+
+    let unlocked: Vec<_> = scores.iter().filter(|c| c.unlocked).collect();
+    if !unlocked.is_empty() {
+        writeln!(out, "\n### Suspicious vendored crates\n")?;
+        for crate_score in unlocked {
+            writeln!(
+                out,
+                "- {} {}: not in Cargo.lock, or its checksum differs",
+                crate_score.name, crate_score.version
+            )?;
+        }
+    }
+
+    if !violations.no_repository.is_empty() {
+        writeln!(out, "\n### Policy failures: no repository metadata\n")?;
+        for crate_score in &violations.no_repository {
+            let lead = match (&crate_score.homepage, &crate_score.documentation) {
+                (Some(homepage), _) => format!(" (check the homepage: {})", homepage),
+                (None, Some(documentation)) => {
+                    format!(" (check the documentation: {})", documentation)
+                }
+                (None, None) => String::new(),
+            };
+            writeln!(
+                out,
+                "- {} {}{}",
+                crate_score.name, crate_score.version, lead
+            )?;
+        }
+    }
+
+    if !violations.below_threshold.is_empty() {
+        // A single threshold goes in the heading; with a build-time one, each crate has its own
+        let policy = violations.policy;
+        match (policy.fail_under, policy.fail_under_build_time) {
+            (Some(threshold), None) => writeln!(
+                out,
+                "\n### Policy failures: scores below {}\n",
+                args.format_score(threshold)
+            )?,
+            _ => writeln!(out, "\n### Policy failures: low scores\n")?,
+        }
+        for crate_score in &violations.below_threshold {
+            let minimum = match policy.fail_under_build_time {
+                Some(_) => format!(
+                    " (minimum {}{})",
+                    args.format_score(policy.fail_under(crate_score).unwrap_or(0.0)),
+                    if crate_score.build_time.any() {
+                        ", runs code at build time"
+                    } else {
+                        ""
+                    }
+                ),
+                None => String::new(),
+            };
+            writeln!(
+                out,
+                "- {} {}: {}{}",
+                crate_score.name,
+                crate_score.version,
+                score_cell(&crate_score.status, args),
+                minimum
+            )?;
+        }
+    }
+
+    if !violations.check_failures.is_empty() {
+        writeln!(out, "\n### Policy failures: check requirements\n")?;
+        for failure in &violations.check_failures {
+            writeln!(
+                out,
+                "- {} {}: {} (needs {}): {}",
+                failure.crate_score.name,
+                failure.crate_score.version,
+                check_value(failure),
+                failure.requirement,
+                failure.documentation
+            )?;
+        }
+    }
+
+    if !violations.stale.is_empty() {
+        writeln!(
+            out,
+            "\n### Policy failures: scans older than {} days\n",
+            violations.policy.max_scan_age.unwrap_or(0)
+        )?;
+        for crate_score in &violations.stale {
+            writeln!(
+                out,
+                "- {} {}: last scanned {}",
+                crate_score.name,
+                crate_score.version,
+                scan_date(crate_score)
+            )?;
+        }
+    }
+
+    if !violations.unknown.is_empty() {
+        writeln!(
+            out,
+            "\n### Policy failures: too many crates without a score\n\n\
+             {:.1}% have no score data, more than --max-unknown {}%\n",
+            violations.coverage.unknown_percent(),
+            violations.policy.max_unknown.unwrap_or(0.0)
+        )?;
+        for crate_score in &violations.unknown {
+            writeln!(
+                out,
+                "- {} {}: {}",
+                crate_score.name,
+                crate_score.version,
+                crate_score.status.category()
+            )?;
+        }
+    }
+
+    if !violations.unscored.is_empty() {
+        writeln!(
+            out,
+            "\n### Policy failures: unscored crates\n\n\
+             {} have no score, more than --max-unscored {}: {}\n",
+            violations.unscored.len(),
+            violations.policy.max_unscored.unwrap_or(0),
+            coverage::list_categories(&coverage::by_category(violations.unscored.iter().copied()))
+        )?;
+        for crate_score in &violations.unscored {
+            writeln!(
+                out,
+                "- {} {}: {}",
+                crate_score.name,
+                crate_score.version,
+                score_cell(&crate_score.status, args)
+            )?;
+        }
+    }
+
+    if !violations.duplicates.is_empty() {
+        writeln!(out, "\n### Policy failures: duplicate versions\n")?;
+        for duplicate in &violations.duplicates {
+            let versions: Vec<&str> = duplicate.versions.iter().map(|v| v.version).collect();
+            writeln!(out, "- {}: {}", duplicate.name, versions.join(", "))?;
+        }
+    }
+
+    if !violations.denied.is_empty() {
+        writeln!(out, "\n### Policy failures: denied crates\n")?;
+        for denied in &violations.denied {
+            writeln!(
+                out,
+                "- {} {} (denied by `{}`), pulled in by: {}",
+                denied.crate_score.name,
+                denied.crate_score.version,
+                denied.rule,
+                denied.path.join(" -> ")
+            )?;
+        }
+    }
+
+    if !violations.regressions.is_empty() {
+        writeln!(out, "\n### Policy failures: score regressions\n")?;
+        for crate_score in &violations.regressions {
+            writeln!(out, "- {}", change_line(crate_score, args))?;
+        }
+    }
+
+    if !violations.advisories.is_empty() {
+        writeln!(out, "\n### Policy failures: advisories\n")?;
+        for crate_score in &violations.advisories {
+            let ids: Vec<&str> = crate_score
+                .reported_advisories(violations.policy.advisories_since)
+                .iter()
+                .map(|advisory| advisory.id.as_str())
+                .collect();
+            writeln!(
+                out,
+                "- {} {}: {}",
+                crate_score.name,
+                crate_score.version,
+                ids.join(", ")
+            )?;
+        }
+    }
+
+    if !violations.dead_repo.is_empty() {
+        writeln!(out, "\n### Policy failures: unreachable repositories\n")?;
+        for crate_score in &violations.dead_repo {
+            writeln!(
+                out,
+                "- {} {}: {}",
+                crate_score.name,
+                crate_score.version,
+                repository_cell(crate_score)
+            )?;
+        }
+    }
+
+    if !violations.unverified.is_empty() {
+        writeln!(
+            out,
+            "\n### Policy failures: repositories without the crate\n"
+        )?;
+        for crate_score in &violations.unverified {
+            writeln!(
+                out,
+                "- {} {}: no Cargo.toml for it in {}",
+                crate_score.name,
+                crate_score.version,
+                repository_cell(crate_score)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Advisories affecting the versions in use. Those published before `--advisories-since`
+/// or waived are only listed with `--detailed`.
+fn write_advisories(
+    out: &mut String,
+    scores: &[CrateScore],
+    since: Option<NaiveDate>,
+    args: &Args,
+) -> fmt::Result {
+    let listed: Vec<(&CrateScore, &Advisory)> = scores
+        .iter()
+        .flat_map(|c| c.advisories.iter().flatten().map(move |a| (c, a)))
+        .filter(|(_, advisory)| {
+            args.detailed || (advisory.waiver.is_none() && advisory.is_since(since))
+        })
+        .collect();
+    if listed.is_empty() {
+        return Ok(());
+    }
+
+    match since {
+        Some(since) => writeln!(out, "\n### Advisories published since {}\n", since)?,
+        None => writeln!(out, "\n### Advisories\n")?,
+    }
+    for (crate_score, advisory) in listed {
+        let published = advisory.published.map_or_else(
+            || "date unknown".to_string(),
+            |p| p.date_naive().to_string(),
+        );
+        let mut notes = match &advisory.waiver {
+            Some(waiver) => format!(", waived in {}", waiver),
+            None => String::new(),
+        };
+        if !advisory.is_since(since) {
+            notes.push_str(", before the cutoff");
+        }
+        writeln!(
+            out,
+            "- {} {}: **{}** {} ({}{})",
+            crate_score.name,
+            crate_score.version,
+            advisory.id,
+            advisory.summary.as_deref().unwrap_or_default(),
+            published,
+            notes
+        )?;
+    }
+    Ok(())
+}
+
+/// Crates that are new or whose version or score moved since `--baseline`, grouped by
+/// the kind of dependency that pulls them in.
+fn write_changes(out: &mut String, scores: &[CrateScore], args: &Args) -> fmt::Result {
+    let changed = scores
+        .iter()
+        .filter(|c| match (&c.baseline, Change::of(c)) {
+            (Some(baseline), Change::Scored(delta)) => {
+                delta != 0.0 || baseline.version != c.version
+            }
+            (Some(baseline), Change::Unscored) => {
+                baseline.version != c.version
+                    || baseline.score.is_some() != matches!(c.status, ScoreStatus::Scored(_))
+            }
+            (_, _) => true,
+        });
+    let mut by_kind: BTreeMap<Option<DepKind>, Vec<&CrateScore>> = BTreeMap::new();
+    for crate_score in changed {
+        by_kind
+            .entry(crate_score.kind)
+            .or_default()
+            .push(crate_score);
+    }
+
+    writeln!(out, "\n### Changes since baseline")?;
+    if by_kind.is_empty() {
+        writeln!(out, "\nNo changes.")?;
+    }
+    // `None` sorts first but reads best last
+    let unknown = by_kind.remove(&None);
+    for (kind, crates) in by_kind.into_iter().chain(unknown.map(|c| (None, c))) {
+        match kind {
+            Some(kind) => writeln!(out, "\n#### {} dependencies\n", kind)?,
+            None => writeln!(out, "\n#### Dependencies of unknown kind\n")?,
+        }
+        for crate_score in crates {
+            writeln!(out, "- {}", change_line(crate_score, args))?;
+        }
+    }
+    Ok(())
+}
+
+/// `name old → new: 7.2 → 5.1 (-2.1)`, or `name version: 5.1 (new)`.
+fn change_line(crate_score: &CrateScore, args: &Args) -> String {
+    let current = score_cell(&crate_score.status, args);
+    let Some(baseline) = &crate_score.baseline else {
+        return format!(
+            "{} {}: {} (new)",
+            crate_score.name, crate_score.version, current
+        );
+    };
+    let version = if baseline.version == crate_score.version {
+        crate_score.version.clone()
+    } else {
+        format!("{} → {}", baseline.version, crate_score.version)
+    };
+    let previous = baseline
+        .score
+        .map_or_else(|| "unscored".to_string(), |score| args.format_score(score));
+    let delta = match Change::of(crate_score) {
+        Change::Scored(delta) if delta > 0.0 => format!(" (+{})", args.format_score(delta)),
+        Change::Scored(delta) if delta < 0.0 => format!(" (-{})", args.format_score(-delta)),
+        _ => String::new(),
+    };
+    format!(
+        "{} {}: {} → {}{}",
+        crate_score.name, version, previous, current, delta
+    )
+}
+
+/// Per-crate scorecard breakdown for `--detailed`.
+fn write_details(out: &mut String, scores: &[CrateScore], args: &Args) -> fmt::Result {
+    let today = chrono::Utc::now().date_naive();
+
+    writeln!(out, "\n### Details")?;
+    for crate_score in scores {
+        let Some(result) = &crate_score.scorecard else {
+            continue;
+        };
+        writeln!(
+            out,
+            "\n#### {} {} — score {} (confidence: {})\n",
+            crate_score.name,
+            crate_score.version,
+            score_cell(&crate_score.status, args),
+            scorecard::confidence(result, today)
+        )?;
+        write_checks(out, result, today, args)?;
+    }
+
+    Ok(())
+}
+
+/// Scan date and the table of every check in one scorecard result.
+pub(super) fn write_checks(
+    out: &mut String,
+    result: &ScorecardResult,
+    today: NaiveDate,
+    args: &Args,
+) -> fmt::Result {
+    writeln!(
+        out,
+        "Scanned {}, {} of {} checks scored.\n",
+        scan_age(result.date(), today),
+        result.scored_checks().count(),
+        result.checks.len()
+    )?;
+    writeln!(out, "| Check | Score | Reason |")?;
+    writeln!(out, "| --- | --- | --- |")?;
+    for check in &result.checks {
+        let score = match check.score {
+            Some(score) if score >= 0.0 => args.format_score(score),
+            _ => "No data".to_string(),
+        };
+        writeln!(
+            out,
+            "| {} | {} | {} |",
+            check.name,
+            score,
+            check.reason.as_deref().unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
+
+/// Yanked versions with whatever context crates.io has, for `--detailed`.
+fn write_yanked(out: &mut String, scores: &[CrateScore]) -> fmt::Result {
+    let yanked: Vec<_> = scores
+        .iter()
+        .filter_map(|c| Some((c, c.yanked.as_ref()?)))
+        .collect();
+    if yanked.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "\n### Yanked versions\n")?;
+    for (crate_score, yank) in yanked {
+        let reason = yank.reason.as_deref().unwrap_or("no reason given");
+        let date = match &yank.updated_at {
+            Some(updated_at) => format!(
+                " (last updated {})",
+                updated_at.get(..10).unwrap_or(updated_at)
+            ),
+            None => String::new(),
+        };
+        writeln!(
+            out,
+            "- {} {}: {}{}",
+            crate_score.name, crate_score.version, reason, date
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Crates whose repository seems to be another crate's, for `--detailed`.
+fn write_mismatches(out: &mut String, scores: &[CrateScore]) -> fmt::Result {
+    let flagged: Vec<&CrateScore> = scores.iter().filter(|c| c.repo_mismatch).collect();
+    if flagged.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "\n### Repositories to check by hand\n")?;
+    writeln!(
+        out,
+        "These crates' names don't appear in their repository, which other crates here \
+         match; the score may belong to another project. Allow a crate checked as fine \
+         with `allow_repo_mismatch` in {}.\n",
+        config::CONFIG_FILE
+    )?;
+    for crate_score in flagged {
+        writeln!(
+            out,
+            "- {} {}: {}, which matches {}",
+            crate_score.name,
+            crate_score.version,
+            crate_score.repository.as_deref().unwrap_or_default(),
+            mismatch::owners(scores, crate_score).join(", ")
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Collapsible remediation hints for `--explain`, one section per crate.
+fn write_explanations(out: &mut String, scores: &[CrateScore], args: &Args) -> fmt::Result {
+    let mut heading = false;
+    for crate_score in scores {
+        let Some(result) = &crate_score.scorecard else {
+            continue;
+        };
+        let weak = weak_checks(result);
+        if weak.is_empty() {
+            continue;
+        }
+
+        if !heading {
+            writeln!(out, "\n### How to improve")?;
+            heading = true;
+        }
+        writeln!(
+            out,
+            "\n<details>\n<summary>{} {}: {} checks below {}</summary>\n",
+            crate_score.name,
+            crate_score.version,
+            weak.len(),
+            args.format_score(checks::EXPLAIN_BELOW)
+        )?;
+        write_hints(out, &weak, args)?;
+        writeln!(out, "\n</details>")?;
+    }
+
+    Ok(())
+}
+
+/// Checks scoring below [`checks::EXPLAIN_BELOW`].
+pub(super) fn weak_checks(result: &ScorecardResult) -> Vec<&Check> {
+    result
+        .scored_checks()
+        .filter(|check| {
+            check
+                .score
+                .is_some_and(|score| score < checks::EXPLAIN_BELOW)
+        })
+        .collect()
+}
+
+/// What each weak check measures and how to fix it, with a link to its docs.
+pub(super) fn write_hints(out: &mut String, weak: &[&Check], args: &Args) -> fmt::Result {
+    for check in weak {
+        let url = check
+            .documentation
+            .as_ref()
+            .and_then(|documentation| documentation.url.clone())
+            .unwrap_or_else(|| checks::documentation_url(&check.name));
+        let score = args.format_score(check.score.unwrap_or_default());
+        match checks::hint(&check.name) {
+            Some(hint) => writeln!(
+                out,
+                "- **{}** ({}): measures {}. Fix: {}. [Docs]({})",
+                check.name, score, hint.measures, hint.remediation, url
+            )?,
+            None => writeln!(out, "- **{}** ({}): [Docs]({})", check.name, score, url)?,
+        }
+    }
+    Ok(())
+}
+
+fn scan_age(date: Option<NaiveDate>, today: NaiveDate) -> String {
+    match date {
+        Some(date) => format!("{} ({} days ago)", date, (today - date).num_days()),
+        None => "on an unknown date".to_string(),
+    }
+}
@@ -0,0 +1,351 @@
+pub mod gh_markdown;
+pub mod json;
+pub mod junit;
+pub mod markdown;
+pub mod prometheus;
+pub mod sarif;
+pub mod summary;
+pub mod table;
+pub mod template;
+
+use chrono::{NaiveDate, Utc};
+
+use crate::cli::{Args, Column, Command, OutputFormat, SortOrder};
+use crate::deps::{CrateSource, Resolution};
+use crate::grade::Grade;
+use crate::policy::{CheckFailure, Policy, Violations};
+use crate::score::{CrateScore, ScoreStatus, SelfScore};
+use crate::stats::RunStats;
+
+/// The run's results, shared by every report rendered from them.
+pub struct Results<'a> {
+    pub scores: &'a [CrateScore],
+    pub project: Option<&'a SelfScore>,
+    pub violations: &'a Violations<'a>,
+    pub grade: Option<&'a Grade>,
+    pub resolution: &'a Resolution,
+    pub stats: Option<&'a RunStats>,
+}
+
+/// Render one report, ending in a newline.
+pub fn render(format: OutputFormat, results: &Results, args: &Args) -> String {
+    let Results {
+        scores,
+        project,
+        violations,
+        grade,
+        resolution,
+        stats,
+    } = *results;
+    match format {
+        OutputFormat::Table => table::render(scores, project, violations, grade, args),
+        OutputFormat::Markdown => markdown::render(scores, project, violations, grade, args),
+        OutputFormat::GhMarkdown => gh_markdown::render(scores, project, violations, grade, args),
+        OutputFormat::Json => {
+            json::render(scores, project, &violations.coverage, resolution, stats) + "\n"
+        }
+        OutputFormat::Count => format!("{}\n", violations.failing_crates()),
+        OutputFormat::Sarif => sarif::render(violations, grade, args) + "\n",
+        OutputFormat::Junit => junit::render(scores, violations, args),
+        OutputFormat::Prometheus => prometheus::render(scores, violations),
+    }
+}
+
+/// Text for the repository column.
+fn repository_cell(crate_score: &CrateScore) -> String {
+    match (
+        &crate_score.repository,
+        &crate_score.repository_inferred_from,
+    ) {
+        (Some(repo), Some(source)) => format!("{} (repo inferred from {})", repo, source),
+        (Some(repo), None) => repo.clone(),
+        (None, _) => "No repository information".to_string(),
+    }
+}
+
+/// Text for the version column, flagging a `--pin` version as hypothetical.
+fn version_cell(crate_score: &CrateScore) -> String {
+    match &crate_score.pinned_from {
+        Some(resolved) => format!(
+            "{} (pinned, hypothetical; resolved {})",
+            crate_score.version, resolved
+        ),
+        None => crate_score.version.clone(),
+    }
+}
+
+/// The notice on a report cut short by Ctrl-C, e.g. `Interrupted — partial (212/403 crates)`.
+fn interrupted_line(scores: &[CrateScore]) -> Option<String> {
+    let skipped = scores
+        .iter()
+        .filter(|c| matches!(c.status, ScoreStatus::Interrupted))
+        .count();
+    (skipped > 0).then(|| {
+        format!(
+            "Interrupted — partial ({}/{} crates)",
+            scores.len() - skipped,
+            scores.len()
+        )
+    })
+}
+
+/// Text for the score column.
+pub fn score_cell(status: &ScoreStatus, args: &Args) -> String {
+    match status {
+        ScoreStatus::Scored(score) => args.format_score(*score),
+        ScoreStatus::UnsupportedForge(host) => format!("Unsupported forge ({})", host),
+        ScoreStatus::Unreachable(code) => format!("Repository not found ({})", code),
+        ScoreStatus::NotScanned => "Not scanned".to_string(),
+        ScoreStatus::NoRepository => "Not available".to_string(),
+        ScoreStatus::Pending => "Pending (scan in progress)".to_string(),
+        ScoreStatus::Error(_) => "Error".to_string(),
+        ScoreStatus::Uncached => "Not available (offline, uncached)".to_string(),
+        ScoreStatus::SkippedDeadline => "Skipped (deadline)".to_string(),
+        ScoreStatus::Interrupted => "Skipped (interrupted)".to_string(),
+        ScoreStatus::Local => "Skipped (path dependency)".to_string(),
+    }
+}
+
+/// The "this project" line above the results table.
+fn self_line(project: &SelfScore, args: &Args) -> String {
+    let Some(repository) = &project.repository else {
+        return "This project: no `repository` in Cargo.toml and no `origin` remote".to_string();
+    };
+    match (&project.status, &project.repo) {
+        (ScoreStatus::NotScanned, Some(repo)) if repo.is_scorecard_supported() => format!(
+            "This project ({}): never scanned; check https://scorecard.dev/viewer/?uri={} \
+             and run https://github.com/ossf/scorecard-action to publish a score",
+            repository, repo
+        ),
+        (status, _) => format!(
+            "This project ({}): {}",
+            repository,
+            score_cell(status, args)
+        ),
+    }
+}
+
+/// The columns of the results table, shared by the table-shaped formats.
+struct Columns {
+    columns: Vec<Column>,
+    /// `--advisories-since`, for the advisories column.
+    advisories_since: Option<NaiveDate>,
+}
+
+impl Columns {
+    /// The `--columns` given, or the default set for the data the run has.
+    fn new(scores: &[CrateScore], policy: &Policy, args: &Args) -> Self {
+        let columns = if args.columns.is_empty() {
+            default_columns(scores, policy, args)
+        } else {
+            args.columns.clone()
+        };
+        Columns {
+            columns,
+            advisories_since: policy.advisories_since,
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Column> + '_ {
+        self.columns.iter().copied()
+    }
+
+    fn header(&self) -> Vec<&'static str> {
+        self.columns.iter().map(|column| column.title()).collect()
+    }
+
+    fn row(&self, crate_score: &CrateScore, args: &Args) -> Vec<String> {
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        self.columns
+            .iter()
+            .map(|column| match column {
+                Column::Name => crate_score.name.clone(),
+                Column::Version => version_cell(crate_score),
+                Column::Repository => repository_cell(crate_score),
+                Column::Score => match crate_score.repo_verified {
+                    Some(false) => format!(
+                        "{} (unverified repository)",
+                        score_cell(&crate_score.status, args)
+                    ),
+                    _ => score_cell(&crate_score.status, args),
+                },
+                Column::CustomScore => or_dash(
+                    crate_score
+                        .custom_score
+                        .map(|score| args.format_score(score)),
+                ),
+                Column::Status => crate_score.category().to_string(),
+                Column::ChecksFailed => or_dash(crate_score.scorecard.as_ref().map(|result| {
+                    let failed: Vec<&str> = result
+                        .checks
+                        .iter()
+                        .filter(|check| check.score == Some(0.0))
+                        .map(|check| check.name.as_str())
+                        .collect();
+                    failed.join(", ")
+                })),
+                Column::Project => crate_score.projects.join(", "),
+                Column::Depth => crate_score
+                    .depth
+                    .map_or(String::new(), |depth| depth.to_string()),
+                Column::IntroducedBy => crate_score.introduced_by.clone().unwrap_or_default(),
+                Column::Exemption => exemption_cell(crate_score),
+                Column::BuildTime => crate_score.build_time.to_string(),
+                Column::UniqueDeps => {
+                    or_dash(crate_score.unique_dependencies.map(|n| n.to_string()))
+                }
+                Column::Reach => or_dash(crate_score.reach.map(|reach| reach.to_string())),
+                Column::Risk => or_dash(crate_score.risk.map(|risk| format!("{:.1}", risk))),
+                Column::Archived => or_dash(
+                    crate_score
+                        .repo_metadata
+                        .as_ref()
+                        .map(|m| if m.archived { "yes" } else { "no" }.to_string()),
+                ),
+                Column::Stars => or_dash(
+                    crate_score
+                        .repo_metadata
+                        .as_ref()
+                        .map(|m| m.stars.to_string()),
+                ),
+                Column::LastPush => or_dash(
+                    crate_score
+                        .repo_metadata
+                        .as_ref()
+                        .and_then(|m| m.last_push)
+                        .map(|date| date.date_naive().to_string()),
+                ),
+                Column::Published => published_cell(crate_score),
+                Column::Advisories => or_dash(crate_score.advisories.as_ref().map(|_| {
+                    crate_score
+                        .reported_advisories(self.advisories_since)
+                        .len()
+                        .to_string()
+                })),
+                Column::Change => or_dash(crate_score.changed.as_ref().map(ToString::to_string)),
+                Column::Source => or_dash(crate_score.source.as_ref().map(ToString::to_string)),
+            })
+            .collect()
+    }
+}
+
+/// The columns shown without `--columns`: the basics, plus whatever the run collected.
+fn default_columns(scores: &[CrateScore], policy: &Policy, args: &Args) -> Vec<Column> {
+    let mut columns = vec![
+        Column::Name,
+        Column::Version,
+        Column::Repository,
+        Column::Score,
+    ];
+    if !args.check_weights.is_empty() {
+        columns.push(Column::CustomScore);
+    }
+    if scores.iter().any(|c| !c.projects.is_empty()) {
+        columns.push(Column::Project);
+    }
+    if scores.iter().any(|c| c.depth.is_some()) {
+        columns.extend([Column::Depth, Column::IntroducedBy]);
+    }
+    if scores.iter().any(|c| c.trusted || c.waiver.is_some()) {
+        columns.push(Column::Exemption);
+    }
+    if scores.iter().any(|c| c.build_time.any()) {
+        columns.push(Column::BuildTime);
+    }
+    if args.direct_only {
+        columns.push(Column::UniqueDeps);
+    }
+    if args.sort == Some(SortOrder::Risk) {
+        columns.extend([Column::Reach, Column::Risk]);
+    }
+    if args.github_enrich {
+        columns.extend([Column::Archived, Column::Stars, Column::LastPush]);
+    }
+    if policy.checks_version_age() {
+        columns.push(Column::Published);
+    }
+    if scores.iter().any(|c| c.advisories.is_some()) {
+        columns.push(Column::Advisories);
+    }
+    if args.changed_since.is_some() {
+        columns.push(Column::Change);
+    }
+    if scores
+        .iter()
+        .any(|c| !matches!(c.source, None | Some(CrateSource::Registry)))
+    {
+        columns.push(Column::Source);
+    }
+    columns
+}
+
+/// Reject `--columns` that need data the run won't collect, rather than show blanks.
+pub fn validate_columns(args: &Args) -> anyhow::Result<()> {
+    for column in &args.columns {
+        let needs = match column {
+            Column::CustomScore if args.check_weights.is_empty() => Some("--check-weights"),
+            Column::Depth | Column::IntroducedBy
+                if !matches!(args.command, Some(Command::Audit { .. })) =>
+            {
+                Some("`cargo scorecard audit`")
+            }
+            Column::Archived | Column::Stars | Column::LastPush if !args.github_enrich => {
+                Some("--github-enrich")
+            }
+            Column::Advisories if !args.checks_advisories() => Some("--advisories"),
+            Column::Change if args.changed_since.is_none() => Some("--changed-since"),
+            _ => None,
+        };
+        if let Some(needs) = needs {
+            return Err(anyhow::anyhow!(
+                "--columns {} needs {}",
+                column.name(),
+                needs
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Why a crate is exempt from policy, if it is.
+fn exemption_cell(crate_score: &CrateScore) -> String {
+    match &crate_score.waiver {
+        _ if crate_score.trusted => "Trusted".to_string(),
+        Some(source) => format!("Waived ({})", source),
+        None => String::new(),
+    }
+}
+
+/// How long ago the version in use was published.
+fn published_cell(crate_score: &CrateScore) -> String {
+    match crate_score.version_age_days(Utc::now()) {
+        Some(0) => "today".to_string(),
+        Some(1) => "1 day ago".to_string(),
+        Some(days) => format!("{} days ago", days),
+        None => "-".to_string(),
+    }
+}
+
+/// A required check's value, e.g. `Code-Review 3`, or `Fuzzing has no data`.
+fn check_value(failure: &CheckFailure) -> String {
+    match failure.score {
+        Some(score) => format!("{} {}", failure.requirement.name, score),
+        None => format!("{} has no data", failure.requirement.name),
+    }
+}
+
+/// When the crate's repository was last scanned, e.g. `2024-05-06 (200 days ago)`.
+fn scan_date(crate_score: &CrateScore) -> String {
+    let date = crate_score
+        .scorecard
+        .as_ref()
+        .and_then(|result| result.date());
+    match date {
+        Some(date) => format!(
+            "{} ({} days ago)",
+            date,
+            (Utc::now().date_naive() - date).num_days()
+        ),
+        None => "on an unknown date".to_string(),
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::grade;
+use crate::policy::Violations;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Render the results in the Prometheus text exposition format, for a Pushgateway
+/// or a node exporter's textfile collector.
+///
+/// Only scored crates get a per-crate series, labelled by name and version alone, so
+/// cardinality stays at one series per crate version in the tree.
+pub fn render(scores: &[CrateScore], violations: &Violations) -> String {
+    let mut out = String::new();
+
+    metric(
+        &mut out,
+        "scorecard_crate_score",
+        "OpenSSF Scorecard score of a dependency, 0-10.",
+    );
+    let mut seen = BTreeSet::new();
+    for crate_score in scores {
+        let ScoreStatus::Scored(score) = crate_score.status else {
+            continue;
+        };
+        if !seen.insert((&crate_score.name, &crate_score.version)) {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "scorecard_crate_score{{crate=\"{}\",version=\"{}\"}} {}",
+            escape(&crate_score.name),
+            escape(crate_score.version.trim_start_matches('v')),
+            score
+        );
+    }
+
+    metric(
+        &mut out,
+        "scorecard_crates",
+        "Dependencies in the tree, by status.",
+    );
+    for (category, count) in ScoreStatus::count_by_category(scores) {
+        let _ = writeln!(
+            out,
+            "scorecard_crates{{status=\"{}\"}} {}",
+            escape(category),
+            count
+        );
+    }
+
+    if let Some(average) = grade::aggregate(scores) {
+        metric(
+            &mut out,
+            "scorecard_average_score",
+            "Mean score of the scored dependencies.",
+        );
+        let _ = writeln!(out, "scorecard_average_score {}", average);
+    }
+
+    if let Some(threshold) = violations.policy.fail_under {
+        metric(
+            &mut out,
+            "scorecard_fail_under",
+            "The --fail-under threshold in force.",
+        );
+        let _ = writeln!(out, "scorecard_fail_under {}", threshold);
+        metric(
+            &mut out,
+            "scorecard_crates_below_threshold",
+            "Dependencies scoring below their minimum, trusted and waived ones aside.",
+        );
+        let _ = writeln!(
+            out,
+            "scorecard_crates_below_threshold {}",
+            violations.below_threshold.len()
+        );
+    }
+
+    metric(
+        &mut out,
+        "scorecard_policy_failing_crates",
+        "Dependencies breaking at least one policy rule.",
+    );
+    let _ = writeln!(
+        out,
+        "scorecard_policy_failing_crates {}",
+        violations.failing_crates()
+    );
+
+    metric(
+        &mut out,
+        "scorecard_coverage_percent",
+        "Share of the dependencies with score data, 0-100.",
+    );
+    let _ = writeln!(
+        out,
+        "scorecard_coverage_percent {}",
+        violations.coverage.percent
+    );
+
+    out
+}
+
+/// The `# HELP` and `# TYPE` lines introducing a gauge.
+fn metric(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+/// Escape a label value: backslash, double quote and newline.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
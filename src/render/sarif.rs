@@ -0,0 +1,315 @@
+use serde_json::{Value, json};
+
+use super::{check_value, repository_cell, scan_date, score_cell};
+use crate::cli::Args;
+use crate::coverage;
+use crate::grade::Grade;
+use crate::policy::Violations;
+use crate::score::CrateScore;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Rules a result can refer to: id, short description, full description.
+const RULES: &[(&str, &str, &str)] = &[
+    (
+        "low-score",
+        "Dependency scores below the minimum",
+        "The dependency's OpenSSF Scorecard score is below the --fail-under threshold.",
+    ),
+    (
+        "denied-crate",
+        "Denied dependency",
+        "The dependency is on the deny list and must be removed from the tree.",
+    ),
+    (
+        "recent-version",
+        "Dependency version was published very recently",
+        "The version in use is younger than --min-version-age; new crates and fresh \
+         versions are where typosquatting and account takeovers show up first.",
+    ),
+    (
+        "advisory",
+        "Dependency has a published advisory",
+        "OSV.dev lists an advisory affecting the version in use.",
+    ),
+    (
+        "score-regression",
+        "Dependency score dropped since the baseline",
+        "The dependency's OpenSSF Scorecard score is lower than in the --baseline report.",
+    ),
+    (
+        "duplicate-crate",
+        "Dependency resolved at several versions",
+        "The crate appears in the tree at more than one version, but --fail-on-duplicates \
+         requires it to converge on one.",
+    ),
+    (
+        "unverified-repository",
+        "Dependency repository does not contain the crate",
+        "The repository linked from crates.io has no Cargo.toml for a package of this \
+         name, so its score may belong to another project.",
+    ),
+    (
+        "unknown-score",
+        "Dependency has no score data",
+        "More of the tree than --max-unknown allows has no OpenSSF Scorecard data, and \
+         this dependency is part of it.",
+    ),
+    (
+        "unscored",
+        "Dependency has no score",
+        "More crates than --max-unscored allows ended up without an OpenSSF Scorecard \
+         score, and this dependency is one of them.",
+    ),
+    (
+        "dead-repository",
+        "Dependency repository is unreachable",
+        "The repository linked from crates.io no longer exists or is private.",
+    ),
+    (
+        "check-requirement",
+        "Dependency falls short of a required check",
+        "An OpenSSF Scorecard check scores below its --require-check minimum for this \
+         dependency, or has no data.",
+    ),
+    (
+        "stale-scan",
+        "Dependency scorecard scan is out of date",
+        "The dependency's OpenSSF Scorecard scan is older than --max-scan-age, or \
+         undated, so its score may no longer reflect the project.",
+    ),
+    (
+        "no-repository",
+        "Dependency has no repository",
+        "The crate does not link a source repository on crates.io.",
+    ),
+];
+
+/// Render policy violations as a SARIF 2.1.0 log for GitHub code scanning.
+pub fn render(violations: &Violations, grade: Option<&Grade>, args: &Args) -> String {
+    let lockfile = args.lockfile_path();
+    let lock_content = std::fs::read_to_string(&lockfile).ok();
+    let uri = lockfile.to_string_lossy().replace('\\', "/");
+
+    let result = |rule_id: &str, crate_score: &CrateScore, message: String| {
+        let mut location = json!({ "artifactLocation": { "uri": uri } });
+        if let Some(line) = lock_content
+            .as_deref()
+            .and_then(|content| lockfile_line(content, crate_score))
+        {
+            location["region"] = json!({ "startLine": line });
+        }
+        json!({
+            "ruleId": rule_id,
+            "level": "error",
+            "message": { "text": message },
+            "locations": [{ "physicalLocation": location }],
+            "partialFingerprints": {
+                "crate": format!("{}@{}", crate_score.name, crate_score.version),
+            },
+        })
+    };
+
+    let mut results = Vec::new();
+    for crate_score in &violations.below_threshold {
+        let threshold = violations.policy.fail_under(crate_score).unwrap_or(0.0);
+        let build_time = if crate_score.build_time.any() {
+            format!("; it runs code at build time ({})", crate_score.build_time)
+        } else {
+            String::new()
+        };
+        let message = format!(
+            "{} {} has an OpenSSF Scorecard score of {}, below the minimum of {} ({}){}",
+            crate_score.name,
+            crate_score.version,
+            score_cell(&crate_score.status, args),
+            args.format_score(threshold),
+            repository_cell(crate_score),
+            build_time
+        );
+        results.push(result("low-score", crate_score, message));
+    }
+    for denied in &violations.denied {
+        let message = format!(
+            "{} {} is denied by `{}`; it is pulled in by {}",
+            denied.crate_score.name,
+            denied.crate_score.version,
+            denied.rule,
+            denied.path.join(" -> ")
+        );
+        results.push(result("denied-crate", denied.crate_score, message));
+    }
+    let now = chrono::Utc::now();
+    for crate_score in &violations.too_new {
+        let message = format!(
+            "{} {} was published {} days ago, less than the minimum of {}",
+            crate_score.name,
+            crate_score.version,
+            crate_score.version_age_days(now).unwrap_or(0),
+            violations.policy.min_version_age(crate_score).unwrap_or(0)
+        );
+        results.push(result("recent-version", crate_score, message));
+    }
+    for crate_score in &violations.advisories {
+        for advisory in crate_score.reported_advisories(violations.policy.advisories_since) {
+            let message = format!(
+                "{} {} is affected by {}: {}",
+                crate_score.name,
+                crate_score.version,
+                advisory.id,
+                advisory.summary.as_deref().unwrap_or("no summary")
+            );
+            results.push(result("advisory", crate_score, message));
+        }
+    }
+    for crate_score in &violations.regressions {
+        let previous = crate_score.baseline.as_ref().and_then(|b| b.score);
+        let message = format!(
+            "{} {} scores {}, down from {} in the baseline; pulled in as a {} dependency",
+            crate_score.name,
+            crate_score.version,
+            score_cell(&crate_score.status, args),
+            previous.map_or_else(|| "-".to_string(), |score| args.format_score(score)),
+            crate_score
+                .kind
+                .map_or_else(|| "unknown".to_string(), |kind| kind.to_string())
+        );
+        results.push(result("score-regression", crate_score, message));
+    }
+    for duplicate in &violations.duplicates {
+        let versions: Vec<&str> = duplicate.versions.iter().map(|v| v.version).collect();
+        for version in &duplicate.versions {
+            let via = match version.required_by {
+                [] => String::new(),
+                required_by => format!("; pulled in by {}", required_by.join(", ")),
+            };
+            let message = format!(
+                "{} is resolved at {} versions ({}){}",
+                duplicate.name,
+                versions.len(),
+                versions.join(", "),
+                via
+            );
+            results.push(result("duplicate-crate", version.crate_score, message));
+        }
+    }
+    for crate_score in &violations.unknown {
+        let message = format!(
+            "{} {} has no score data ({}); {:.1}% of the tree doesn't, more than the \
+             maximum of {}%",
+            crate_score.name,
+            crate_score.version,
+            crate_score.status.category(),
+            violations.coverage.unknown_percent(),
+            violations.policy.max_unknown.unwrap_or(0.0)
+        );
+        results.push(result("unknown-score", crate_score, message));
+    }
+    for failure in &violations.check_failures {
+        let crate_score = failure.crate_score;
+        let message = format!(
+            "{} {}: {}, short of the required {} (see {})",
+            crate_score.name,
+            crate_score.version,
+            check_value(failure),
+            failure.requirement,
+            failure.documentation
+        );
+        results.push(result("check-requirement", crate_score, message));
+    }
+    for crate_score in &violations.unscored {
+        let message = format!(
+            "{} {} has no score ({}); {} in the tree have none ({}), more than the maximum of {}",
+            crate_score.name,
+            crate_score.version,
+            score_cell(&crate_score.status, args),
+            violations.unscored.len(),
+            coverage::list_categories(&coverage::by_category(violations.unscored.iter().copied())),
+            violations.policy.max_unscored.unwrap_or(0)
+        );
+        results.push(result("unscored", crate_score, message));
+    }
+    for crate_score in &violations.stale {
+        let message = format!(
+            "{} {} was last scanned {}; the maximum age is {} days",
+            crate_score.name,
+            crate_score.version,
+            scan_date(crate_score),
+            violations.policy.max_scan_age.unwrap_or(0)
+        );
+        results.push(result("stale-scan", crate_score, message));
+    }
+    for crate_score in &violations.dead_repo {
+        let message = format!(
+            "{} {} links to a repository that is unreachable: {}",
+            crate_score.name,
+            crate_score.version,
+            repository_cell(crate_score)
+        );
+        results.push(result("dead-repository", crate_score, message));
+    }
+    for crate_score in &violations.unverified {
+        let message = format!(
+            "{} {} claims {}, which has no Cargo.toml for it",
+            crate_score.name,
+            crate_score.version,
+            repository_cell(crate_score)
+        );
+        results.push(result("unverified-repository", crate_score, message));
+    }
+    for crate_score in &violations.no_repository {
+        let message = format!(
+            "{} {} has no repository metadata",
+            crate_score.name, crate_score.version
+        );
+        results.push(result("no-repository", crate_score, message));
+    }
+
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|(id, short, full)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": short },
+                "fullDescription": { "text": full },
+                "helpUri": "https://securityscorecards.dev",
+                "defaultConfiguration": { "level": "error" },
+            })
+        })
+        .collect();
+
+    let mut log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+            "properties": { "resolution": args.resolution() },
+        }],
+    });
+    if let Some(grade) = grade {
+        log["runs"][0]["properties"]["grade"] = json!(grade.to_string());
+        log["runs"][0]["properties"]["meanScore"] = json!(grade.score);
+    }
+    serde_json::to_string_pretty(&log).expect("a JSON value always serializes")
+}
+
+/// The 1-based line of the crate's `name = "..."` entry in Cargo.lock.
+fn lockfile_line(content: &str, crate_score: &CrateScore) -> Option<usize> {
+    let name = format!("name = \"{}\"", crate_score.name);
+    let version = format!(
+        "version = \"{}\"",
+        crate_score.version.trim_start_matches('v')
+    );
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .windows(2)
+        .position(|pair| pair[0] == name && pair[1] == version)
+        .map(|index| index + 1)
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::deps::Resolution;
+use crate::grade::{self, GradeCutoffs};
+use crate::policy::Violations;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Version of the `--summary-file` layout, bumped on the same terms as the report's.
+pub const SUMMARY_VERSION: u32 = 1;
+
+/// The compact `--summary-file` written next to the report, for automation.
+#[derive(Serialize, JsonSchema)]
+pub struct Summary {
+    /// [`SUMMARY_VERSION`] of the tool that wrote the file.
+    schema_version: u32,
+    tool_version: &'static str,
+    generated_at: DateTime<Utc>,
+    /// The build configuration the report reflects.
+    resolution: Resolution,
+    dependencies: usize,
+    /// Crates per summary category, e.g. `Not scanned`; every category is listed.
+    statuses: BTreeMap<&'static str, usize>,
+    /// Scored crates per grade band (`A` to `F`), using the `[grade]` cutoffs.
+    score_bands: BTreeMap<char, usize>,
+    /// Mean score of the scored crates; absent when none has a score.
+    aggregate_score: Option<f64>,
+    outcome: Outcome,
+    /// Names of the crates breaking the policy, sorted and without duplicates.
+    failing_crates: Vec<String>,
+    /// Share of the dependencies with score data, 0-100, as in the report's `coverage`.
+    coverage_percent: f64,
+}
+
+/// How the run went, as far as the policy is concerned.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Pass,
+    /// No policy failures, but some crates went unscored because of `--offline` or
+    /// `--max-time`, so the run is incomplete.
+    Warn,
+    Fail,
+}
+
+impl Summary {
+    pub fn new(
+        scores: &[CrateScore],
+        violations: &Violations,
+        cutoffs: &GradeCutoffs,
+        resolution: &Resolution,
+    ) -> Self {
+        let incomplete = scores.iter().any(|c| {
+            matches!(
+                c.status,
+                ScoreStatus::Uncached | ScoreStatus::SkippedDeadline | ScoreStatus::Interrupted
+            )
+        });
+        let outcome = if !violations.is_empty() {
+            Outcome::Fail
+        } else if incomplete {
+            Outcome::Warn
+        } else {
+            Outcome::Pass
+        };
+
+        let mut failing_crates: Vec<String> = violations
+            .failing()
+            .into_iter()
+            .map(|c| c.name.clone())
+            .collect();
+        failing_crates.dedup();
+
+        Summary {
+            schema_version: SUMMARY_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION"),
+            generated_at: Utc::now(),
+            resolution: resolution.clone(),
+            dependencies: scores.len(),
+            statuses: ScoreStatus::count_by_category(scores).into_iter().collect(),
+            score_bands: grade::bands(scores, cutoffs),
+            aggregate_score: grade::aggregate(scores),
+            outcome,
+            failing_crates,
+            coverage_percent: violations.coverage.percent,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a summary always serializes")
+    }
+}
+
+/// JSON Schema for the `--summary-file` output.
+pub fn schema() -> String {
+    let mut schema = schemars::schema_for!(Summary);
+    schema.insert(
+        "$id".to_string(),
+        format!("urn:cargo-scorecard:summary:v{}", SUMMARY_VERSION).into(),
+    );
+    serde_json::to_string_pretty(&schema).expect("a JSON Schema always serializes")
+}
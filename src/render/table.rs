@@ -0,0 +1,91 @@
+use std::fmt::Write;
+
+use comfy_table::{
+    CellAlignment, ColumnConstraint, ContentArrangement, Row, Table, Width, presets,
+};
+
+use super::markdown::write_sections;
+use super::{Columns, interrupted_line, self_line};
+use crate::cli::{Args, Column};
+use crate::grade::Grade;
+use crate::policy::Violations;
+use crate::score::{CrateScore, SelfScore};
+
+/// Narrowest the repository column gets before the table overflows instead.
+const REPOSITORY_MIN_WIDTH: u16 = 20;
+
+/// Render the report with an aligned table sized to the terminal.
+pub fn render(
+    scores: &[CrateScore],
+    project: Option<&SelfScore>,
+    violations: &Violations,
+    grade: Option<&Grade>,
+    args: &Args,
+) -> String {
+    let unicode = supports_unicode();
+    let columns = Columns::new(scores, violations.policy, args);
+
+    let mut table = Table::new();
+    table
+        .load_style(if unicode {
+            presets::UTF8_FULL_CONDENSED
+        } else {
+            presets::ASCII_FULL_CONDENSED
+        })
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_truncation_indicator(if unicode { "…" } else { "..." });
+
+    // Truncate rather than wrap, so every crate stays on one line
+    let mut header = Row::from(columns.header());
+    header.max_height(1);
+    table.set_header(header);
+    for crate_score in scores {
+        let mut row = Row::from(columns.row(crate_score, args));
+        row.max_height(1);
+        table.add_row(row);
+    }
+
+    for (kind, column) in columns.iter().zip(table.column_iter_mut()) {
+        match kind {
+            // Never narrowed
+            Column::Name | Column::Version => {
+                column.set_constraint(ColumnConstraint::ContentWidth);
+            }
+            // The one that gives up width when space is short
+            Column::Repository => {
+                column.set_constraint(ColumnConstraint::LowerBoundary(Width::Fixed(
+                    REPOSITORY_MIN_WIDTH,
+                )));
+            }
+            // Right-aligned so decimals line up
+            Column::Score | Column::CustomScore => {
+                column.set_cell_alignment(CellAlignment::Right);
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = String::new();
+    if let Some(line) = interrupted_line(scores) {
+        writeln!(out, "{}\n", line).expect("writing to a String cannot fail");
+    }
+    if let Some(project) = project {
+        writeln!(out, "{}\n", self_line(project, args)).expect("writing to a String cannot fail");
+    }
+    writeln!(out, "Dependency resolution: {}\n", args.resolution())
+        .and_then(|_| writeln!(out, "{}", table))
+        .and_then(|_| write_sections(&mut out, scores, violations, grade, false, args))
+        .expect("writing to a String cannot fail");
+    out
+}
+
+/// Whether the locale promises UTF-8 output; box-drawing characters need it.
+pub fn supports_unicode() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
@@ -0,0 +1,176 @@
+use anyhow::{Result, anyhow};
+
+use crate::cli::Args;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Placeholders a `--template` can use.
+const PLACEHOLDERS: &[&str] = &[
+    "name",
+    "version",
+    "pinned_from",
+    "changed",
+    "source",
+    "score",
+    "custom_score",
+    "status",
+    "repository",
+    "repo",
+    "repository_inferred_from",
+    "direct",
+    "kind",
+    "depth",
+    "introduced_by",
+    "projects",
+    "required_by",
+    "build_time",
+    "unique_dependencies",
+    "reach",
+    "risk",
+    "repo_exists",
+    "repo_verified",
+    "homepage",
+    "documentation",
+    "yanked",
+    "published_at",
+    "crate_created_at",
+    "checks",
+    "advisories",
+    "archived",
+    "stars",
+    "last_push",
+    "baseline_version",
+    "baseline_score",
+    "trusted",
+    "waiver",
+    "unlocked",
+];
+
+/// A piece of a parsed template.
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Split a template into text and `{name}` placeholders; `{{` and `}}` are literal braces.
+fn parse(template: &str) -> Result<Vec<Part<'_>>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        parts.push(Part::Text(&rest[..start]));
+        let brace = &rest[start..start + 1];
+        if rest[start + 1..].starts_with(brace) {
+            parts.push(Part::Text(brace));
+            rest = &rest[start + 2..];
+            continue;
+        }
+        if brace == "}" {
+            return Err(anyhow!(
+                "--template has an unmatched `}}`; write `}}}}` for a literal brace"
+            ));
+        }
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("--template has an unclosed `{{`"))?;
+        let name = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(anyhow!(
+                "--template has an unknown placeholder {{{}}}; available: {}",
+                name,
+                PLACEHOLDERS.join(", ")
+            ));
+        }
+        parts.push(Part::Placeholder(name));
+        rest = &rest[start + end + 1..];
+    }
+    parts.push(Part::Text(rest));
+    Ok(parts)
+}
+
+/// Check a `--template` before any work is done.
+pub fn validate(template: &str) -> Result<()> {
+    parse(template).map(|_| ())
+}
+
+/// One line per crate, filled in from `--template`; empty values become `--template-missing`.
+pub fn render(scores: &[CrateScore], template: &str, args: &Args) -> Result<String> {
+    let parts = parse(template)?;
+    let mut output = String::new();
+    for crate_score in scores {
+        for part in &parts {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Placeholder(name) => match value(crate_score, name, args) {
+                    Some(value) if !value.is_empty() => output.push_str(&value),
+                    _ => output.push_str(&args.template_missing),
+                },
+            }
+        }
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// The text for one placeholder, `None` when the crate has no value for it.
+fn value(c: &CrateScore, name: &str, args: &Args) -> Option<String> {
+    let list = |items: &[String]| (!items.is_empty()).then(|| items.join(", "));
+    let date = |date: Option<chrono::DateTime<chrono::Utc>>| {
+        date.map(|date| date.format("%Y-%m-%d").to_string())
+    };
+    match name {
+        "name" => Some(c.name.clone()),
+        "version" => Some(c.version.clone()),
+        "pinned_from" => c.pinned_from.clone(),
+        "changed" => c.changed.as_ref().map(ToString::to_string),
+        "source" => c.source.as_ref().map(ToString::to_string),
+        "score" => match c.status {
+            ScoreStatus::Scored(score) => Some(args.format_score(score)),
+            _ => None,
+        },
+        "custom_score" => c.custom_score.map(|score| args.format_score(score)),
+        "status" => Some(c.category().to_string()),
+        "repository" => c.repository.clone(),
+        "repo" => c.repo.as_ref().map(ToString::to_string),
+        "repository_inferred_from" => c.repository_inferred_from.clone(),
+        "direct" => Some(c.direct.to_string()),
+        "kind" => c.kind.map(|kind| kind.to_string()),
+        "depth" => c.depth.map(|depth| depth.to_string()),
+        "introduced_by" => c.introduced_by.clone(),
+        "projects" => list(&c.projects),
+        "required_by" => list(&c.required_by),
+        "build_time" => Some(c.build_time.to_string()),
+        "unique_dependencies" => c.unique_dependencies.map(|n| n.to_string()),
+        "reach" => c.reach.map(|n| n.to_string()),
+        "risk" => c.risk.map(|risk| format!("{:.1}", risk)),
+        "repo_exists" => c.repo_exists.map(|exists| exists.to_string()),
+        "repo_verified" => c.repo_verified.map(|verified| verified.to_string()),
+        "homepage" => c.homepage.clone(),
+        "documentation" => c.documentation.clone(),
+        "yanked" => Some(c.yanked.is_some().to_string()),
+        "published_at" => date(c.published_at),
+        "crate_created_at" => date(c.crate_created_at),
+        "checks" => c.scorecard.as_ref().map(|scorecard| {
+            scorecard
+                .checks
+                .iter()
+                .map(|check| match check.score {
+                    Some(score) if score >= 0.0 => format!("{}={}", check.name, score),
+                    _ => format!("{}=?", check.name),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        }),
+        "advisories" => c.advisories.as_ref().map(|advisories| {
+            let ids: Vec<String> = advisories.iter().map(|a| a.id.clone()).collect();
+            ids.join(", ")
+        }),
+        "archived" => c.repo_metadata.as_ref().map(|m| m.archived.to_string()),
+        "stars" => c.repo_metadata.as_ref().map(|m| m.stars.to_string()),
+        "last_push" => date(c.repo_metadata.as_ref()?.last_push),
+        "baseline_version" => c.baseline.as_ref().map(|b| b.version.clone()),
+        "baseline_score" => c.baseline.as_ref()?.score.map(|s| args.format_score(s)),
+        "trusted" => Some(c.trusted.to_string()),
+        "waiver" => c.waiver.clone(),
+        "unlocked" => Some(c.unlocked.to_string()),
+        _ => unreachable!("placeholders are validated when parsing"),
+    }
+}
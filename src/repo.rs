@@ -0,0 +1,189 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// Forge hosts for which the hosted scorecard API publishes results.
+///
+/// Add new hosts here when the scorecard API starts covering them.
+pub const SCORECARD_API_HOSTS: &[&str] = &["github.com", "gitlab.com"];
+
+/// A repository URL reduced to the `host/owner/repo` form the scorecard API expects.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Repository {
+    pub host: String,
+    pub path: String,
+}
+
+impl Repository {
+    /// Normalize a `repository` field from crate metadata.
+    ///
+    /// Strips schemes, `.git` suffixes, trailing slashes and deep links such as
+    /// `/tree/master/subdir`. Returns `None` when the value is not a usable URL.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let url = parse_url(raw)?;
+        let host = url.host_str()?.trim_start_matches("www.").to_string();
+
+        let segments = url.path_segments()?.filter(|s| !s.is_empty());
+        let segments: Vec<&str> = if host == "github.com" {
+            segments.take(2).collect()
+        } else {
+            // GitLab, Gitea and friends separate the repo path from deep links.
+            segments
+                .take_while(|s| !matches!(*s, "-" | "tree" | "blob" | "src"))
+                .collect()
+        };
+        if segments.len() < 2 {
+            return None;
+        }
+        let path = segments.join("/");
+        let path = path.strip_suffix(".git").unwrap_or(&path).to_string();
+
+        Some(Repository { host, path })
+    }
+
+    /// Whether the hosted scorecard API can have a result for this repository.
+    pub fn is_scorecard_supported(&self) -> bool {
+        SCORECARD_API_HOSTS.contains(&self.host.as_str())
+    }
+
+    /// Whether the repository lives under `prefix`, such as `github.com/rust-lang`.
+    ///
+    /// Matches whole path segments only, so `github.com/rust-lang` does not cover
+    /// `github.com/rust-lang-nursery/...`. Forge paths are compared case-insensitively.
+    pub fn is_within(&self, prefix: &str) -> bool {
+        let prefix = prefix.trim().trim_end_matches('/');
+        let prefix = prefix
+            .strip_prefix("https://")
+            .or_else(|| prefix.strip_prefix("http://"))
+            .unwrap_or(prefix);
+        let repo = self.to_string();
+        match repo.get(..prefix.len()) {
+            Some(head) if head.eq_ignore_ascii_case(prefix) => {
+                repo.len() == prefix.len() || repo[prefix.len()..].starts_with('/')
+            }
+            _ => false,
+        }
+    }
+}
+
+/// GitHub paths that are account pages rather than an owner, e.g. `github.com/sponsors/x`.
+const GITHUB_NON_OWNERS: &[&str] = &["sponsors", "orgs", "users", "marketplace", "topics"];
+
+/// A repository from a `homepage` or `documentation` link, for crates without a
+/// `repository` field.
+///
+/// Only links into a forge the scorecard API covers count: docs.rs, crates.io and
+/// project sites say nothing about where the source lives.
+pub fn infer_from_link(raw: &str) -> Option<Repository> {
+    let repo = Repository::parse(raw)?;
+    let owner = repo.path.split('/').next().unwrap_or_default();
+    if !repo.is_scorecard_supported()
+        || (repo.host == "github.com" && GITHUB_NON_OWNERS.contains(&owner))
+    {
+        return None;
+    }
+    Some(repo)
+}
+
+/// Whether a `repository` field is an http(s) URL with a host, rather than junk
+/// such as an email address, a local path or "TBD".
+pub fn is_valid_url(raw: &str) -> bool {
+    parse_url(raw).is_some()
+}
+
+fn parse_url(raw: &str) -> Option<Url> {
+    let raw = raw.trim();
+    let raw = raw.strip_prefix("git+").unwrap_or(raw);
+    let url = Url::parse(raw).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return None;
+    }
+    Some(url)
+}
+
+impl std::fmt::Display for Repository {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.host, self.path)
+    }
+}
+
+impl From<Repository> for String {
+    fn from(repo: Repository) -> Self {
+        repo.to_string()
+    }
+}
+
+/// Read back the `host/owner/repo` form a report holds.
+impl TryFrom<String> for Repository {
+    type Error = String;
+
+    fn try_from(repo: String) -> Result<Self, Self::Error> {
+        Repository::parse(&format!("https://{}", repo))
+            .ok_or_else(|| format!("{} is not a repository", repo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_malformed_repository_fields() {
+        for raw in [
+            "",
+            "TBD",
+            "maintainer@example.com",
+            "mailto:maintainer@example.com",
+            "../my-crate",
+            "/home/me/src/my-crate",
+            "git@github.com:owner/repo.git",
+            "ftp://example.com/owner/repo",
+            "https://",
+        ] {
+            assert!(!is_valid_url(raw), "{:?} should be rejected", raw);
+            assert_eq!(Repository::parse(raw), None, "{:?} should not parse", raw);
+        }
+    }
+
+    #[test]
+    fn accepts_http_urls() {
+        for raw in [
+            "https://github.com/owner/repo",
+            "http://gitlab.com/owner/repo",
+            "git+https://github.com/owner/repo.git",
+            "  https://example.com/owner/repo  ",
+        ] {
+            assert!(is_valid_url(raw), "{:?} should be accepted", raw);
+        }
+    }
+
+    #[test]
+    fn normalizes_repository_urls() {
+        let cases = [
+            ("https://github.com/owner/repo", "github.com/owner/repo"),
+            (
+                "https://www.github.com/owner/repo.git/",
+                "github.com/owner/repo",
+            ),
+            (
+                "git+https://github.com/owner/repo/tree/master/subdir",
+                "github.com/owner/repo",
+            ),
+            (
+                "https://gitlab.com/group/sub/repo/-/tree/main",
+                "gitlab.com/group/sub/repo",
+            ),
+        ];
+        for (raw, expected) in cases {
+            let repo = Repository::parse(raw).expect(raw);
+            assert_eq!(repo.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn a_url_without_owner_and_repo_is_not_a_repository() {
+        assert!(is_valid_url("https://example.com/"));
+        assert_eq!(Repository::parse("https://example.com/"), None);
+        assert_eq!(Repository::parse("https://github.com/owner"), None);
+    }
+}
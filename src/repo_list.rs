@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+
+use crate::deps::{BuildTime, CrateInfo};
+use crate::repo::Repository;
+
+/// Read a `--repos-file`: one repository URL per line, with blank lines and `#`
+/// comments ignored. Each becomes a row named `owner/repo` with version `-`, scored
+/// straight from the repository without a crates.io lookup.
+pub fn read(path: &Path) -> Result<Vec<CrateInfo>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split_once('#').map_or(line, |(url, _)| url).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let repo = Repository::parse(line).ok_or_else(|| {
+            anyhow!(
+                "{}:{}: {} is not a repository URL such as https://github.com/owner/repo",
+                path.display(),
+                number + 1,
+                line
+            )
+        })?;
+        if !seen.insert(repo.clone()) {
+            continue;
+        }
+        entries.push(CrateInfo {
+            name: repo.path.clone(),
+            version: "-".to_string(),
+            direct: true,
+            kind: None,
+            vendored: None,
+            depth: None,
+            introduced_by: None,
+            projects: Vec::new(),
+            build_time: BuildTime::default(),
+            unique_dependencies: None,
+            reach: None,
+            pinned_from: None,
+            listed_repository: Some(format!("https://{}", repo)),
+            mapped_repository: None,
+            changed: None,
+            source: None,
+        });
+    }
+    Ok(entries)
+}
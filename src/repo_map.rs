@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use tracing::debug;
+
+use crate::deps::CrateInfo;
+use crate::repo;
+
+/// Read a `--repo-map`: crate names mapped to repository URLs, as a TOML table
+/// (`serde = "https://github.com/serde-rs/serde"`) or, for a `.json` file, a JSON
+/// object. Every URL must be an http(s) URL.
+pub fn load(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let map: HashMap<String, String> = match path.extension().is_some_and(|ext| ext == "json") {
+        true => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        false => toml::from_str(&content).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+    let mut invalid: Vec<String> = map
+        .iter()
+        .filter(|(_, url)| !repo::is_valid_url(url))
+        .map(|(name, url)| format!("{} = {:?}", name, url))
+        .collect();
+    if !invalid.is_empty() {
+        invalid.sort();
+        return Err(anyhow!(
+            "{} maps crates to invalid repository URLs: {}",
+            path.display(),
+            invalid.join(", ")
+        ));
+    }
+    Ok(map)
+}
+
+/// Point each mapped crate at its repository in the map, over what crates.io or its
+/// manifest says.
+pub fn apply(crates: &mut [CrateInfo], map: &HashMap<String, String>) {
+    for crate_info in crates {
+        if let Some(repository) = map.get(&crate_info.name) {
+            debug!(crate.name = %crate_info.name, repository, "repository from --repo-map");
+            crate_info.mapped_repository = Some(repository.clone());
+        }
+    }
+}
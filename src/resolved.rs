@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::cache::{Cache, CacheKind};
+use crate::deps::{self, CargoOptions, CrateInfo};
+
+/// A cached tree and the local manifests it was resolved from.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// SHA-256 of each local package's Cargo.toml, by path.
+    manifests: BTreeMap<PathBuf, String>,
+    crates: Vec<CrateInfo>,
+}
+
+/// The crates cargo resolves for `manifests`, reused from the cache while every
+/// manifest and its Cargo.lock are unchanged, so re-rendering a report skips cargo.
+///
+/// Besides the given manifests and their lockfiles, this covers the Cargo.toml of
+/// every workspace member and path dependency, whose features and dependency kinds
+/// shape the tree without showing in Cargo.lock. Without a lockfile cargo would
+/// resolve afresh, so nothing is cached; `--no-cache` keeps the entry to this run.
+pub fn dependencies(
+    cache: &Cache,
+    manifests: &[&Path],
+    options: &CargoOptions,
+    resolve: impl FnOnce() -> Result<Vec<CrateInfo>>,
+) -> Result<Vec<CrateInfo>> {
+    let Some(key) = key(manifests, options) else {
+        return resolve();
+    };
+    if let Some(value) = cache.get(CacheKind::Resolved, &key) {
+        match serde_json::from_value::<Entry>(value) {
+            Ok(entry) if is_current(&entry) => {
+                debug!(key = %key, "reusing the dependency tree resolved for this Cargo.lock");
+                return Ok(entry.crates);
+            }
+            Ok(_) => debug!(key = %key, "a local manifest changed; resolving again"),
+            Err(e) => debug!(key = %key, error = %e, "ignoring unreadable resolved tree"),
+        }
+    }
+    let crates = resolve()?;
+    let Some(local) = local_manifests(manifests, options) else {
+        return Ok(crates);
+    };
+    let entry = Entry {
+        manifests: local,
+        crates,
+    };
+    match serde_json::to_value(&entry) {
+        Ok(value) => cache.put(CacheKind::Resolved, &key, &value),
+        Err(e) => debug!(error = %e, "could not cache the resolved tree"),
+    }
+    Ok(entry.crates)
+}
+
+/// Whether every local manifest still reads as it did when the tree was resolved.
+fn is_current(entry: &Entry) -> bool {
+    entry
+        .manifests
+        .iter()
+        .all(|(path, hash)| digest(path).as_ref() == Some(hash))
+}
+
+/// The hashes of the local packages' manifests behind `manifests`; `None` when cargo
+/// can't list them, and then the tree isn't cached.
+fn local_manifests(
+    manifests: &[&Path],
+    options: &CargoOptions,
+) -> Option<BTreeMap<PathBuf, String>> {
+    let mut local = BTreeMap::new();
+    for manifest in manifests {
+        let options = CargoOptions {
+            manifest_path: Some(manifest),
+            ..*options
+        };
+        let paths = match deps::local_manifests(&options) {
+            Ok(paths) => paths,
+            Err(e) => {
+                debug!(error = %e, "could not list local manifests; not caching the tree");
+                return None;
+            }
+        };
+        for path in paths {
+            let hash = digest(&path)?;
+            local.insert(path, hash);
+        }
+    }
+    Some(local)
+}
+
+/// A hash of each manifest and its Cargo.lock, the cargo options and the tool version;
+/// `None` when a manifest has no lockfile yet.
+fn key(manifests: &[&Path], options: &CargoOptions) -> Option<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(options.resolution());
+    for manifest in manifests {
+        let manifest = std::fs::canonicalize(manifest).ok()?;
+        let lockfile = lockfile(&manifest)?;
+        for path in [&manifest, &lockfile] {
+            hasher.update(path.to_string_lossy().as_bytes());
+            hasher.update(std::fs::read(path).ok()?);
+        }
+    }
+    Some(hex(hasher))
+}
+
+/// SHA-256 of a file's contents.
+fn digest(path: &Path) -> Option<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(std::fs::read(path).ok()?);
+    Some(hex(hasher))
+}
+
+fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The Cargo.lock cargo uses for `manifest`: next to it, or next to its workspace root.
+fn lockfile(manifest: &Path) -> Option<PathBuf> {
+    manifest
+        .parent()?
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lockfile| lockfile.is_file())
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::deps::{CrateInfo, Resolution};
+use crate::render::json::SCHEMA_VERSION;
+use crate::score::{CrateScore, ScoreStatus, SelfScore};
+
+/// The parts of a `--format json` report that `cargo scorecard retry` reads back.
+#[derive(Deserialize)]
+pub struct Report {
+    schema_version: u32,
+    #[serde(default)]
+    metadata: Metadata,
+    pub self_score: Option<SelfScore>,
+    pub crates: Vec<CrateScore>,
+}
+
+#[derive(Default, Deserialize)]
+struct Metadata {
+    resolution: Resolution,
+}
+
+impl Report {
+    /// Read a report written by a release with the same schema version; rows from
+    /// another layout couldn't be merged faithfully.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let report: Report = serde_json::from_str(&content).map_err(|e| {
+            anyhow!(
+                "{} is not a --format json report this release can retry: {}",
+                path.display(),
+                e
+            )
+        })?;
+        if report.schema_version != SCHEMA_VERSION {
+            return Err(anyhow!(
+                "{} has schema version {}, but this release writes version {}; rerun the \
+                 whole report instead",
+                path.display(),
+                report.schema_version,
+                SCHEMA_VERSION
+            ));
+        }
+        Ok(report)
+    }
+
+    /// The build configuration the report was resolved for, which the merged report
+    /// still reflects.
+    pub fn resolution(&self) -> &Resolution {
+        &self.metadata.resolution
+    }
+
+    /// Indices of the rows whose lookup failed.
+    pub fn failed(&self) -> Vec<usize> {
+        (0..self.crates.len())
+            .filter(|&index| matches!(self.crates[index].status, ScoreStatus::Error(_)))
+            .collect()
+    }
+}
+
+/// What fetching a report row again needs to know about it.
+pub fn crate_info(row: &CrateScore) -> CrateInfo {
+    CrateInfo {
+        name: row.name.clone(),
+        version: row.version.clone(),
+        direct: row.direct,
+        kind: row.kind,
+        vendored: None,
+        depth: row.depth,
+        introduced_by: row.introduced_by.clone(),
+        projects: row.projects.clone(),
+        build_time: row.build_time,
+        unique_dependencies: row.unique_dependencies,
+        reach: row.reach,
+        pinned_from: row.pinned_from.clone(),
+        listed_repository: row.listed.then(|| row.repository.clone()).flatten(),
+        mapped_repository: None,
+        changed: row.changed.clone(),
+        source: row.source.clone(),
+    }
+}
+
+/// Put a fetched-again row in place of the failed one; one the run never got to, cut
+/// short by `--max-time` or Ctrl-C, leaves the old row as it was.
+pub fn merge(previous: &mut CrateScore, mut retried: CrateScore) {
+    if matches!(
+        retried.status,
+        ScoreStatus::SkippedDeadline | ScoreStatus::Interrupted
+    ) {
+        return;
+    }
+    retried.attempts = previous.attempts + 1;
+    retried.required_by = std::mem::take(&mut previous.required_by);
+    *previous = retried;
+}
@@ -0,0 +1,324 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::changed::LockChange;
+use crate::deps::{BuildTime, CrateInfo, CrateSource, DepKind};
+use crate::diff::BaselineEntry;
+use crate::enrich::RepoMetadata;
+use crate::osv::Advisory;
+use crate::repo::Repository;
+use crate::scorecard::ScorecardResult;
+
+/// One row of the report; `--format json` prints these under `crates`, and
+/// `cargo scorecard retry` reads them back.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CrateScore {
+    pub name: String,
+    pub version: String,
+    pub direct: bool,
+    /// The strongest kind of dependency edge that pulls the crate in, when known.
+    pub kind: Option<DepKind>,
+    /// Distance from the audited crate, for `cargo scorecard audit`.
+    pub depth: Option<usize>,
+    /// The crate that first pulled this one in, for `cargo scorecard audit`.
+    pub introduced_by: Option<String>,
+    /// The projects using the crate, when several manifests are scored together.
+    #[serde(default)]
+    pub projects: Vec<String>,
+    /// Direct dependencies pulling this version in, as `name version`; only filled in
+    /// for crates resolved at more than one version.
+    #[serde(default)]
+    pub required_by: Vec<String>,
+    /// Build scripts and proc-macros; all false when unknown, e.g. in audit mode.
+    #[serde(default)]
+    pub build_time: BuildTime,
+    /// For a direct dependency, how many crates only it pulls in.
+    pub unique_dependencies: Option<usize>,
+    /// How many packages in the tree depend on the crate, directly or not.
+    pub reach: Option<usize>,
+    /// `(10 - score) × ln(1 + reach)`: a low score matters more the more of the tree
+    /// depends on it. `None` without both a score and a reach.
+    pub risk: Option<f64>,
+    /// A `--repos-file` entry rather than a crate: `name` is the repository's
+    /// `owner/repo` and `version` is `-`.
+    #[serde(default)]
+    pub listed: bool,
+    /// The resolved version when `--pin` swapped in `version` to preview an upgrade.
+    pub pinned_from: Option<String>,
+    /// How the version differs from the `--changed-since` lockfile: added, upgraded
+    /// or downgraded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed: Option<LockChange>,
+    /// Registry, git or path, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<CrateSource>,
+    pub repository: Option<String>,
+    /// `homepage` or `documentation` when the crate has no `repository` field and
+    /// `repository` was inferred from that link instead.
+    pub repository_inferred_from: Option<String>,
+    /// `repository` normalized for API lookups.
+    #[schemars(with = "Option<String>")]
+    pub repo: Option<Repository>,
+    /// Whether the repository exists: `false` for bad metadata pointing nowhere, `true`
+    /// when it exists (scored or not); `None` when not checked.
+    pub repo_exists: Option<bool>,
+    /// Whether the repository has a Cargo.toml for a package of this name, with
+    /// `--verify-provenance`; `false` makes the score suspect, `None` when not checked.
+    pub repo_verified: Option<bool>,
+    /// The repository seems to belong to other crates in the tree and not this one;
+    /// only a heuristic, to check by hand.
+    #[serde(default)]
+    pub repo_mismatch: bool,
+    /// crates.io `homepage` and `documentation` links, leads when `repository` is missing.
+    pub homepage: Option<String>,
+    pub documentation: Option<String>,
+    /// Set when crates.io reports the version in use as yanked.
+    pub yanked: Option<Yank>,
+    /// When crates.io published the version in use.
+    pub published_at: Option<DateTime<Utc>>,
+    /// When crates.io published the crate's first version.
+    pub crate_created_at: Option<DateTime<Utc>>,
+    pub status: ScoreStatus,
+    /// Lookups made for the row: 1, plus one for each `cargo scorecard retry` of it.
+    #[serde(default = "first_attempt")]
+    pub attempts: u32,
+    /// The weighted mean of the checks under `--check-weights`; `status` keeps the
+    /// official score.
+    pub custom_score: Option<f64>,
+    /// The full scorecard result behind a `Scored` status.
+    pub scorecard: Option<ScorecardResult>,
+    /// OSV advisories for the version in use, with `--advisories`; `None` when not checked.
+    pub advisories: Option<Vec<Advisory>>,
+    /// Forge metadata, filled in by `--github-enrich`.
+    pub repo_metadata: Option<RepoMetadata>,
+    /// The same crate in the `--baseline` report; `None` without a baseline or when new.
+    pub baseline: Option<BaselineEntry>,
+    /// The repository belongs to a `trusted_orgs` entry, exempting it from policy.
+    #[serde(default)]
+    pub trusted: bool,
+    /// Where a waiver exempting the crate from policy came from, e.g. `deny.toml`.
+    pub waiver: Option<String>,
+    /// Vendored, but missing from Cargo.lock or with a different checksum.
+    #[serde(default)]
+    pub unlocked: bool,
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+/// The scorecard result for the project being scored, shown above the dependencies.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SelfScore {
+    /// From the root manifest, or the `origin` git remote.
+    pub repository: Option<String>,
+    #[schemars(with = "Option<String>")]
+    pub repo: Option<Repository>,
+    pub status: ScoreStatus,
+}
+
+impl CrateScore {
+    /// Whole days since the version in use was published.
+    pub fn version_age_days(&self, now: DateTime<Utc>) -> Option<i64> {
+        Some((now - self.published_at?).num_days())
+    }
+
+    /// Advisories reported under `--advisories-since`, leaving out waived ones.
+    pub fn reported_advisories(&self, since: Option<NaiveDate>) -> Vec<&Advisory> {
+        self.advisories
+            .iter()
+            .flatten()
+            .filter(|advisory| advisory.waiver.is_none() && advisory.is_since(since))
+            .collect()
+    }
+
+    /// Whether the crate comes from a local path, as workspace members and path
+    /// dependencies do, rather than from a registry or git.
+    pub fn is_local(&self) -> bool {
+        self.source == Some(CrateSource::Path) || matches!(self.status, ScoreStatus::Local)
+    }
+
+    /// Whether the crate itself, not just this version, first appeared within `days`.
+    pub fn is_new_crate(&self, now: DateTime<Utc>, days: i64) -> bool {
+        self.crate_created_at
+            .is_some_and(|created| (now - created).num_days() < days)
+    }
+}
+
+/// Context for a yanked version; either field is often missing.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Yank {
+    /// The message given when yanking, if any.
+    pub reason: Option<String>,
+    /// When the version last changed on crates.io, usually the yank itself.
+    pub updated_at: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreStatus {
+    Scored(f64),
+    NotScanned,
+    /// The scorecard API knows the repository but is still computing its score.
+    Pending,
+    NoRepository,
+    UnsupportedForge(String),
+    /// The repository itself is gone or private; holds the HTTP status.
+    Unreachable(u16),
+    /// A lookup failed; holds the error message.
+    Error(String),
+    /// Offline and the needed responses are not in the cache.
+    Uncached,
+    /// Not fetched because `--max-time` expired first.
+    SkippedDeadline,
+    /// Not fetched because the run was stopped with Ctrl-C.
+    Interrupted,
+    /// A path dependency: local code with nothing to look up.
+    Local,
+}
+
+impl ScoreStatus {
+    /// Summary categories in display order.
+    const CATEGORIES: &[&str] = &[
+        "Scored",
+        "Not scanned",
+        "Pending",
+        "No repository",
+        "Unsupported forge",
+        "Unreachable",
+        "Error",
+        "Uncached",
+        "Skipped (deadline)",
+        "Interrupted",
+        "Local",
+        "Trusted",
+    ];
+
+    /// The summary category this status is counted under.
+    pub fn category(&self) -> &'static str {
+        match self {
+            ScoreStatus::Scored(_) => "Scored",
+            ScoreStatus::NotScanned => "Not scanned",
+            ScoreStatus::Pending => "Pending",
+            ScoreStatus::NoRepository => "No repository",
+            ScoreStatus::UnsupportedForge(_) => "Unsupported forge",
+            ScoreStatus::Unreachable(_) => "Unreachable",
+            ScoreStatus::Error(_) => "Error",
+            ScoreStatus::Uncached => "Uncached",
+            ScoreStatus::SkippedDeadline => "Skipped (deadline)",
+            ScoreStatus::Interrupted => "Interrupted",
+            ScoreStatus::Local => "Local",
+        }
+    }
+
+    /// Number of crates in each summary category, in display order.
+    pub fn count_by_category(scores: &[CrateScore]) -> Vec<(&'static str, usize)> {
+        Self::CATEGORIES
+            .iter()
+            .map(|category| {
+                let count = scores.iter().filter(|c| c.category() == *category).count();
+                (*category, count)
+            })
+            .collect()
+    }
+}
+
+/// The risk of a crate with this score and reach; see [`CrateScore::risk`].
+pub fn risk(status: &ScoreStatus, reach: Option<usize>) -> Option<f64> {
+    match status {
+        ScoreStatus::Scored(score) => Some((10.0 - score) * (1.0 + reach? as f64).ln()),
+        _ => None,
+    }
+}
+
+impl CrateScore {
+    /// The summary category this crate is counted under; trusted crates get their own.
+    pub fn category(&self) -> &'static str {
+        if self.trusted {
+            "Trusted"
+        } else {
+            self.status.category()
+        }
+    }
+
+    /// A row for a crate whose repository could not be looked up.
+    pub fn unfetched(crate_info: &CrateInfo, status: ScoreStatus) -> Self {
+        CrateScore {
+            name: crate_info.name.clone(),
+            version: crate_info.version.clone(),
+            direct: crate_info.direct,
+            kind: crate_info.kind,
+            depth: crate_info.depth,
+            introduced_by: crate_info.introduced_by.clone(),
+            projects: crate_info.projects.clone(),
+            required_by: Vec::new(),
+            build_time: crate_info.build_time,
+            unique_dependencies: crate_info.unique_dependencies,
+            reach: crate_info.reach,
+            risk: None,
+            listed: crate_info.listed_repository.is_some(),
+            pinned_from: crate_info.pinned_from.clone(),
+            changed: crate_info.changed.clone(),
+            source: crate_info.source.clone(),
+            repository: None,
+            repository_inferred_from: None,
+            repo: None,
+            repo_exists: None,
+            repo_verified: None,
+            repo_mismatch: false,
+            homepage: None,
+            documentation: None,
+            yanked: None,
+            published_at: None,
+            crate_created_at: None,
+            status,
+            attempts: 1,
+            custom_score: None,
+            scorecard: None,
+            advisories: None,
+            repo_metadata: None,
+            baseline: None,
+            trusted: false,
+            waiver: None,
+            unlocked: crate_info.is_unlocked(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A registry crate with nothing looked up beyond `status`.
+    pub fn crate_score(name: &str, status: ScoreStatus) -> CrateScore {
+        let crate_info = CrateInfo {
+            name: name.to_string(),
+            version: "v1.0.0".to_string(),
+            direct: false,
+            kind: None,
+            vendored: None,
+            depth: None,
+            introduced_by: None,
+            projects: Vec::new(),
+            build_time: BuildTime::default(),
+            unique_dependencies: None,
+            reach: None,
+            pinned_from: None,
+            listed_repository: None,
+            mapped_repository: None,
+            changed: None,
+            source: Some(CrateSource::Registry),
+        };
+        CrateScore::unfetched(&crate_info, status)
+    }
+
+    #[test]
+    fn path_crates_are_local() {
+        let mut crate_score = crate_score("member", ScoreStatus::NoRepository);
+        assert!(!crate_score.is_local());
+        crate_score.source = Some(CrateSource::Path);
+        assert!(crate_score.is_local());
+        assert!(super::tests::crate_score("dep", ScoreStatus::Local).is_local());
+    }
+}
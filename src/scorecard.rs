@@ -0,0 +1,325 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Error, anyhow};
+
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The parts of a scorecard API result we use.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct ScorecardResult {
+    /// When the repository was last scanned, e.g. `2024-05-06` or an RFC 3339 timestamp.
+    pub date: Option<String>,
+    pub score: Option<f64>,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct Check {
+    pub name: String,
+    /// 0-10, or -1 when the check could not produce a result.
+    pub score: Option<f64>,
+    pub reason: Option<String>,
+    pub documentation: Option<CheckDocumentation>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct CheckDocumentation {
+    pub short: Option<String>,
+    pub url: Option<String>,
+}
+
+impl ScorecardResult {
+    /// The day the scan ran.
+    pub fn date(&self) -> Option<NaiveDate> {
+        let date = self.date.as_deref()?;
+        NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+    }
+
+    /// Checks that produced a score rather than "no data".
+    pub fn scored_checks(&self) -> impl Iterator<Item = &Check> {
+        self.checks
+            .iter()
+            .filter(|c| c.score.is_some_and(|s| s >= 0.0))
+    }
+}
+
+/// A `--check-weights` entry, written `name=weight`.
+#[derive(Clone, Debug)]
+pub struct CheckWeight {
+    /// Matched against check names ignoring case, e.g. `Code-Review`.
+    pub name: String,
+    pub weight: f64,
+}
+
+impl FromStr for CheckWeight {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, weight) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected name=weight, got {}", s))?;
+        let weight: f64 = weight
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("invalid weight in {}: {}", s, e))?;
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(anyhow!("weight in {} must be zero or more", s));
+        }
+        Ok(CheckWeight {
+            name: name.trim().to_string(),
+            weight,
+        })
+    }
+}
+
+/// A `--require-check` entry, written `name>=value`.
+#[derive(Clone, Debug)]
+pub struct CheckRequirement {
+    /// Matched against check names ignoring case, e.g. `Branch-Protection`.
+    pub name: String,
+    /// The lowest passing check score, 0-10.
+    pub min: f64,
+}
+
+impl FromStr for CheckRequirement {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, min) = s
+            .split_once(">=")
+            .ok_or_else(|| anyhow!("expected name>=value, got {}", s))?;
+        let min: f64 = min
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("invalid value in {}: {}", s, e))?;
+        if !(0.0..=10.0).contains(&min) {
+            return Err(anyhow!("value in {} must be between 0 and 10", s));
+        }
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("missing check name in {}", s));
+        }
+        Ok(CheckRequirement {
+            name: name.to_string(),
+            min,
+        })
+    }
+}
+
+impl fmt::Display for CheckRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}>={}", self.name, self.min)
+    }
+}
+
+/// The weighted mean of the scored checks, each weighing 1 unless `weights` names it.
+///
+/// Checks without data are left out rather than counted as zero; `None` when no
+/// scored check carries any weight.
+pub fn weighted_score(result: &ScorecardResult, weights: &[CheckWeight]) -> Option<f64> {
+    let (total, weight_sum) = result
+        .scored_checks()
+        .filter_map(|check| {
+            let weight = weights
+                .iter()
+                .rfind(|w| w.name.eq_ignore_ascii_case(&check.name))
+                .map_or(1.0, |w| w.weight);
+            Some((check.score? * weight, weight))
+        })
+        .fold((0.0, 0.0), |(total, sum), (score, weight)| {
+            (total + score, sum + weight)
+        });
+    (weight_sum > 0.0).then(|| total / weight_sum)
+}
+
+/// How much a bare aggregate score can be trusted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        })
+    }
+}
+
+/// Rate a result by its age and by how many of its checks produced a score.
+///
+/// High confidence needs a scan at most 30 days old with at least 80% of checks
+/// scored; a scan older than 180 days, an unknown date, or fewer than half the
+/// checks scored is low confidence. Everything in between is medium.
+pub fn confidence(result: &ScorecardResult, today: NaiveDate) -> Confidence {
+    let Some(date) = result.date() else {
+        return Confidence::Low;
+    };
+    let age_days = (today - date).num_days();
+    let coverage = if result.checks.is_empty() {
+        0.0
+    } else {
+        result.scored_checks().count() as f64 / result.checks.len() as f64
+    };
+
+    if age_days > 180 || coverage < 0.5 {
+        Confidence::Low
+    } else if age_days <= 30 && coverage >= 0.8 {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str, score: f64) -> Check {
+        Check {
+            name: name.to_string(),
+            score: Some(score),
+            ..Check::default()
+        }
+    }
+
+    /// A result scanned on `date` with `scored` checks out of `total` producing a score.
+    fn scanned(date: Option<&str>, scored: usize, total: usize) -> ScorecardResult {
+        ScorecardResult {
+            date: date.map(str::to_string),
+            score: Some(5.0),
+            checks: (0..total)
+                .map(|i| check(&format!("Check-{}", i), if i < scored { 5.0 } else { -1.0 }))
+                .collect(),
+        }
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn confidence_combines_age_and_coverage() {
+        let today = day("2024-06-30");
+        let cases = [
+            (Some("2024-06-20"), 9, 10, Confidence::High),
+            (Some("2024-05-31"), 8, 10, Confidence::High),
+            (Some("2024-05-30"), 8, 10, Confidence::Medium),
+            (Some("2024-06-20"), 7, 10, Confidence::Medium),
+            (Some("2024-06-20"), 5, 10, Confidence::Medium),
+            (Some("2024-06-20"), 4, 10, Confidence::Low),
+            (Some("2024-01-02"), 10, 10, Confidence::Medium),
+            (Some("2023-12-01"), 10, 10, Confidence::Low),
+            (None, 10, 10, Confidence::Low),
+            (Some("2024-06-20"), 0, 0, Confidence::Low),
+        ];
+        for (date, scored, total, expected) in cases {
+            let result = scanned(date, scored, total);
+            assert_eq!(
+                confidence(&result, today),
+                expected,
+                "{:?} with {}/{} checks",
+                date,
+                scored,
+                total
+            );
+        }
+    }
+
+    #[test]
+    fn confidence_reads_rfc3339_dates() {
+        let result = scanned(Some("2024-06-20T04:10:53Z"), 10, 10);
+        assert_eq!(confidence(&result, day("2024-06-30")), Confidence::High);
+
+        let garbled = scanned(Some("last week"), 10, 10);
+        assert_eq!(confidence(&garbled, day("2024-06-30")), Confidence::Low);
+    }
+
+    fn weights(list: &[&str]) -> Vec<CheckWeight> {
+        list.iter().map(|w| w.parse().unwrap()).collect()
+    }
+
+    fn checks(checks: &[(&str, f64)]) -> ScorecardResult {
+        ScorecardResult {
+            date: Some("2024-06-20".to_string()),
+            score: Some(5.0),
+            checks: checks
+                .iter()
+                .map(|(name, score)| check(name, *score))
+                .collect(),
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("a weighted score");
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn unweighted_checks_average_evenly() {
+        let result = checks(&[("Code-Review", 8.0), ("Fuzzing", 0.0), ("License", 10.0)]);
+        assert_close(weighted_score(&result, &[]), 6.0);
+    }
+
+    #[test]
+    fn weights_scale_each_check() {
+        let result = checks(&[
+            ("Branch-Protection", 4.0),
+            ("Code-Review", 10.0),
+            ("Fuzzing", 0.0),
+        ]);
+        // (4×3 + 10×2 + 0×1) / (3 + 2 + 1)
+        let custom = weights(&["branch-protection=3", "Code-Review=2"]);
+        assert_close(weighted_score(&result, &custom), 32.0 / 6.0);
+
+        // A zero weight drops the check; the last entry for a check wins
+        let without_fuzzing = weights(&["Fuzzing=5", "fuzzing=0"]);
+        assert_close(weighted_score(&result, &without_fuzzing), 7.0);
+    }
+
+    #[test]
+    fn checks_without_data_are_left_out() {
+        let mut result = checks(&[("Code-Review", 6.0), ("Packaging", -1.0)]);
+        result.checks.push(Check {
+            name: "Signed-Releases".to_string(),
+            ..Check::default()
+        });
+        let custom = weights(&["Packaging=10", "Signed-Releases=10"]);
+        assert_close(weighted_score(&result, &custom), 6.0);
+    }
+
+    #[test]
+    fn no_weighted_data_has_no_score() {
+        assert_eq!(weighted_score(&checks(&[]), &[]), None);
+        let result = checks(&[("Fuzzing", 0.0), ("Packaging", -1.0)]);
+        assert_eq!(weighted_score(&result, &weights(&["Fuzzing=0"])), None);
+    }
+
+    #[test]
+    fn rejects_malformed_weights() {
+        for weight in [
+            "Fuzzing",
+            "Fuzzing=",
+            "Fuzzing=high",
+            "Fuzzing=-1",
+            "Fuzzing=inf",
+        ] {
+            assert!(weight.parse::<CheckWeight>().is_err(), "{:?}", weight);
+        }
+    }
+}
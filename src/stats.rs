@@ -0,0 +1,138 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::cache::{Cache, CacheKind};
+use crate::http::{HostStats, Http, SlowRequest};
+
+/// Wall-clock time of one phase of the run.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PhaseTiming {
+    pub phase: &'static str,
+    pub secs: f64,
+}
+
+/// Splits the run into consecutive phases, each ending when the next begins.
+pub struct Phases {
+    started: Instant,
+    timings: Vec<PhaseTiming>,
+}
+
+impl Phases {
+    pub fn start() -> Self {
+        Phases {
+            started: Instant::now(),
+            timings: Vec::new(),
+        }
+    }
+
+    /// End the current phase under `phase` and start the next one.
+    pub fn lap(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.timings.push(PhaseTiming {
+            phase,
+            secs: now.duration_since(self.started).as_secs_f64(),
+        });
+        self.started = now;
+    }
+}
+
+/// Cache lookups for one kind of cached response.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CacheStats {
+    pub kind: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// What a run did, as reported by `--stats`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RunStats {
+    pub phases: Vec<PhaseTiming>,
+    pub hosts: Vec<HostStats>,
+    pub cache: Vec<CacheStats>,
+    /// The slowest requests, slowest first.
+    pub slowest_requests: Vec<SlowRequest>,
+}
+
+impl RunStats {
+    pub fn collect(phases: Phases, http: &Http, cache: &Cache) -> Self {
+        RunStats {
+            phases: phases.timings,
+            hosts: http.stats(),
+            cache: CacheKind::ALL
+                .into_iter()
+                .map(|kind| {
+                    let (hits, misses) = cache.counters(kind);
+                    CacheStats {
+                        kind: kind.dir(),
+                        hits,
+                        misses,
+                    }
+                })
+                .filter(|stats| stats.hits + stats.misses > 0)
+                .collect(),
+            slowest_requests: http.slowest(),
+        }
+    }
+
+    /// Print the statistics to stderr.
+    pub fn print(&self) {
+        eprintln!("\nRun statistics:");
+        for timing in &self.phases {
+            eprintln!("  {}: {}", timing.phase, secs(timing.secs));
+        }
+
+        eprintln!("\nRequests:");
+        if self.hosts.is_empty() {
+            eprintln!("  none");
+        }
+        for host in &self.hosts {
+            eprintln!(
+                "  {}: {} requests, {} retries, {} waiting on limits \
+                 ({} blocked by concurrency, {} by rate)",
+                host.host,
+                host.requests,
+                host.retries,
+                secs(host.waited_secs),
+                host.concurrency_blocked,
+                host.rate_limited
+            );
+        }
+        if self.hosts.len() > 1 {
+            let total = |count: fn(&HostStats) -> u64| self.hosts.iter().map(count).sum::<u64>();
+            eprintln!(
+                "  total: {} requests, {} retries, {} blocked by concurrency, {} by rate",
+                total(|h| h.requests),
+                total(|h| h.retries),
+                total(|h| h.concurrency_blocked),
+                total(|h| h.rate_limited)
+            );
+        }
+
+        eprintln!("\nCache:");
+        for stats in &self.cache {
+            eprintln!(
+                "  {}: {} hits, {} misses",
+                stats.kind, stats.hits, stats.misses
+            );
+        }
+        if self.cache.len() > 1 {
+            let hits: u64 = self.cache.iter().map(|stats| stats.hits).sum();
+            let misses: u64 = self.cache.iter().map(|stats| stats.misses).sum();
+            eprintln!("  total: {} hits, {} misses", hits, misses);
+        }
+
+        if !self.slowest_requests.is_empty() {
+            eprintln!("\nSlowest requests:");
+            for request in &self.slowest_requests {
+                let crate_name = request.crate_name.as_deref().unwrap_or("-");
+                eprintln!("  {} {} ({})", secs(request.secs), request.url, crate_name);
+            }
+        }
+    }
+}
+
+fn secs(secs: f64) -> String {
+    format!("{:.2}s", secs)
+}
@@ -0,0 +1,415 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Result, anyhow};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cli::Args;
+use crate::policy::Violations;
+use crate::render::score_cell;
+use crate::score::{CrateScore, ScoreStatus};
+
+/// Rows moved by PageUp and PageDown.
+const PAGE: u16 = 20;
+
+/// The order rows are listed in; `s` cycles through them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Sort {
+    /// As collected, which follows the dependency tree.
+    Tree,
+    Name,
+    /// Lowest score first, crates without one last, like `--sort score`.
+    Score,
+    /// Highest risk first, like `--sort risk`.
+    Risk,
+}
+
+impl Sort {
+    fn next(self) -> Sort {
+        match self {
+            Sort::Tree => Sort::Name,
+            Sort::Name => Sort::Score,
+            Sort::Score => Sort::Risk,
+            Sort::Risk => Sort::Tree,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Sort::Tree => "tree",
+            Sort::Name => "name",
+            Sort::Score => "score",
+            Sort::Risk => "risk",
+        }
+    }
+}
+
+/// The browser's state: which rows are shown, in what order, and what is open.
+struct App<'a> {
+    scores: &'a [CrateScore],
+    args: &'a Args,
+    /// Indices into `scores` of the rows breaking the policy.
+    failing: HashSet<usize>,
+    /// Indices into `scores` matching `filter`, in `sort` order.
+    rows: Vec<usize>,
+    sort: Sort,
+    filter: String,
+    /// Whether keys go to the filter rather than the table.
+    editing: bool,
+    table: TableState,
+    /// The row whose details are open, and how far they are scrolled.
+    detail: Option<(usize, u16)>,
+    /// Dependency paths traced so far, or why tracing failed.
+    paths: HashMap<usize, Result<Vec<String>, String>>,
+}
+
+/// `--tui`: browse the results in a scrollable table, sorted with `s`, filtered after
+/// `/`, with a crate's checks, advisories and dependency path a press of Enter away.
+///
+/// `path` traces how a crate entered the tree; it runs when a crate is first opened,
+/// since it may need cargo.
+pub fn run(
+    scores: &[CrateScore],
+    violations: &Violations,
+    args: &Args,
+    mut path: impl FnMut(&CrateScore) -> Result<Vec<String>>,
+) -> Result<()> {
+    let failing: HashSet<*const CrateScore> = violations
+        .failing()
+        .into_iter()
+        .map(std::ptr::from_ref)
+        .collect();
+    let failing: HashSet<usize> = scores
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| failing.contains(&std::ptr::from_ref(*c)))
+        .map(|(index, _)| index)
+        .collect();
+    let mut app = App {
+        scores,
+        args,
+        failing,
+        rows: Vec::new(),
+        sort: Sort::Tree,
+        filter: String::new(),
+        editing: false,
+        table: TableState::default(),
+        detail: None,
+        paths: HashMap::new(),
+    };
+    app.refresh();
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal, &mut path);
+    ratatui::restore();
+    result
+}
+
+impl App<'_> {
+    fn event_loop(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        path: &mut impl FnMut(&CrateScore) -> Result<Vec<String>>,
+    ) -> Result<()> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(|e| anyhow!("Failed to draw the terminal: {}", e))?;
+            let event = event::read().map_err(|e| anyhow!("Failed to read a key: {}", e))?;
+            let Event::Key(key) = event else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(());
+            }
+            if self.editing {
+                self.edit_filter(key);
+            } else if let Some((index, scroll)) = self.detail {
+                self.detail = match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace | KeyCode::Left => None,
+                    KeyCode::Down | KeyCode::Char('j') => Some((index, scroll.saturating_add(1))),
+                    KeyCode::Up | KeyCode::Char('k') => Some((index, scroll.saturating_sub(1))),
+                    KeyCode::PageDown => Some((index, scroll.saturating_add(PAGE))),
+                    KeyCode::PageUp => Some((index, scroll.saturating_sub(PAGE))),
+                    _ => Some((index, scroll)),
+                };
+            } else {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Down | KeyCode::Char('j') => self.table.select_next(),
+                    KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+                    KeyCode::PageDown => self.table.scroll_down_by(PAGE),
+                    KeyCode::PageUp => self.table.scroll_up_by(PAGE),
+                    KeyCode::Home | KeyCode::Char('g') => self.table.select_first(),
+                    KeyCode::End | KeyCode::Char('G') => self.table.select_last(),
+                    KeyCode::Char('s') => {
+                        self.sort = self.sort.next();
+                        self.refresh();
+                    }
+                    KeyCode::Char('/') => self.editing = true,
+                    KeyCode::Enter | KeyCode::Right => {
+                        if let Some(&index) =
+                            self.table.selected().and_then(|row| self.rows.get(row))
+                        {
+                            self.paths.entry(index).or_insert_with(|| {
+                                path(&self.scores[index]).map_err(|e| e.to_string())
+                            });
+                            self.detail = Some((index, 0));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Keys typed after `/`: Enter keeps the filter, Esc drops it.
+    fn edit_filter(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.editing = false,
+            KeyCode::Esc => {
+                self.editing = false;
+                self.filter.clear();
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    /// Recompute the shown rows after the filter or sort changed, keeping the selected
+    /// crate selected when it is still shown.
+    fn refresh(&mut self) {
+        let selected = self
+            .table
+            .selected()
+            .and_then(|row| self.rows.get(row).copied());
+        let filter = self.filter.to_lowercase();
+        let mut rows: Vec<usize> = (0..self.scores.len())
+            .filter(|&index| filter.is_empty() || self.haystack(index).contains(&filter))
+            .collect();
+        let scores = self.scores;
+        match self.sort {
+            Sort::Tree => {}
+            Sort::Name => rows.sort_by(|&a, &b| {
+                (&scores[a].name, &scores[a].version).cmp(&(&scores[b].name, &scores[b].version))
+            }),
+            Sort::Score => rows.sort_by(|&a, &b| match (&scores[a].status, &scores[b].status) {
+                (ScoreStatus::Scored(a), ScoreStatus::Scored(b)) => a.total_cmp(b),
+                (ScoreStatus::Scored(_), _) => std::cmp::Ordering::Less,
+                (_, ScoreStatus::Scored(_)) => std::cmp::Ordering::Greater,
+                _ => std::cmp::Ordering::Equal,
+            }),
+            Sort::Risk => rows.sort_by(|&a, &b| match (scores[a].risk, scores[b].risk) {
+                (Some(a), Some(b)) => b.total_cmp(&a),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            }),
+        }
+        let row = selected
+            .and_then(|index| rows.iter().position(|&r| r == index))
+            .or((!rows.is_empty()).then_some(0));
+        self.rows = rows;
+        self.table.select(row);
+    }
+
+    /// What the filter matches against: name, version, repository and status.
+    fn haystack(&self, index: usize) -> String {
+        let c = &self.scores[index];
+        format!(
+            "{} {} {} {}",
+            c.name,
+            c.version,
+            c.repository.as_deref().unwrap_or(""),
+            c.category()
+        )
+        .to_lowercase()
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        match self.detail {
+            Some((index, scroll)) => self.draw_detail(frame, main, index, scroll),
+            None => self.draw_table(frame, main),
+        }
+
+        let footer_text = if self.editing {
+            Line::from(vec![
+                Span::raw("/"),
+                Span::raw(&self.filter),
+                Span::raw("▏"),
+            ])
+        } else if self.detail.is_some() {
+            Line::from("↑↓ scroll  Esc back  Ctrl-C quit").dim()
+        } else {
+            let filter = match self.filter.as_str() {
+                "" => String::new(),
+                filter => format!("  filter: {}", filter),
+            };
+            Line::from(format!(
+                "{} of {} crates  sorted by {}{}  ·  ↑↓ move  Enter details  s sort  / filter  q quit",
+                self.rows.len(),
+                self.scores.len(),
+                self.sort.label(),
+                filter
+            ))
+            .dim()
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
+        let header = Row::new([
+            "Crate",
+            "Version",
+            "Score",
+            "Status",
+            "Policy",
+            "Repository",
+        ])
+        .style(Style::new().bold());
+        let rows = self.rows.iter().map(|&index| {
+            let c = &self.scores[index];
+            let policy = if self.failing.contains(&index) {
+                Cell::from("fail").red()
+            } else {
+                Cell::from("")
+            };
+            Row::new([
+                Cell::from(c.name.as_str()),
+                Cell::from(c.version.as_str()),
+                Cell::from(score_cell(&c.status, self.args)).style(score_style(&c.status)),
+                Cell::from(c.category()),
+                policy,
+                Cell::from(c.repository.as_deref().unwrap_or("-")),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(2),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(16),
+            Constraint::Length(6),
+            Constraint::Fill(3),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(Block::bordered().title(" cargo scorecard "));
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect, index: usize, scroll: u16) {
+        let c = &self.scores[index];
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw("Score: ").bold(),
+                Span::styled(score_cell(&c.status, self.args), score_style(&c.status)),
+                Span::raw(format!("  ({})", c.category())),
+            ]),
+            Line::from(format!(
+                "Repository: {}{}",
+                c.repository.as_deref().unwrap_or("-"),
+                c.repository_inferred_from
+                    .as_ref()
+                    .map_or_else(String::new, |from| format!(" (inferred from {})", from))
+            )),
+        ];
+        if let Some(kind) = c.kind {
+            let direct = if c.direct { "direct" } else { "transitive" };
+            lines.push(Line::from(format!("Dependency: {}, {}", direct, kind)));
+        }
+        if let Some(published) = c.published_at {
+            lines.push(Line::from(format!("Published: {}", published.date_naive())));
+        }
+        if let Some(date) = c.scorecard.as_ref().and_then(|result| result.date()) {
+            lines.push(Line::from(format!("Last scanned: {}", date)));
+        }
+        if self.failing.contains(&index) {
+            lines.push(Line::from("Breaks the policy").red());
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("Checks").bold());
+        match &c.scorecard {
+            Some(result) if !result.checks.is_empty() => {
+                for check in &result.checks {
+                    let score = match check.score {
+                        Some(score) if score >= 0.0 => score.to_string(),
+                        _ => "-".to_string(),
+                    };
+                    lines.push(Line::from(vec![
+                        Span::raw(format!("  {:<24}", check.name)),
+                        Span::styled(format!("{:>3}", score), check_style(check.score)),
+                        Span::raw(format!("  {}", check.reason.as_deref().unwrap_or(""))),
+                    ]));
+                }
+            }
+            _ => lines.push(Line::from("  no check data").dim()),
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("Advisories").bold());
+        match &c.advisories {
+            None => lines.push(Line::from("  not checked (--advisories)").dim()),
+            Some(advisories) if advisories.is_empty() => lines.push(Line::from("  none")),
+            Some(advisories) => {
+                for advisory in advisories {
+                    lines.push(Line::from(vec![
+                        Span::raw("  "),
+                        Span::raw(advisory.id.as_str()).red(),
+                        Span::raw(format!("  {}", advisory.summary.as_deref().unwrap_or(""))),
+                    ]));
+                }
+            }
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("Dependency path").bold());
+        match self.paths.get(&index) {
+            Some(Ok(path)) if !path.is_empty() => {
+                for (depth, step) in path.iter().enumerate() {
+                    lines.push(Line::from(format!("  {}{}", "  ".repeat(depth), step)));
+                }
+            }
+            Some(Err(e)) => lines.push(Line::from(format!("  {}", e)).red()),
+            _ => lines.push(Line::from("  unknown").dim()),
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(Block::bordered().title(format!(" {} {} ", c.name, c.version)));
+        frame.render_widget(paragraph, area);
+    }
+}
+
+/// Red below 4, yellow below 7, green from 7, like a traffic light.
+fn score_style(status: &ScoreStatus) -> Style {
+    match status {
+        ScoreStatus::Scored(score) => check_style(Some(*score)),
+        _ => Style::new().dim(),
+    }
+}
+
+fn check_style(score: Option<f64>) -> Style {
+    match score {
+        Some(score) if score < 0.0 => Style::new().dim(),
+        Some(score) if score < 4.0 => Style::new().fg(Color::Red),
+        Some(score) if score < 7.0 => Style::new().fg(Color::Yellow),
+        Some(_) => Style::new().fg(Color::Green),
+        None => Style::new().dim(),
+    }
+}
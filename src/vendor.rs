@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::deps::{BuildTime, CrateInfo, Vendored};
+
+/// The parts of a vendored crate's Cargo.toml we use.
+#[derive(Deserialize)]
+struct Manifest {
+    package: Package,
+    lib: Option<Lib>,
+}
+
+#[derive(Deserialize)]
+struct Lib {
+    #[serde(rename = "proc-macro", default)]
+    proc_macro: bool,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    version: String,
+    repository: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    /// A path to the build script, or `false` to disable the `build.rs` default.
+    build: Option<toml::Value>,
+}
+
+/// `.cargo-checksum.json`, written by `cargo vendor` next to each crate.
+#[derive(Default, Deserialize)]
+struct Checksum {
+    package: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Lockfile {
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    checksum: Option<String>,
+}
+
+/// The dependency tables of the project's own Cargo.toml, used to tell direct
+/// dependencies apart without running cargo.
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct RootManifest {
+    dependencies: BTreeMap<String, toml::Value>,
+    dev_dependencies: BTreeMap<String, toml::Value>,
+    build_dependencies: BTreeMap<String, toml::Value>,
+    workspace: Option<RootWorkspace>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RootWorkspace {
+    dependencies: BTreeMap<String, toml::Value>,
+}
+
+/// Enumerate the crates in a `cargo vendor` directory without invoking cargo.
+///
+/// Versions and links come from each crate's own Cargo.toml. Crates that are not in
+/// `lockfile`, or whose recorded checksum differs from it, are marked as not locked.
+pub fn get_dependencies(
+    vendor_dir: &Path,
+    manifest: &Path,
+    lockfile: &Path,
+) -> Result<Vec<CrateInfo>> {
+    let locked = read_lockfile(lockfile)?;
+    let direct = direct_dependencies(manifest);
+
+    let entries = std::fs::read_dir(vendor_dir)
+        .map_err(|e| anyhow!("Failed to read {}: {}", vendor_dir.display(), e))?;
+    let mut crates = Vec::new();
+    for entry in entries {
+        let dir = entry
+            .map_err(|e| anyhow!("Failed to read {}: {}", vendor_dir.display(), e))?
+            .path();
+        let manifest_path = dir.join("Cargo.toml");
+        if !manifest_path.is_file() {
+            debug!(path = %dir.display(), "skipping vendor entry without a Cargo.toml");
+            continue;
+        }
+
+        let content = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", manifest_path.display(), e))?;
+        let manifest = toml::from_str::<Manifest>(&content)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", manifest_path.display(), e))?;
+        let build_time = BuildTime {
+            build_script: match &manifest.package.build {
+                Some(toml::Value::Boolean(enabled)) => *enabled && dir.join("build.rs").is_file(),
+                Some(_) => true,
+                None => dir.join("build.rs").is_file(),
+            },
+            proc_macro: manifest.lib.is_some_and(|lib| lib.proc_macro),
+        };
+        let package = manifest.package;
+        let checksum = std::fs::read_to_string(dir.join(".cargo-checksum.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Checksum>(&content).ok())
+            .unwrap_or_default();
+
+        let in_lockfile = match locked.get(&(package.name.clone(), package.version.clone())) {
+            Some(Some(expected)) => checksum.package.as_ref().is_none_or(|sum| sum == expected),
+            Some(None) => true,
+            None => false,
+        };
+        if !in_lockfile {
+            warn!(
+                "vendored crate {} {} does not match Cargo.lock",
+                package.name, package.version
+            );
+        }
+
+        crates.push(CrateInfo {
+            direct: direct.contains(&package.name),
+            kind: None,
+            version: format!("v{}", package.version),
+            name: package.name,
+            vendored: Some(Vendored {
+                repository: package.repository,
+                homepage: package.homepage,
+                documentation: package.documentation,
+                in_lockfile,
+            }),
+            depth: None,
+            introduced_by: None,
+            projects: Vec::new(),
+            build_time,
+            unique_dependencies: None,
+            reach: None,
+            pinned_from: None,
+            listed_repository: None,
+            mapped_repository: None,
+            changed: None,
+            source: None,
+        });
+    }
+
+    crates.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    Ok(crates)
+}
+
+/// Locked packages keyed by name and version, with their registry checksum.
+fn read_lockfile(path: &Path) -> Result<HashMap<(String, String), Option<String>>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let lockfile: Lockfile = toml::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(lockfile
+        .package
+        .into_iter()
+        .map(|package| ((package.name, package.version), package.checksum))
+        .collect())
+}
+
+/// Package names the root manifest depends on, honoring `package = "..."` renames.
+fn direct_dependencies(manifest: &Path) -> HashSet<String> {
+    let Some(root) = std::fs::read_to_string(manifest)
+        .ok()
+        .and_then(|content| toml::from_str::<RootManifest>(&content).ok())
+    else {
+        warn!(
+            "could not read {}; no crate will be marked direct",
+            manifest.display()
+        );
+        return HashSet::new();
+    };
+
+    let workspace = root.workspace.unwrap_or_default();
+    [
+        root.dependencies,
+        root.dev_dependencies,
+        root.build_dependencies,
+        workspace.dependencies,
+    ]
+    .into_iter()
+    .flatten()
+    .map(
+        |(key, spec)| match spec.get("package").and_then(|p| p.as_str()) {
+            Some(package) => package.to_string(),
+            None => key,
+        },
+    )
+    .collect()
+}
@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tracing::{debug, warn};
+
+/// Quiet time after the last lockfile change before scoring again, so a `cargo update`
+/// touching the file several times triggers a single run.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Clear the terminal and move the cursor home.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Run `score` now and again after every change to the lockfile of any of `manifests`,
+/// until the process is interrupted.
+///
+/// The lockfiles' directories are watched rather than the files themselves, so the
+/// watch survives a lockfile being deleted and written again. A failing run is
+/// reported and the watch goes on.
+pub fn watch(
+    manifests: &[&Path],
+    clear: bool,
+    mut score: impl FnMut() -> Result<ExitCode>,
+) -> Result<ExitCode> {
+    let lockfiles: Vec<PathBuf> = manifests.iter().map(|m| lockfile_for(m)).collect();
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)
+        .map_err(|e| anyhow!("Failed to watch Cargo.lock: {}", e))?;
+    for lockfile in &lockfiles {
+        let dir = lockfile.parent().unwrap_or(Path::new("."));
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| anyhow!("Failed to watch {}: {}", dir.display(), e))?;
+    }
+
+    loop {
+        if clear {
+            print!("{}", CLEAR_SCREEN);
+        }
+        match score() {
+            Ok(code) => debug!(?code, "watch run finished"),
+            Err(e) => eprintln!("Error: {:?}", e),
+        }
+        let watched: Vec<String> = lockfiles.iter().map(|l| l.display().to_string()).collect();
+        println!(
+            "\nWatching {} for changes; press Ctrl-C to stop",
+            watched.join(", ")
+        );
+        wait_for_change(&receiver, &lockfiles)?;
+    }
+}
+
+/// Block until one of `lockfiles` changes and then stays unchanged for [`DEBOUNCE`].
+fn wait_for_change(
+    receiver: &Receiver<notify::Result<Event>>,
+    lockfiles: &[PathBuf],
+) -> Result<()> {
+    let closed = || anyhow!("The Cargo.lock watcher stopped unexpectedly");
+    let touches_lockfile = |event: notify::Result<Event>| match event {
+        Ok(event) => {
+            !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|path| lockfiles.contains(path))
+        }
+        Err(e) => {
+            warn!("Error watching Cargo.lock: {}", e);
+            false
+        }
+    };
+
+    while !touches_lockfile(receiver.recv().map_err(|_| closed())?) {}
+    loop {
+        match receiver.recv_timeout(DEBOUNCE) {
+            Ok(event) => {
+                touches_lockfile(event);
+            }
+            Err(RecvTimeoutError::Timeout) => return Ok(()),
+            Err(RecvTimeoutError::Disconnected) => return Err(closed()),
+        }
+    }
+}
+
+/// The lockfile cargo uses for `manifest`: the nearest Cargo.lock in its directory or
+/// above, as workspace members share the workspace root's.
+///
+/// Paths are absolute, to match the paths in watch events; without any lockfile yet,
+/// the one cargo would create next to the manifest.
+fn lockfile_for(manifest: &Path) -> PathBuf {
+    let dir = manifest
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    dir.ancestors()
+        .map(|ancestor| ancestor.join("Cargo.lock"))
+        .find(|lockfile| lockfile.is_file())
+        .unwrap_or_else(|| dir.join("Cargo.lock"))
+}